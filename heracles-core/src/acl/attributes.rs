@@ -5,7 +5,42 @@
 //! "contact", "security") rather than individual attribute checkboxes.
 
 use serde::{Deserialize, Serialize};
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
+
+/// Predicate over an entry's current attribute values.
+///
+/// Used by conditional attribute rules, e.g. "can read `employeeNumber`
+/// only if it is already set". Attribute names match case-insensitively.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AttributeCondition {
+    /// The attribute has at least one value.
+    Present(String),
+    /// The attribute has no values.
+    Absent(String),
+    /// The attribute has the given value (case-insensitive).
+    Equals(String, String),
+}
+
+impl AttributeCondition {
+    /// Evaluate this condition against an entry's attributes.
+    pub fn evaluate(&self, entry: &HashMap<String, Vec<String>>) -> bool {
+        let values = |attr: &str| {
+            entry
+                .iter()
+                .find(|(k, _)| k.eq_ignore_ascii_case(attr))
+                .map(|(_, v)| v.as_slice())
+                .unwrap_or(&[])
+        };
+
+        match self {
+            AttributeCondition::Present(attr) => !values(attr).is_empty(),
+            AttributeCondition::Absent(attr) => values(attr).is_empty(),
+            AttributeCondition::Equals(attr, value) => {
+                values(attr).iter().any(|v| v.eq_ignore_ascii_case(value))
+            }
+        }
+    }
+}
 
 /// Resolved attribute rules for one (object_type, action) pair.
 ///
@@ -43,6 +78,10 @@ pub struct AttributeFilter {
     allowed: Option<HashSet<String>>,
     /// Always applied — these attributes are stripped regardless.
     denied: HashSet<String>,
+    /// Conditional allows: the attribute is permitted only when one of its
+    /// conditions holds for the entry being checked. Deny still wins.
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    conditions: HashMap<String, Vec<AttributeCondition>>,
 }
 
 impl AttributeFilter {
@@ -58,7 +97,11 @@ impl AttributeFilter {
         let allowed = allowed.map(|set| set.into_iter().map(|s| s.to_ascii_lowercase()).collect());
         let denied = denied.into_iter().map(|s| s.to_ascii_lowercase()).collect();
 
-        Self { allowed, denied }
+        Self {
+            allowed,
+            denied,
+            conditions: HashMap::new(),
+        }
     }
 
     /// Create a filter that allows all attributes (no restrictions).
@@ -66,6 +109,7 @@ impl AttributeFilter {
         Self {
             allowed: None,
             denied: HashSet::new(),
+            conditions: HashMap::new(),
         }
     }
 
@@ -74,6 +118,7 @@ impl AttributeFilter {
         Self {
             allowed: Some(HashSet::new()),
             denied: HashSet::new(),
+            conditions: HashMap::new(),
         }
     }
 
@@ -90,6 +135,7 @@ impl AttributeFilter {
         Self {
             allowed: Some(allowed),
             denied: HashSet::new(),
+            conditions: HashMap::new(),
         }
     }
 
@@ -106,6 +152,7 @@ impl AttributeFilter {
         Self {
             allowed: None,
             denied,
+            conditions: HashMap::new(),
         }
     }
 
@@ -114,8 +161,10 @@ impl AttributeFilter {
     /// # Rules
     ///
     /// 1. If attribute is in deny list → denied
-    /// 2. If no allow list (None) → allowed
-    /// 3. If allow list exists → must be in allow list
+    /// 2. If attribute has conditional rules → denied, as there is no entry
+    ///    to test them against (see [`is_attribute_permitted_for`](Self::is_attribute_permitted_for))
+    /// 3. If no allow list (None) → allowed
+    /// 4. If allow list exists → must be in allow list
     pub fn is_attribute_permitted(&self, attr: &str) -> bool {
         let attr_lower = attr.to_ascii_lowercase();
 
@@ -124,6 +173,10 @@ impl AttributeFilter {
             return false;
         }
 
+        if self.conditions.contains_key(&attr_lower) {
+            return false;
+        }
+
         // If no whitelist, everything (not denied) is allowed
         match &self.allowed {
             None => true,
@@ -131,6 +184,63 @@ impl AttributeFilter {
         }
     }
 
+    /// Check if an attribute is permitted given the entry's current values.
+    ///
+    /// Like [`is_attribute_permitted`](Self::is_attribute_permitted), but an
+    /// attribute with conditional rules is permitted only if one of its
+    /// conditions holds for `entry`.
+    pub fn is_attribute_permitted_for(
+        &self,
+        attr: &str,
+        entry: &HashMap<String, Vec<String>>,
+    ) -> bool {
+        let attr_lower = attr.to_ascii_lowercase();
+
+        if self.denied.contains(&attr_lower) {
+            return false;
+        }
+
+        if let Some(conditions) = self.conditions.get(&attr_lower) {
+            return conditions.iter().any(|c| c.evaluate(entry));
+        }
+
+        self.is_attribute_permitted(attr)
+    }
+
+    /// Filter a list of attributes against the entry's current values.
+    pub fn filter_attributes_for<'a>(
+        &self,
+        attrs: &[&'a str],
+        entry: &HashMap<String, Vec<String>>,
+    ) -> Vec<&'a str> {
+        attrs
+            .iter()
+            .copied()
+            .filter(|attr| self.is_attribute_permitted_for(attr, entry))
+            .collect()
+    }
+
+    /// Make an attribute's permission conditional on the entry's values.
+    ///
+    /// Multiple conditions for the same attribute are OR'ed.
+    pub fn add_condition(&mut self, attr: &str, condition: AttributeCondition) {
+        self.conditions
+            .entry(attr.to_ascii_lowercase())
+            .or_default()
+            .push(condition);
+    }
+
+    /// Builder form of [`add_condition`](Self::add_condition).
+    pub fn with_condition(mut self, attr: &str, condition: AttributeCondition) -> Self {
+        self.add_condition(attr, condition);
+        self
+    }
+
+    /// Get the conditional rules, keyed by lowercase attribute name.
+    pub fn conditions(&self) -> &HashMap<String, Vec<AttributeCondition>> {
+        &self.conditions
+    }
+
     /// Filter a list of attributes, returning only permitted ones.
     pub fn filter_attributes<'a>(&self, attrs: &[&'a str]) -> Vec<&'a str> {
         attrs
@@ -166,7 +276,7 @@ impl AttributeFilter {
 
     /// Check if this filter allows all attributes (no restrictions).
     pub fn is_allow_all(&self) -> bool {
-        self.allowed.is_none() && self.denied.is_empty()
+        self.allowed.is_none() && self.denied.is_empty() && self.conditions.is_empty()
     }
 
     /// Merge another filter into this one.
@@ -180,6 +290,14 @@ impl AttributeFilter {
         // Merge denies (union — if ANY policy denies it, denied)
         self.denied = self.denied.union(&other.denied).cloned().collect();
 
+        // Merge conditions (OR'ed per attribute)
+        for (attr, conditions) in &other.conditions {
+            self.conditions
+                .entry(attr.clone())
+                .or_default()
+                .extend(conditions.iter().cloned());
+        }
+
         // Merge allows (union — expand what's allowed)
         match (&mut self.allowed, &other.allowed) {
             // Both have whitelists: union them
@@ -449,4 +567,78 @@ mod tests {
         assert!(!filter.is_attribute_permitted("userPassword"));
         assert!(filter.is_attribute_permitted("cn"));
     }
+
+    fn entry(pairs: &[(&str, &[&str])]) -> HashMap<String, Vec<String>> {
+        pairs
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.iter().map(|s| s.to_string()).collect()))
+            .collect()
+    }
+
+    #[test]
+    fn test_conditional_allow_requires_present() {
+        let filter = AttributeFilter::with_allowed(["cn"]).with_condition(
+            "employeeNumber",
+            AttributeCondition::Present("employeeNumber".into()),
+        );
+
+        let with_number = entry(&[("cn", &["John"]), ("employeeNumber", &["42"])]);
+        let without_number = entry(&[("cn", &["John"])]);
+
+        assert!(filter.is_attribute_permitted_for("employeeNumber", &with_number));
+        assert!(!filter.is_attribute_permitted_for("employeeNumber", &without_number));
+        assert!(filter.is_attribute_permitted_for("cn", &without_number));
+        assert_eq!(
+            filter.filter_attributes_for(&["cn", "employeeNumber"], &without_number),
+            vec!["cn"]
+        );
+    }
+
+    #[test]
+    fn test_conditional_denied_without_entry() {
+        let filter = AttributeFilter::allow_all().with_condition(
+            "employeeNumber",
+            AttributeCondition::Present("employeeNumber".into()),
+        );
+
+        assert!(!filter.is_attribute_permitted("employeeNumber"));
+        assert!(filter.is_attribute_permitted("cn"));
+        assert_eq!(
+            filter.filter_attributes(&["cn", "employeeNumber"]),
+            vec!["cn"]
+        );
+    }
+
+    #[test]
+    fn test_conditional_deny_wins() {
+        let mut filter = AttributeFilter::with_denied(["employeeNumber"]);
+        filter.add_condition(
+            "employeeNumber",
+            AttributeCondition::Present("employeeNumber".into()),
+        );
+
+        let e = entry(&[("employeeNumber", &["42"])]);
+        assert!(!filter.is_attribute_permitted_for("employeeNumber", &e));
+    }
+
+    #[test]
+    fn test_attribute_condition_evaluate() {
+        let e = entry(&[("objectClass", &["inetOrgPerson", "posixAccount"])]);
+
+        assert!(
+            AttributeCondition::Equals("objectclass".into(), "POSIXACCOUNT".into()).evaluate(&e)
+        );
+        assert!(AttributeCondition::Absent("mail".into()).evaluate(&e));
+        assert!(!AttributeCondition::Present("mail".into()).evaluate(&e));
+    }
+
+    #[test]
+    fn test_merge_keeps_conditions() {
+        let conditional = AttributeFilter::allow_all()
+            .with_condition("mail", AttributeCondition::Present("mail".into()));
+        let merged = AttributeFilter::allow_all().merged(&conditional);
+
+        assert!(!merged.is_allow_all());
+        assert!(!merged.is_attribute_permitted_for("mail", &HashMap::new()));
+    }
 }
@@ -0,0 +1,414 @@
+//! Growable permission set for deployments exceeding 128 permissions.
+//!
+//! `BigPermissionSet` mirrors the `PermissionBitmap` API over a `Vec<u64>`
+//! of words. The low 128 bits use the same layout as `PermissionBitmap`, so
+//! values convert losslessly in both directions (within 128 bits) and share
+//! the `to_halves`/`from_halves` storage format for those bits.
+
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+use super::bitmap::PermissionBitmap;
+
+/// Operations shared by permission set representations.
+///
+/// Lets ACL evaluation be written once for both the fixed 128-bit
+/// `PermissionBitmap` and the growable `BigPermissionSet`.
+pub trait PermissionSet: Clone + Default + PartialEq {
+    /// Check if ALL bits in `required` are set.
+    fn has(&self, required: &Self) -> bool;
+
+    /// Check if ANY bit in `required` is set.
+    fn has_any(&self, required: &Self) -> bool;
+
+    /// Union (OR) of two sets.
+    fn union(&self, other: &Self) -> Self;
+
+    /// Remove bits present in `other` (AND NOT).
+    fn subtract(&self, other: &Self) -> Self;
+
+    /// Check if no permissions are set.
+    fn is_empty(&self) -> bool;
+}
+
+impl PermissionSet for PermissionBitmap {
+    fn has(&self, required: &Self) -> bool {
+        PermissionBitmap::has(*self, *required)
+    }
+
+    fn has_any(&self, required: &Self) -> bool {
+        PermissionBitmap::has_any(*self, *required)
+    }
+
+    fn union(&self, other: &Self) -> Self {
+        PermissionBitmap::union(*self, *other)
+    }
+
+    fn subtract(&self, other: &Self) -> Self {
+        PermissionBitmap::subtract(*self, *other)
+    }
+
+    fn is_empty(&self) -> bool {
+        PermissionBitmap::is_empty(*self)
+    }
+}
+
+/// A permission set with no upper bound on bit positions.
+///
+/// Trailing zero words are always trimmed, so equal sets compare and hash
+/// equal regardless of how they were built.
+///
+/// # Example
+///
+/// ```rust
+/// use heracles_core::acl::BigPermissionSet;
+///
+/// let user = BigPermissionSet::from_bits(&[0, 130, 300]);
+/// assert!(user.has(&BigPermissionSet::from_bit(130)));
+/// assert!(!user.has(&BigPermissionSet::from_bit(131)));
+/// ```
+#[derive(Clone, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(from = "RawWords")]
+pub struct BigPermissionSet {
+    words: Vec<u64>,
+}
+
+/// Serialized form of [`BigPermissionSet`], trimmed on the way in.
+#[derive(Deserialize)]
+struct RawWords {
+    words: Vec<u64>,
+}
+
+impl From<RawWords> for BigPermissionSet {
+    fn from(raw: RawWords) -> Self {
+        Self::from_words(raw.words)
+    }
+}
+
+impl BigPermissionSet {
+    /// Create a new empty set.
+    pub fn new() -> Self {
+        Self { words: Vec::new() }
+    }
+
+    /// Create a set with a single bit set at the given position.
+    pub fn from_bit(pos: u32) -> Self {
+        Self::new().set_bit(pos)
+    }
+
+    /// Create a set from multiple bit positions.
+    pub fn from_bits(positions: &[u32]) -> Self {
+        positions
+            .iter()
+            .fold(Self::new(), |set, &pos| set.set_bit(pos))
+    }
+
+    /// Reconstruct the low 128 bits from two i64 halves.
+    ///
+    /// Same layout as [`PermissionBitmap::from_halves`].
+    pub fn from_halves(low: i64, high: i64) -> Self {
+        PermissionBitmap::from_halves(low, high).into()
+    }
+
+    /// Split the low 128 bits into two i64 halves.
+    ///
+    /// Bits at position 128 and above are not represented; check
+    /// [`fits_bitmap`](Self::fits_bitmap) first when that matters.
+    pub fn to_halves(&self) -> (i64, i64) {
+        self.low_bitmap().to_halves()
+    }
+
+    /// Returns the low 128 bits as a `PermissionBitmap`.
+    pub fn low_bitmap(&self) -> PermissionBitmap {
+        let lo = self.word(0) as u128;
+        let hi = (self.word(1) as u128) << 64;
+        PermissionBitmap::from_raw(lo | hi)
+    }
+
+    /// Returns true if no bit at position 128 or above is set.
+    pub fn fits_bitmap(&self) -> bool {
+        self.words.len() <= 2
+    }
+
+    /// Check if ALL bits in `required` are set.
+    pub fn has(&self, required: &Self) -> bool {
+        required
+            .words
+            .iter()
+            .enumerate()
+            .all(|(i, &w)| self.word(i) & w == w)
+    }
+
+    /// Check if ANY bit in `required` is set.
+    pub fn has_any(&self, required: &Self) -> bool {
+        required
+            .words
+            .iter()
+            .enumerate()
+            .any(|(i, &w)| self.word(i) & w != 0)
+    }
+
+    /// Check if a specific bit position is set.
+    pub fn has_bit(&self, pos: u32) -> bool {
+        let (index, mask) = Self::locate(pos);
+        self.word(index) & mask != 0
+    }
+
+    /// Union (OR) two sets - combines permissions.
+    pub fn union(&self, other: &Self) -> Self {
+        let len = self.words.len().max(other.words.len());
+        Self::from_words((0..len).map(|i| self.word(i) | other.word(i)).collect())
+    }
+
+    /// Intersection (AND) two sets - common permissions only.
+    pub fn intersection(&self, other: &Self) -> Self {
+        let len = self.words.len().min(other.words.len());
+        Self::from_words((0..len).map(|i| self.word(i) & other.word(i)).collect())
+    }
+
+    /// Subtract (AND NOT) — remove bits present in `other`.
+    pub fn subtract(&self, other: &Self) -> Self {
+        Self::from_words(
+            self.words
+                .iter()
+                .enumerate()
+                .map(|(i, &w)| w & !other.word(i))
+                .collect(),
+        )
+    }
+
+    /// Check if the set is empty (no permissions set).
+    pub fn is_empty(&self) -> bool {
+        self.words.is_empty()
+    }
+
+    /// Count the number of set bits (permissions).
+    pub fn count(&self) -> u32 {
+        self.words.iter().map(|w| w.count_ones()).sum()
+    }
+
+    /// Set a bit at the given position.
+    pub fn set_bit(mut self, pos: u32) -> Self {
+        let (index, mask) = Self::locate(pos);
+        if self.words.len() <= index {
+            self.words.resize(index + 1, 0);
+        }
+        self.words[index] |= mask;
+        self
+    }
+
+    /// Clear a bit at the given position.
+    pub fn clear_bit(mut self, pos: u32) -> Self {
+        let (index, mask) = Self::locate(pos);
+        if let Some(word) = self.words.get_mut(index) {
+            *word &= !mask;
+        }
+        self.trim();
+        self
+    }
+
+    /// Get all set bit positions.
+    pub fn to_bits(&self) -> Vec<u32> {
+        let mut positions = Vec::new();
+        for (i, &word) in self.words.iter().enumerate() {
+            let mut bits = word;
+            while bits != 0 {
+                let offset = bits.trailing_zeros();
+                positions.push(i as u32 * 64 + offset);
+                bits &= bits - 1;
+            }
+        }
+        positions
+    }
+
+    fn locate(pos: u32) -> (usize, u64) {
+        ((pos / 64) as usize, 1u64 << (pos % 64))
+    }
+
+    fn word(&self, index: usize) -> u64 {
+        self.words.get(index).copied().unwrap_or(0)
+    }
+
+    fn from_words(words: Vec<u64>) -> Self {
+        let mut set = Self { words };
+        set.trim();
+        set
+    }
+
+    fn trim(&mut self) {
+        while self.words.last() == Some(&0) {
+            self.words.pop();
+        }
+    }
+}
+
+impl From<PermissionBitmap> for BigPermissionSet {
+    fn from(bitmap: PermissionBitmap) -> Self {
+        let bits = bitmap.as_raw();
+        Self::from_words(vec![bits as u64, (bits >> 64) as u64])
+    }
+}
+
+impl PermissionSet for BigPermissionSet {
+    fn has(&self, required: &Self) -> bool {
+        BigPermissionSet::has(self, required)
+    }
+
+    fn has_any(&self, required: &Self) -> bool {
+        BigPermissionSet::has_any(self, required)
+    }
+
+    fn union(&self, other: &Self) -> Self {
+        BigPermissionSet::union(self, other)
+    }
+
+    fn subtract(&self, other: &Self) -> Self {
+        BigPermissionSet::subtract(self, other)
+    }
+
+    fn is_empty(&self) -> bool {
+        BigPermissionSet::is_empty(self)
+    }
+}
+
+impl fmt::Debug for BigPermissionSet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "BigPermissionSet({:?})", self.to_bits())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_empty_set() {
+        let empty = BigPermissionSet::new();
+        assert!(empty.is_empty());
+        assert_eq!(empty.count(), 0);
+        assert!(empty.fits_bitmap());
+    }
+
+    #[test]
+    fn test_from_bit_beyond_128() {
+        let perm = BigPermissionSet::from_bit(200);
+        assert!(perm.has_bit(200));
+        assert!(!perm.has_bit(199));
+        assert!(!perm.has_bit(1000));
+        assert_eq!(perm.count(), 1);
+        assert!(!perm.fits_bitmap());
+    }
+
+    #[test]
+    fn test_has_all() {
+        let user = BigPermissionSet::from_bits(&[0, 128, 129, 300]);
+
+        assert!(user.has(&BigPermissionSet::from_bits(&[128, 300])));
+        assert!(!user.has(&BigPermissionSet::from_bits(&[128, 301])));
+    }
+
+    #[test]
+    fn test_has_any() {
+        let user = BigPermissionSet::from_bits(&[128, 129, 130]);
+
+        assert!(user.has_any(&BigPermissionSet::from_bits(&[5, 129, 500])));
+        assert!(!user.has_any(&BigPermissionSet::from_bits(&[5, 131, 500])));
+    }
+
+    #[test]
+    fn test_union() {
+        let a = BigPermissionSet::from_bits(&[128, 129]);
+        let b = BigPermissionSet::from_bits(&[129, 256]);
+        let combined = a.union(&b);
+
+        assert_eq!(combined.to_bits(), vec![128, 129, 256]);
+        assert_eq!(combined.count(), 3);
+    }
+
+    #[test]
+    fn test_subtract_trims() {
+        let allowed = BigPermissionSet::from_bits(&[0, 130, 400]);
+        let denied = BigPermissionSet::from_bits(&[400]);
+        let effective = allowed.subtract(&denied);
+
+        assert_eq!(effective, BigPermissionSet::from_bits(&[0, 130]));
+        assert!(!effective.has_bit(400));
+    }
+
+    #[test]
+    fn test_intersection_and_clear_bit() {
+        let a = BigPermissionSet::from_bits(&[1, 200, 300]);
+        let b = BigPermissionSet::from_bits(&[200, 300]);
+        assert_eq!(a.intersection(&b), b);
+
+        assert_eq!(
+            b.clone().clear_bit(300).clear_bit(200),
+            BigPermissionSet::new()
+        );
+    }
+
+    #[test]
+    fn test_bitmap_interop() {
+        let bitmap = PermissionBitmap::from_bits(&[0, 63, 64, 127]);
+        let big = BigPermissionSet::from(bitmap);
+
+        assert_eq!(big.to_bits(), vec![0, 63, 64, 127]);
+        assert!(big.fits_bitmap());
+        assert_eq!(big.low_bitmap(), bitmap);
+        assert_eq!(big.to_halves(), bitmap.to_halves());
+
+        let (low, high) = bitmap.to_halves();
+        assert_eq!(BigPermissionSet::from_halves(low, high), big);
+    }
+
+    #[test]
+    fn test_halves_ignore_high_bits() {
+        let big = BigPermissionSet::from_bits(&[5, 140]);
+        assert_eq!(big.low_bitmap(), PermissionBitmap::from_bit(5));
+    }
+
+    #[test]
+    fn test_permission_set_trait() {
+        fn allowed<P: PermissionSet>(granted: &P, denied: &P, required: &P) -> bool {
+            granted.subtract(denied).has(required)
+        }
+
+        let granted = BigPermissionSet::from_bits(&[1, 129]);
+        assert!(allowed(
+            &granted,
+            &BigPermissionSet::new(),
+            &BigPermissionSet::from_bit(129)
+        ));
+        assert!(!allowed(
+            &granted,
+            &BigPermissionSet::from_bit(129),
+            &BigPermissionSet::from_bit(129)
+        ));
+
+        let bitmap = PermissionBitmap::from_bits(&[1, 2]);
+        assert!(allowed(
+            &bitmap,
+            &PermissionBitmap::from_bit(2),
+            &PermissionBitmap::from_bit(1)
+        ));
+    }
+
+    #[test]
+    fn test_serde_roundtrip() {
+        let original = BigPermissionSet::from_bits(&[3, 190]);
+        let json = serde_json::to_string(&original).unwrap();
+        let restored: BigPermissionSet = serde_json::from_str(&json).unwrap();
+        assert_eq!(original, restored);
+    }
+
+    #[test]
+    fn test_serde_trims_trailing_zero_words() {
+        let empty: BigPermissionSet = serde_json::from_str(r#"{"words":[0]}"#).unwrap();
+        assert_eq!(empty, BigPermissionSet::default());
+        assert!(empty.is_empty());
+
+        let padded: BigPermissionSet = serde_json::from_str(r#"{"words":[8,0,0]}"#).unwrap();
+        assert_eq!(padded, BigPermissionSet::from_bit(3));
+        assert_eq!(serde_json::to_string(&padded).unwrap(), r#"{"words":[8]}"#);
+    }
+}
@@ -3,9 +3,12 @@
 //! Supports up to 128 distinct permissions encoded as bit positions.
 //! Operations are O(1) CPU instructions (AND, OR, XOR).
 
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
 use serde::{Deserialize, Serialize};
 use std::fmt;
 
+use crate::errors::{HeraclesError, Result};
+
 /// A set of permissions encoded as a u128 bitmap.
 ///
 /// Supports up to 128 permissions. Each permission is assigned a stable
@@ -250,17 +253,39 @@ impl PermissionBitmap {
 
     /// Get all set bit positions.
     pub fn to_bits(self) -> Vec<u8> {
-        let mut positions = Vec::new();
-        let mut bits = self.bits;
-        let mut pos = 0u8;
-        while bits != 0 {
-            if bits & 1 != 0 {
-                positions.push(pos);
-            }
-            bits >>= 1;
-            pos += 1;
-        }
-        positions
+        self.iter().collect()
+    }
+
+    /// Iterate over set bit positions in ascending order, without allocating.
+    pub fn iter(&self) -> Bits {
+        Bits { bits: self.bits }
+    }
+
+    /// Encode as an unpadded base64url token (22 chars) for URLs and JWTs.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use heracles_core::acl::PermissionBitmap;
+    ///
+    /// let perms = PermissionBitmap::from_bits(&[0, 5, 127]);
+    /// let token = perms.to_base64url();
+    /// assert_eq!(token.len(), 22);
+    /// assert_eq!(PermissionBitmap::from_base64url(&token).unwrap(), perms);
+    /// ```
+    pub fn to_base64url(self) -> String {
+        URL_SAFE_NO_PAD.encode(self.bits.to_be_bytes())
+    }
+
+    /// Decode a token produced by [`to_base64url`](Self::to_base64url).
+    pub fn from_base64url(token: &str) -> Result<Self> {
+        let bytes = URL_SAFE_NO_PAD
+            .decode(token)
+            .map_err(|e| HeraclesError::InvalidPermissions(e.to_string()))?;
+        let bytes: [u8; 16] = bytes.try_into().map_err(|b: Vec<u8>| {
+            HeraclesError::InvalidPermissions(format!("expected 16 bytes, got {}", b.len()))
+        })?;
+        Ok(Self::from_raw(u128::from_be_bytes(bytes)))
     }
 }
 
@@ -275,11 +300,10 @@ impl fmt::Display for PermissionBitmap {
         if self.is_empty() {
             write!(f, "(none)")
         } else {
-            let bits = self.to_bits();
             write!(
                 f,
                 "bits[{}]",
-                bits.iter()
+                self.iter()
                     .map(|b| b.to_string())
                     .collect::<Vec<_>>()
                     .join(",")
@@ -288,6 +312,46 @@ impl fmt::Display for PermissionBitmap {
     }
 }
 
+/// Iterator over the set bit positions of a [`PermissionBitmap`].
+///
+/// Created by [`PermissionBitmap::iter`]; skips runs of zero bits with
+/// `trailing_zeros`.
+#[derive(Debug, Clone)]
+pub struct Bits {
+    bits: u128,
+}
+
+impl Iterator for Bits {
+    type Item = u8;
+
+    fn next(&mut self) -> Option<u8> {
+        if self.bits == 0 {
+            return None;
+        }
+        let pos = self.bits.trailing_zeros() as u8;
+        self.bits &= self.bits - 1;
+        Some(pos)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let n = self.bits.count_ones() as usize;
+        (n, Some(n))
+    }
+}
+
+impl ExactSizeIterator for Bits {}
+
+impl std::iter::FusedIterator for Bits {}
+
+impl IntoIterator for &PermissionBitmap {
+    type Item = u8;
+    type IntoIter = Bits;
+
+    fn into_iter(self) -> Bits {
+        self.iter()
+    }
+}
+
 impl std::ops::BitOr for PermissionBitmap {
     type Output = Self;
 
@@ -422,6 +486,22 @@ mod tests {
         assert_eq!(bits, vec![0, 5, 10, 127]);
     }
 
+    #[test]
+    fn test_iter_sparse_high_bits() {
+        let perm = PermissionBitmap::from_bits(&[0, 64, 127]);
+        let positions: Vec<u8> = perm.iter().collect();
+        assert_eq!(positions, perm.to_bits());
+        assert_eq!(positions, vec![0, 64, 127]);
+        assert_eq!(perm.iter().len(), 3);
+
+        let mut via_ref = Vec::new();
+        for pos in &perm {
+            via_ref.push(pos);
+        }
+        assert_eq!(via_ref, positions);
+        assert_eq!(PermissionBitmap::EMPTY.iter().next(), None);
+    }
+
     #[test]
     fn test_bitops() {
         let a = PermissionBitmap::from_bit(0);
@@ -452,4 +532,29 @@ mod tests {
         let restored: PermissionBitmap = serde_json::from_str(&json).unwrap();
         assert_eq!(original, restored);
     }
+
+    #[test]
+    fn test_base64url_roundtrip() {
+        for bitmap in [
+            PermissionBitmap::EMPTY,
+            PermissionBitmap::ALL,
+            PermissionBitmap::from_bits(&[0, 63, 64, 127]),
+        ] {
+            let token = bitmap.to_base64url();
+            assert_eq!(token.len(), 22);
+            assert!(!token.contains(['+', '/', '=']));
+            assert_eq!(PermissionBitmap::from_base64url(&token).unwrap(), bitmap);
+        }
+        assert_eq!(
+            PermissionBitmap::EMPTY.to_base64url(),
+            "AAAAAAAAAAAAAAAAAAAAAA"
+        );
+    }
+
+    #[test]
+    fn test_base64url_malformed() {
+        assert!(PermissionBitmap::from_base64url("not base64!").is_err());
+        assert!(PermissionBitmap::from_base64url("AAAA").is_err());
+        assert!(PermissionBitmap::from_base64url("").is_err());
+    }
 }
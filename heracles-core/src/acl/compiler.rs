@@ -3,11 +3,13 @@
 //! Compiles raw database query results into an optimized UserAcl structure
 //! for runtime permission evaluation.
 
+use rayon::prelude::*;
 use std::collections::{HashMap, HashSet};
 
 use super::attributes::{AttributeFilter, ObjectAttributeAcl};
 use super::bitmap::PermissionBitmap;
-use super::engine::{ScopedEntry, UserAcl};
+use super::engine::{AttrResolution, ScopedEntry, UserAcl};
+use crate::errors::{HeraclesError, Result};
 
 /// Raw row from the SQL query (one per matching assignment+policy join).
 ///
@@ -17,7 +19,7 @@ use super::engine::{ScopedEntry, UserAcl};
 /// - `acl_policy_attr_rules` (expanded)
 ///
 /// The Python layer fetches these rows and passes them to the Rust compiler.
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub struct AclRow {
     /// Policy name (for debugging/logging).
     pub policy_name: String,
@@ -51,7 +53,7 @@ pub struct AclRow {
 ///
 /// Represents one row from `acl_policy_attr_rules` with attribute groups
 /// expanded to their actual attribute names.
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub struct AttrRuleRow {
     /// Object type this rule applies to (e.g., "user", "group").
     pub object_type: String,
@@ -66,6 +68,13 @@ pub struct AttrRuleRow {
     pub attributes: Vec<String>,
 }
 
+/// Options controlling how rows are compiled into a UserAcl.
+#[derive(Clone, Debug, Default)]
+pub struct CompileOptions {
+    /// How attribute filters from matching scopes are combined.
+    pub attr_resolution: AttrResolution,
+}
+
 /// Compile raw database rows into a UserAcl.
 ///
 /// This is called once at login by the Python ACL service.
@@ -84,6 +93,62 @@ pub struct AttrRuleRow {
 /// 4. Sort scoped entries by priority ascending
 /// 5. Return optimized UserAcl
 pub fn compile(user_dn: &str, rows: Vec<AclRow>) -> UserAcl {
+    compile_with_options(user_dn, rows, &CompileOptions::default())
+}
+
+/// Compile rows after checking the permission catalog fits the bitmap.
+///
+/// `catalog` maps permission names to their assigned bit positions. Rows
+/// carry bitmaps as two 64-bit halves, so any permission at position 128
+/// or above would be silently dropped by [`compile`]; this rejects such a
+/// catalog with `HeraclesError::InvalidPermissions` instead.
+pub fn compile_checked(
+    user_dn: &str,
+    rows: Vec<AclRow>,
+    catalog: &HashMap<String, u32>,
+) -> Result<UserAcl> {
+    let mut overflow: Vec<_> = catalog
+        .iter()
+        .filter(|(_, &bit)| bit >= 128)
+        .map(|(name, bit)| format!("{} (bit {})", name, bit))
+        .collect();
+
+    if !overflow.is_empty() {
+        overflow.sort();
+        return Err(HeraclesError::InvalidPermissions(format!(
+            "permissions beyond the 128-bit bitmap: {}",
+            overflow.join(", ")
+        )));
+    }
+
+    Ok(compile(user_dn, rows))
+}
+
+/// Compile ACLs for many users in parallel, e.g. to warm a cache at startup.
+///
+/// Runs on a dedicated pool of at most `parallelism` threads (minimum one)
+/// so warming doesn't saturate every core. Results keep the input order.
+pub fn precompile_many(
+    inputs: Vec<(String, Vec<AclRow>)>,
+    parallelism: usize,
+) -> Vec<(String, UserAcl)> {
+    let compile_one = |(user_dn, rows): (String, Vec<AclRow>)| {
+        let acl = compile(&user_dn, rows);
+        (user_dn, acl)
+    };
+
+    match rayon::ThreadPoolBuilder::new()
+        .num_threads(parallelism.max(1))
+        .build()
+    {
+        Ok(pool) => pool.install(|| inputs.into_par_iter().map(compile_one).collect()),
+        // Fall back to the calling thread if no pool can be spawned.
+        Err(_) => inputs.into_iter().map(compile_one).collect(),
+    }
+}
+
+/// Compile raw database rows into a UserAcl with explicit options.
+pub fn compile_with_options(user_dn: &str, rows: Vec<AclRow>, options: &CompileOptions) -> UserAcl {
     let mut global_allow = PermissionBitmap::EMPTY;
     let mut global_deny = PermissionBitmap::EMPTY;
     let mut global_attr_acls: HashMap<String, ObjectAttributeAcl> = HashMap::new();
@@ -126,6 +191,7 @@ pub fn compile(user_dn: &str, rows: Vec<AclRow>) -> UserAcl {
         global_attr_acls,
         scoped_entries,
     )
+    .with_attr_resolution(options.attr_resolution)
 }
 
 /// Build attribute ACLs from attr_rules.
@@ -245,6 +311,59 @@ mod tests {
         assert!(acl.scoped_entries().is_empty());
     }
 
+    #[test]
+    fn test_compile_checked_rejects_bit_128() {
+        let mut catalog = HashMap::new();
+        catalog.insert("user:read".to_string(), 0);
+        catalog.insert("user:write".to_string(), 127);
+        assert!(compile_checked(test_user(), Vec::new(), &catalog).is_ok());
+
+        catalog.insert("audit:export".to_string(), 128);
+        match compile_checked(test_user(), Vec::new(), &catalog) {
+            Err(HeraclesError::InvalidPermissions(msg)) => {
+                assert!(msg.contains("audit:export (bit 128)"));
+                assert!(!msg.contains("user:write"));
+            }
+            other => panic!("expected InvalidPermissions, got {:?}", other.map(|_| ())),
+        }
+    }
+
+    #[test]
+    fn test_precompile_many_matches_compile() {
+        let inputs: Vec<(String, Vec<AclRow>)> = (0..64)
+            .map(|i| {
+                let rows = vec![AclRow {
+                    policy_name: format!("Policy {}", i),
+                    perm_low: i,
+                    perm_high: i % 3,
+                    scope_dn: if i % 2 == 0 {
+                        String::new()
+                    } else {
+                        format!("ou=team{},dc=example,dc=com", i)
+                    },
+                    scope_type: "subtree".to_string(),
+                    self_only: false,
+                    deny: i % 5 == 0,
+                    priority: i as i16,
+                    attr_rules: vec![],
+                }];
+                (format!("uid=user{},ou=users,dc=example,dc=com", i), rows)
+            })
+            .collect();
+
+        let results = precompile_many(inputs.clone(), 4);
+
+        assert_eq!(results.len(), inputs.len());
+        for ((dn, acl), (input_dn, rows)) in results.iter().zip(inputs) {
+            assert_eq!(dn, &input_dn);
+            let expected = compile(&input_dn, rows);
+            assert_eq!(
+                serde_json::to_string(acl).unwrap(),
+                serde_json::to_string(&expected).unwrap()
+            );
+        }
+    }
+
     #[test]
     fn test_compile_global_allow() {
         let rows = vec![AclRow {
@@ -470,4 +589,17 @@ mod tests {
         assert_eq!(acl.scoped_entries()[0].priority, 1);
         assert_eq!(acl.scoped_entries()[1].priority, 100);
     }
+
+    #[test]
+    fn test_compile_with_most_specific_option() {
+        let options = CompileOptions {
+            attr_resolution: AttrResolution::MostSpecific,
+        };
+        let acl = compile_with_options(test_user(), vec![], &options);
+        assert_eq!(acl.attr_resolution(), AttrResolution::MostSpecific);
+        assert_eq!(
+            compile(test_user(), vec![]).attr_resolution(),
+            AttrResolution::Merge
+        );
+    }
 }
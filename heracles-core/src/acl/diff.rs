@@ -0,0 +1,133 @@
+//! ACL Row Diff - Preview policy changes before applying them.
+//!
+//! Compares the currently assigned ACL rows with a desired set so admin
+//! tooling can show what recompiling would change.
+
+use super::compiler::AclRow;
+
+/// A row present in both sets whose contents differ.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RowChange {
+    /// The current row.
+    pub before: AclRow,
+    /// The desired row.
+    pub after: AclRow,
+}
+
+/// Differences between two sets of ACL rows.
+///
+/// Rows are matched by policy name and scope DN (case-insensitive).
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct RowDiff {
+    /// Rows only in the desired set.
+    pub added: Vec<AclRow>,
+    /// Rows only in the current set.
+    pub removed: Vec<AclRow>,
+    /// Rows in both sets with different contents.
+    pub changed: Vec<RowChange>,
+}
+
+impl RowDiff {
+    /// Returns true if the two sets are equivalent.
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+}
+
+/// Key identifying a row across both sets.
+fn row_key(row: &AclRow) -> (&str, String) {
+    (row.policy_name.as_str(), row.scope_dn.to_ascii_lowercase())
+}
+
+/// Compute the differences between `current` and `desired` rows.
+///
+/// Results follow the input order: `added` and `changed` in `desired` order,
+/// `removed` in `current` order.
+pub fn diff_rows(current: &[AclRow], desired: &[AclRow]) -> RowDiff {
+    let mut diff = RowDiff::default();
+
+    for row in desired {
+        match current.iter().find(|c| row_key(c) == row_key(row)) {
+            None => diff.added.push(row.clone()),
+            Some(before) if before != row => diff.changed.push(RowChange {
+                before: before.clone(),
+                after: row.clone(),
+            }),
+            Some(_) => {}
+        }
+    }
+
+    diff.removed = current
+        .iter()
+        .filter(|c| !desired.iter().any(|d| row_key(d) == row_key(c)))
+        .cloned()
+        .collect();
+
+    diff
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn row(policy: &str, scope: &str, priority: i16) -> AclRow {
+        AclRow {
+            policy_name: policy.to_string(),
+            perm_low: 0b1,
+            perm_high: 0,
+            scope_dn: scope.to_string(),
+            scope_type: "subtree".to_string(),
+            self_only: false,
+            deny: false,
+            priority,
+            attr_rules: vec![],
+        }
+    }
+
+    #[test]
+    fn test_diff_identical() {
+        let rows = vec![row("Readers", "", 0)];
+        assert!(diff_rows(&rows, &rows).is_empty());
+    }
+
+    #[test]
+    fn test_diff_added_policy() {
+        let current = vec![row("Readers", "", 0)];
+        let desired = vec![
+            row("Readers", "", 0),
+            row("Helpdesk", "ou=users,dc=example,dc=com", 5),
+        ];
+
+        let diff = diff_rows(&current, &desired);
+        assert_eq!(diff.added, vec![desired[1].clone()]);
+        assert!(diff.removed.is_empty());
+        assert!(diff.changed.is_empty());
+    }
+
+    #[test]
+    fn test_diff_removed_policy() {
+        let current = vec![
+            row("Readers", "", 0),
+            row("Helpdesk", "ou=users,dc=example,dc=com", 5),
+        ];
+        let desired = vec![row("Readers", "", 0)];
+
+        let diff = diff_rows(&current, &desired);
+        assert_eq!(diff.removed, vec![current[1].clone()]);
+        assert!(diff.added.is_empty());
+        assert!(diff.changed.is_empty());
+    }
+
+    #[test]
+    fn test_diff_changed_priority() {
+        let current = vec![row("Helpdesk", "ou=users,dc=example,dc=com", 5)];
+        let desired = vec![row("Helpdesk", "OU=Users,DC=example,DC=com", 10)];
+
+        let diff = diff_rows(&current, &desired);
+        assert_eq!(diff.changed.len(), 1);
+        assert_eq!(diff.changed[0].before.priority, 5);
+        assert_eq!(diff.changed[0].after.priority, 10);
+        assert!(diff.added.is_empty());
+        assert!(diff.removed.is_empty());
+    }
+}
@@ -4,10 +4,12 @@
 //! Precompiled at login from database rows, cached in Redis.
 
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeSet, HashMap};
 
 use super::attributes::{AttributeFilter, ObjectAttributeAcl};
 use super::bitmap::PermissionBitmap;
+use crate::ldap::dn::DistinguishedName;
+use crate::ldap::operations::{LdapEntry, LdapModification};
 
 /// A single scoped ACL entry (compiled from an assignment + policy).
 ///
@@ -72,6 +74,20 @@ impl ScopedEntry {
     }
 }
 
+/// How attribute filters from matching scopes are combined.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AttrResolution {
+    /// Merge the global filter with every matching scope's filter.
+    #[default]
+    Merge,
+
+    /// Use only the most specific matching scope's filter (deepest DN,
+    /// then highest priority). Falls back to the global filter when no
+    /// scope defines one. Deny entries still contribute their denied
+    /// attributes.
+    MostSpecific,
+}
+
 /// Result of an ACL check.
 #[derive(Clone, Debug)]
 pub struct AclVerdict {
@@ -134,6 +150,10 @@ pub struct UserAcl {
     /// Scoped entries, sorted by priority ascending.
     /// Evaluated after global rules for matching target DNs.
     scoped: Vec<ScopedEntry>,
+
+    /// Attribute filter resolution mode.
+    #[serde(default)]
+    attr_resolution: AttrResolution,
 }
 
 impl UserAcl {
@@ -159,9 +179,21 @@ impl UserAcl {
             global_deny,
             global_attr_acls,
             scoped,
+            attr_resolution: AttrResolution::Merge,
         }
     }
 
+    /// Set the attribute filter resolution mode.
+    pub fn with_attr_resolution(mut self, mode: AttrResolution) -> Self {
+        self.attr_resolution = mode;
+        self
+    }
+
+    /// Get the attribute filter resolution mode.
+    pub fn attr_resolution(&self) -> AttrResolution {
+        self.attr_resolution
+    }
+
     /// Create an empty ACL (no permissions).
     pub fn empty(user_dn: String) -> Self {
         Self::new(
@@ -189,6 +221,15 @@ impl UserAcl {
         &self.user_dn
     }
 
+    /// The user's own DN, parsed, for self-service lookups.
+    ///
+    /// Returns `None` when the stored DN is empty or malformed.
+    pub fn self_dn(&self) -> Option<DistinguishedName> {
+        DistinguishedName::parse(&self.user_dn)
+            .ok()
+            .filter(|dn| !dn.is_empty())
+    }
+
     /// Fast object-level permission check. Returns bool.
     ///
     /// This is the primary permission check API.
@@ -306,6 +347,153 @@ impl UserAcl {
             .collect()
     }
 
+    /// The subset of `candidate` attributes the user may read on `target_dn`.
+    ///
+    /// Only attribute-level read rules are applied; check object-level
+    /// access separately. Attributes readable only under a condition are
+    /// left out, since their values aren't known before fetching (the
+    /// entry-less [`AttributeFilter::filter_attributes`] denies them).
+    pub fn readable_attributes(
+        &self,
+        target_dn: &str,
+        object_type: &str,
+        candidate: &[&str],
+    ) -> Vec<String> {
+        self.resolve_attr_filter_for_type(target_dn, object_type, "read")
+            .filter_attributes(candidate)
+            .into_iter()
+            .map(|s| s.to_string())
+            .collect()
+    }
+
+    /// Filter attributes, evaluating conditional rules against the entry's
+    /// current values.
+    ///
+    /// Same as [`filter_attributes`](Self::filter_attributes), with `entry`
+    /// supplying the values conditional attribute rules depend on.
+    pub fn filter_attributes_for_entry(
+        &self,
+        target_dn: &str,
+        required: PermissionBitmap,
+        object_type: &str,
+        action: &str,
+        attributes: &[&str],
+        entry: &HashMap<String, Vec<String>>,
+    ) -> Vec<String> {
+        let verdict = self.evaluate(target_dn, required);
+        if !verdict.allowed {
+            return Vec::new();
+        }
+
+        let attr_filter = self.resolve_attr_filter_for_type(target_dn, object_type, action);
+        attr_filter
+            .filter_attributes_for(attributes, entry)
+            .into_iter()
+            .map(|s| s.to_string())
+            .collect()
+    }
+
+    /// Check a modification against the user's write ACLs before sending it.
+    ///
+    /// Returns `Err` with the attributes (deduplicated, in modification order)
+    /// the user may not write. If `write_bit` is not granted on `target_dn`,
+    /// every attribute in `mods` is reported. Attributes writable only under
+    /// a condition are rejected, since there is no entry to test it against;
+    /// use [`authorize_modify_for_entry`](Self::authorize_modify_for_entry)
+    /// to evaluate them.
+    pub fn authorize_modify(
+        &self,
+        target_dn: &str,
+        object_type: &str,
+        mods: &[LdapModification],
+        write_bit: PermissionBitmap,
+    ) -> Result<(), Vec<String>> {
+        self.reject_unwritable(target_dn, object_type, mods, write_bit, |filter, attr| {
+            filter.is_attribute_permitted(attr)
+        })
+    }
+
+    /// Like [`authorize_modify`](Self::authorize_modify), evaluating
+    /// conditional write rules against `entry`, the target's current values.
+    pub fn authorize_modify_for_entry(
+        &self,
+        target_dn: &str,
+        object_type: &str,
+        mods: &[LdapModification],
+        entry: &HashMap<String, Vec<String>>,
+        write_bit: PermissionBitmap,
+    ) -> Result<(), Vec<String>> {
+        self.reject_unwritable(target_dn, object_type, mods, write_bit, |filter, attr| {
+            filter.is_attribute_permitted_for(attr, entry)
+        })
+    }
+
+    /// Collects the attributes of `mods` that `permitted` rejects.
+    fn reject_unwritable(
+        &self,
+        target_dn: &str,
+        object_type: &str,
+        mods: &[LdapModification],
+        write_bit: PermissionBitmap,
+        permitted: impl Fn(&AttributeFilter, &str) -> bool,
+    ) -> Result<(), Vec<String>> {
+        let allowed = self.evaluate(target_dn, write_bit).allowed;
+        let attr_filter = self.resolve_attr_filter_for_type(target_dn, object_type, "write");
+
+        let mut rejected: Vec<String> = Vec::new();
+        for m in mods {
+            let attr = m.attr();
+            if (!allowed || !permitted(&attr_filter, attr))
+                && !rejected.iter().any(|r| r.eq_ignore_ascii_case(attr))
+            {
+                rejected.push(attr.to_string());
+            }
+        }
+
+        if rejected.is_empty() {
+            Ok(())
+        } else {
+            Err(rejected)
+        }
+    }
+
+    /// Return a copy of `entry` without the attributes the user may not read.
+    ///
+    /// Conditional attribute rules are evaluated against the entry's own
+    /// values. Binary attributes are checked by their name without options,
+    /// so a rule on `userCertificate` covers `userCertificate;binary`. If
+    /// `read_bit` is not granted on the entry, the copy keeps the DN but has
+    /// no attributes.
+    pub fn redact_entry(
+        &self,
+        entry: &LdapEntry,
+        object_type: &str,
+        read_bit: PermissionBitmap,
+    ) -> LdapEntry {
+        let mut redacted = LdapEntry::new(entry.dn.clone());
+        if !self.evaluate(&entry.dn, read_bit).allowed {
+            return redacted;
+        }
+
+        let attr_filter = self.resolve_attr_filter_for_type(&entry.dn, object_type, "read");
+        redacted.attributes = entry
+            .attributes
+            .iter()
+            .filter(|(name, _)| attr_filter.is_attribute_permitted_for(name, &entry.attributes))
+            .map(|(name, values)| (name.clone(), values.clone()))
+            .collect();
+        redacted.bin_attributes = entry
+            .bin_attributes
+            .iter()
+            .filter(|(name, _)| {
+                let base = name.split(';').next().unwrap_or(name);
+                attr_filter.is_attribute_permitted_for(base, &entry.attributes)
+            })
+            .map(|(name, values)| (name.clone(), values.clone()))
+            .collect();
+        redacted
+    }
+
     /// Get the effective permissions for a target DN.
     ///
     /// Returns the permission bitmap after applying all global and scoped rules.
@@ -338,6 +526,10 @@ impl UserAcl {
         let target_lower = target_dn.to_ascii_lowercase();
         let is_self = target_lower == self.user_dn_lower;
 
+        if self.attr_resolution == AttrResolution::MostSpecific {
+            return self.resolve_most_specific(&target_lower, is_self, object_type, "read");
+        }
+
         // Start with global filter for this object type
         let mut filter = self
             .global_attr_acls
@@ -374,6 +566,10 @@ impl UserAcl {
         let target_lower = target_dn.to_ascii_lowercase();
         let is_self = target_lower == self.user_dn_lower;
 
+        if self.attr_resolution == AttrResolution::MostSpecific {
+            return self.resolve_most_specific(&target_lower, is_self, object_type, action);
+        }
+
         // Start with global filter
         let mut filter = self
             .global_attr_acls
@@ -412,6 +608,80 @@ impl UserAcl {
         filter
     }
 
+    /// Resolve the attribute filter from the single most specific scope.
+    fn resolve_most_specific(
+        &self,
+        target_lower: &str,
+        is_self: bool,
+        object_type: &str,
+        action: &str,
+    ) -> AttributeFilter {
+        let select = |acl: &ObjectAttributeAcl| {
+            if action == "write" {
+                acl.write.clone()
+            } else {
+                acl.read.clone()
+            }
+        };
+
+        let matching: Vec<(&ScopedEntry, &ObjectAttributeAcl)> = self
+            .scoped
+            .iter()
+            .filter(|e| e.matches(target_lower, &self.user_dn_lower, is_self))
+            .filter_map(|e| e.attr_acls.get(object_type).map(|acl| (e, acl)))
+            .collect();
+
+        // Scoped entries are sorted by priority, so max_by_key keeps the
+        // highest priority among equally deep scopes.
+        let mut filter = matching
+            .iter()
+            .filter(|(e, _)| !e.deny)
+            .max_by_key(|(e, _)| (self.scope_depth(e), e.priority))
+            .map(|(_, acl)| select(acl))
+            .or_else(|| self.global_attr_acls.get(object_type).map(select))
+            .unwrap_or_else(AttributeFilter::allow_all);
+
+        for (_, acl) in matching.iter().filter(|(e, _)| e.deny) {
+            for attr in select(acl).denied() {
+                filter.add_denied([attr.as_str()]);
+            }
+        }
+
+        filter
+    }
+
+    /// Number of RDNs in an entry's scope; self-only entries without a
+    /// scope DN target the user's own entry.
+    fn scope_depth(&self, entry: &ScopedEntry) -> usize {
+        let dn = if entry.dn_lower.is_empty() && entry.self_only {
+            &self.user_dn_lower
+        } else {
+            &entry.dn_lower
+        };
+        dn_depth(dn)
+    }
+
+    /// Every attribute name mentioned by this ACL's attribute filters.
+    ///
+    /// Collects allowed, denied and conditionally allowed attributes from
+    /// the read and write filters of global and scoped entries. Names are
+    /// lowercase, as stored by `AttributeFilter`.
+    pub fn referenced_attributes(&self) -> BTreeSet<String> {
+        let mut attrs = BTreeSet::new();
+        let acls = self
+            .global_attr_acls
+            .values()
+            .chain(self.scoped.iter().flat_map(|e| e.attr_acls.values()));
+        for acl in acls {
+            for filter in [&acl.read, &acl.write] {
+                attrs.extend(filter.allowed().into_iter().flatten().cloned());
+                attrs.extend(filter.denied().iter().cloned());
+                attrs.extend(filter.conditions().keys().cloned());
+            }
+        }
+        attrs
+    }
+
     /// Get all scoped entries (for debugging/inspection).
     pub fn scoped_entries(&self) -> &[ScopedEntry] {
         &self.scoped
@@ -428,6 +698,23 @@ impl UserAcl {
     }
 }
 
+/// Count the RDNs of a DN, ignoring escaped commas.
+fn dn_depth(dn: &str) -> usize {
+    if dn.is_empty() {
+        return 0;
+    }
+    let mut depth = 1;
+    let mut escaped = false;
+    for c in dn.chars() {
+        match c {
+            '\\' if !escaped => escaped = true,
+            ',' if !escaped => depth += 1,
+            _ => escaped = false,
+        }
+    }
+    depth
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -436,6 +723,16 @@ mod tests {
         "uid=testuser,ou=users,dc=example,dc=com".to_string()
     }
 
+    #[test]
+    fn test_self_dn() {
+        let acl = UserAcl::empty(test_user_dn());
+        let dn = acl.self_dn().unwrap();
+
+        assert_eq!(dn.rdn_value(), Some("testuser"));
+        assert_eq!(dn.to_string(), test_user_dn());
+        assert!(UserAcl::empty(String::new()).self_dn().is_none());
+    }
+
     #[test]
     fn test_empty_acl_denies_all() {
         let acl = UserAcl::empty(test_user_dn());
@@ -768,4 +1065,380 @@ mod tests {
         assert_eq!(acl.global_deny, restored.global_deny);
         assert_eq!(acl.scoped.len(), restored.scoped.len());
     }
+
+    fn attr_scope(dn: &str, priority: i16, read: AttributeFilter) -> ScopedEntry {
+        let mut attr_acls = HashMap::new();
+        attr_acls.insert(
+            "user".to_string(),
+            ObjectAttributeAcl::new(read, AttributeFilter::allow_all()),
+        );
+        ScopedEntry {
+            dn_lower: dn.to_string(),
+            subtree: true,
+            self_only: false,
+            deny: false,
+            priority,
+            permissions: PermissionBitmap::from_bit(0),
+            attr_acls,
+        }
+    }
+
+    fn whitelist(attrs: &[&str]) -> AttributeFilter {
+        AttributeFilter::with_allowed(attrs.iter())
+    }
+
+    #[test]
+    fn test_most_specific_scope_overrides_shallow() {
+        let scoped = vec![
+            attr_scope("dc=example,dc=com", 0, AttributeFilter::allow_all()),
+            attr_scope(
+                "dc=example,dc=com",
+                0,
+                whitelist(&["cn", "mail", "userPassword"]),
+            ),
+            attr_scope("ou=users,dc=example,dc=com", 0, whitelist(&["cn", "mail"])),
+        ];
+        let target = "uid=john,ou=users,dc=example,dc=com";
+        let attrs = ["cn", "mail", "userPassword"];
+
+        let merged = UserAcl::new(
+            test_user_dn(),
+            PermissionBitmap::from_bit(0),
+            PermissionBitmap::EMPTY,
+            HashMap::new(),
+            scoped.clone(),
+        );
+        assert_eq!(merged.attr_resolution(), AttrResolution::Merge);
+        assert_eq!(
+            merged
+                .filter_attributes(target, PermissionBitmap::EMPTY, "user", "read", &attrs)
+                .len(),
+            3
+        );
+
+        let specific = merged.with_attr_resolution(AttrResolution::MostSpecific);
+        assert_eq!(
+            specific.filter_attributes(target, PermissionBitmap::EMPTY, "user", "read", &attrs),
+            vec!["cn", "mail"]
+        );
+
+        // Outside the deep scope the shallow allow-all applies.
+        assert_eq!(
+            specific
+                .filter_attributes(
+                    "cn=admins,ou=groups,dc=example,dc=com",
+                    PermissionBitmap::EMPTY,
+                    "user",
+                    "read",
+                    &attrs
+                )
+                .len(),
+            3
+        );
+    }
+
+    #[test]
+    fn test_most_specific_priority_breaks_ties() {
+        let acl = UserAcl::new(
+            test_user_dn(),
+            PermissionBitmap::from_bit(0),
+            PermissionBitmap::EMPTY,
+            HashMap::new(),
+            vec![
+                attr_scope("ou=users,dc=example,dc=com", 10, whitelist(&["cn"])),
+                attr_scope("ou=users,dc=example,dc=com", 1, whitelist(&["mail"])),
+            ],
+        )
+        .with_attr_resolution(AttrResolution::MostSpecific);
+
+        assert!(acl.check_attribute(
+            "uid=john,ou=users,dc=example,dc=com",
+            PermissionBitmap::EMPTY,
+            "user",
+            "read",
+            "cn"
+        ));
+        assert!(!acl.check_attribute(
+            "uid=john,ou=users,dc=example,dc=com",
+            PermissionBitmap::EMPTY,
+            "user",
+            "read",
+            "mail"
+        ));
+    }
+
+    #[test]
+    fn test_conditional_attribute_denied_without_entry() {
+        use crate::acl::attributes::AttributeCondition;
+
+        let read = AttributeFilter::allow_all().with_condition(
+            "employeeNumber",
+            AttributeCondition::Present("employeeNumber".into()),
+        );
+        let acl = UserAcl::new(
+            test_user_dn(),
+            PermissionBitmap::from_bit(0),
+            PermissionBitmap::EMPTY,
+            HashMap::new(),
+            vec![attr_scope("ou=users,dc=example,dc=com", 0, read)],
+        );
+        let target = "uid=john,ou=users,dc=example,dc=com";
+        let bit = PermissionBitmap::from_bit(0);
+
+        assert!(acl.check_attribute(target, bit, "user", "read", "cn"));
+        assert!(!acl.check_attribute(target, bit, "user", "read", "employeeNumber"));
+        assert_eq!(
+            acl.filter_attributes(target, bit, "user", "read", &["cn", "employeeNumber"]),
+            vec!["cn"]
+        );
+    }
+
+    #[test]
+    fn test_filter_attributes_for_entry_conditional() {
+        use crate::acl::attributes::AttributeCondition;
+
+        let read = AttributeFilter::with_allowed(["cn"]).with_condition(
+            "employeeNumber",
+            AttributeCondition::Present("employeeNumber".into()),
+        );
+        let acl = UserAcl::new(
+            test_user_dn(),
+            PermissionBitmap::from_bit(0),
+            PermissionBitmap::EMPTY,
+            HashMap::new(),
+            vec![attr_scope("ou=users,dc=example,dc=com", 0, read)],
+        );
+        let target = "uid=john,ou=users,dc=example,dc=com";
+        let attrs = ["cn", "employeeNumber"];
+
+        let mut entry = HashMap::new();
+        entry.insert("cn".to_string(), vec!["John".to_string()]);
+        let visible = acl.filter_attributes_for_entry(
+            target,
+            PermissionBitmap::from_bit(0),
+            "user",
+            "read",
+            &attrs,
+            &entry,
+        );
+        assert_eq!(visible, vec!["cn"]);
+
+        entry.insert("employeeNumber".to_string(), vec!["42".to_string()]);
+        let visible = acl.filter_attributes_for_entry(
+            target,
+            PermissionBitmap::from_bit(0),
+            "user",
+            "read",
+            &attrs,
+            &entry,
+        );
+        assert_eq!(visible, vec!["cn", "employeeNumber"]);
+    }
+
+    fn write_scope(dn: &str, write: AttributeFilter) -> ScopedEntry {
+        let mut entry = attr_scope(dn, 0, AttributeFilter::allow_all());
+        entry.permissions = PermissionBitmap::from_bit(1);
+        entry.attr_acls.insert(
+            "user".to_string(),
+            ObjectAttributeAcl::new(AttributeFilter::allow_all(), write),
+        );
+        entry
+    }
+
+    #[test]
+    fn test_referenced_attributes() {
+        let mut global = HashMap::new();
+        global.insert(
+            "group".to_string(),
+            ObjectAttributeAcl::new(
+                whitelist(&["cn", "member"]),
+                AttributeFilter::with_denied(["gidNumber"]),
+            ),
+        );
+        let acl = UserAcl::new(
+            test_user_dn(),
+            PermissionBitmap::EMPTY,
+            PermissionBitmap::EMPTY,
+            global,
+            vec![
+                attr_scope("ou=users,dc=example,dc=com", 0, whitelist(&["cn", "mail"])),
+                write_scope(
+                    "ou=users,dc=example,dc=com",
+                    AttributeFilter::with_denied(["userPassword"]),
+                ),
+            ],
+        );
+
+        let attrs: Vec<String> = acl.referenced_attributes().into_iter().collect();
+        assert_eq!(
+            attrs,
+            vec!["cn", "gidnumber", "mail", "member", "userpassword"]
+        );
+    }
+
+    #[test]
+    fn test_authorize_modify() {
+        let acl = UserAcl::new(
+            test_user_dn(),
+            PermissionBitmap::EMPTY,
+            PermissionBitmap::EMPTY,
+            HashMap::new(),
+            vec![write_scope(
+                "ou=users,dc=example,dc=com",
+                AttributeFilter::with_denied(["userPassword", "uidNumber"]),
+            )],
+        );
+        let target = "uid=john,ou=users,dc=example,dc=com";
+        let write = PermissionBitmap::from_bit(1);
+
+        let allowed = [LdapModification::replace_single("mail", "john@example.com")];
+        assert!(acl
+            .authorize_modify(target, "user", &allowed, write)
+            .is_ok());
+
+        let mods = [
+            LdapModification::replace_single("mail", "john@example.com"),
+            LdapModification::replace_single("userPassword", "{SSHA}x"),
+            LdapModification::delete_all("userpassword"),
+        ];
+        assert_eq!(
+            acl.authorize_modify(target, "user", &mods, write),
+            Err(vec!["userPassword".to_string()])
+        );
+
+        // No object-level write outside the scope: everything is rejected.
+        assert_eq!(
+            acl.authorize_modify("uid=x,ou=other,dc=example,dc=com", "user", &allowed, write),
+            Err(vec!["mail".to_string()])
+        );
+    }
+
+    #[test]
+    fn test_authorize_modify_conditional() {
+        use crate::acl::attributes::AttributeCondition;
+
+        let write = AttributeFilter::allow_all().with_condition(
+            "employeeNumber",
+            AttributeCondition::Present("employeeType".into()),
+        );
+        let acl = UserAcl::new(
+            test_user_dn(),
+            PermissionBitmap::EMPTY,
+            PermissionBitmap::EMPTY,
+            HashMap::new(),
+            vec![write_scope("ou=users,dc=example,dc=com", write)],
+        );
+        let target = "uid=john,ou=users,dc=example,dc=com";
+        let bit = PermissionBitmap::from_bit(1);
+        let mods = [
+            LdapModification::replace_single("mail", "john@example.com"),
+            LdapModification::replace_single("employeeNumber", "42"),
+        ];
+        let rejected = Err(vec!["employeeNumber".to_string()]);
+
+        let mut entry = HashMap::new();
+        assert_eq!(
+            acl.authorize_modify_for_entry(target, "user", &mods, &entry, bit),
+            rejected
+        );
+
+        entry.insert("employeeType".to_string(), vec!["staff".to_string()]);
+        assert!(acl
+            .authorize_modify_for_entry(target, "user", &mods, &entry, bit)
+            .is_ok());
+        // Without the entry the condition can't be checked.
+        assert_eq!(acl.authorize_modify(target, "user", &mods, bit), rejected);
+    }
+
+    #[test]
+    fn test_readable_attributes() {
+        let mut read = whitelist(&["cn", "mail", "userPassword"]);
+        read.add_denied(["userPassword"]);
+        let acl = UserAcl::new(
+            test_user_dn(),
+            PermissionBitmap::EMPTY,
+            PermissionBitmap::EMPTY,
+            HashMap::new(),
+            vec![attr_scope("ou=users,dc=example,dc=com", 0, read)],
+        );
+        let candidate = ["cn", "mail", "userPassword", "telephoneNumber"];
+
+        assert_eq!(
+            acl.readable_attributes("uid=john,ou=users,dc=example,dc=com", "user", &candidate),
+            vec!["cn", "mail"]
+        );
+        // No attribute rules apply outside the scope.
+        assert_eq!(
+            acl.readable_attributes("cn=admins,ou=groups,dc=example,dc=com", "user", &candidate),
+            candidate
+        );
+
+        // Attributes readable only under a condition are not requested.
+        let conditional = AttributeFilter::allow_all().with_condition(
+            "employeeNumber",
+            crate::acl::attributes::AttributeCondition::Present("employeeType".into()),
+        );
+        let acl = UserAcl::new(
+            test_user_dn(),
+            PermissionBitmap::EMPTY,
+            PermissionBitmap::EMPTY,
+            HashMap::new(),
+            vec![attr_scope("ou=users,dc=example,dc=com", 0, conditional)],
+        );
+        assert_eq!(
+            acl.readable_attributes(
+                "uid=john,ou=users,dc=example,dc=com",
+                "user",
+                &["cn", "employeeNumber"]
+            ),
+            vec!["cn"]
+        );
+    }
+
+    #[test]
+    fn test_redact_entry() {
+        let acl = UserAcl::new(
+            test_user_dn(),
+            PermissionBitmap::EMPTY,
+            PermissionBitmap::EMPTY,
+            HashMap::new(),
+            vec![attr_scope(
+                "ou=users,dc=example,dc=com",
+                0,
+                AttributeFilter::with_denied(["userPassword", "userCertificate"]),
+            )],
+        );
+        let read = PermissionBitmap::from_bit(0);
+        let entry = LdapEntry::new("uid=john,ou=users,dc=example,dc=com")
+            .with_single("cn", "John")
+            .with_single("mail", "john@example.com")
+            .with_single("userPassword", "{SSHA}secret")
+            .with_binary("jpegPhoto", vec![vec![0xff, 0xd8]])
+            .with_binary("userCertificate;binary", vec![vec![0x30, 0x82]]);
+
+        let redacted = acl.redact_entry(&entry, "user", read);
+        assert_eq!(redacted.dn, entry.dn);
+        assert_eq!(redacted.get_first("cn"), Some("John"));
+        assert_eq!(redacted.get_first("mail"), Some("john@example.com"));
+        assert!(redacted.get_first("userPassword").is_none());
+        assert_eq!(redacted.get_binary("jpegPhoto"), Some(&[0xff, 0xd8][..]));
+        assert!(redacted.get_binary("userCertificate").is_none());
+    }
+
+    #[test]
+    fn test_redact_entry_object_denied() {
+        let acl = UserAcl::empty(test_user_dn());
+        let entry = LdapEntry::new("uid=john,ou=users,dc=example,dc=com").with_single("cn", "John");
+
+        let redacted = acl.redact_entry(&entry, "user", PermissionBitmap::from_bit(0));
+        assert_eq!(redacted.dn, entry.dn);
+        assert!(redacted.attributes.is_empty());
+    }
+
+    #[test]
+    fn test_dn_depth() {
+        assert_eq!(dn_depth(""), 0);
+        assert_eq!(dn_depth("dc=com"), 1);
+        assert_eq!(dn_depth("cn=a\\,b,dc=example,dc=com"), 3);
+    }
 }
@@ -4,9 +4,11 @@
 //!
 //! This module provides:
 //! - `PermissionBitmap`: u128 bitmap for fast permission checks
+//! - `BigPermissionSet`: growable bitmap for more than 128 permissions
 //! - `AttributeFilter`: Group-based attribute-level access control
 //! - `UserAcl`: Precompiled per-user ACL for runtime evaluation
 //! - `compile`: Compile raw database rows into UserAcl
+//! - `diff_rows`: Preview changes between two sets of database rows
 //!
 //! ## Architecture
 //!
@@ -28,11 +30,18 @@
 //! ```
 
 mod attributes;
+mod big_bitmap;
 mod bitmap;
 mod compiler;
+mod diff;
 mod engine;
 
-pub use attributes::{AttributeFilter, ObjectAttributeAcl};
-pub use bitmap::PermissionBitmap;
-pub use compiler::{compile, AclRow, AttrRuleRow};
-pub use engine::{AclVerdict, ScopedEntry, UserAcl};
+pub use attributes::{AttributeCondition, AttributeFilter, ObjectAttributeAcl};
+pub use big_bitmap::{BigPermissionSet, PermissionSet};
+pub use bitmap::{Bits, PermissionBitmap};
+pub use compiler::{
+    compile, compile_checked, compile_with_options, precompile_many, AclRow, AttrRuleRow,
+    CompileOptions,
+};
+pub use diff::{diff_rows, RowChange, RowDiff};
+pub use engine::{AclVerdict, AttrResolution, ScopedEntry, UserAcl};
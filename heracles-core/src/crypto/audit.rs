@@ -0,0 +1,118 @@
+//! Password storage auditing.
+//!
+//! Helpers for scanning `userPassword` values and flagging weak or legacy
+//! hash schemes without needing the cleartext passwords.
+
+use super::password::{unpeppered, HashMethod, PasswordHash};
+
+/// Classification of a single `userPassword` value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HashClassification {
+    /// Position of the value in the attribute.
+    pub index: usize,
+    /// Detected method; `None` for an unrecognized `{SCHEME}` prefix.
+    pub method: Option<HashMethod>,
+    /// Whether the method is considered secure. Unknown schemes are not.
+    pub is_secure: bool,
+}
+
+/// Classifies every value of a (possibly multi-valued) `userPassword`.
+///
+/// Values are classified with [`HashMethod::detect_or_plain`]: values
+/// without a `{SCHEME}` prefix are stored in cleartext and are reported as
+/// [`HashMethod::Plain`], while unknown schemes and unprintable data have
+/// no method. Peppered values are classified by their inner hash.
+///
+/// # Example
+///
+/// ```rust
+/// use heracles_core::crypto::{classify_userpassword, HashMethod};
+///
+/// let report = classify_userpassword(&["{MD5}X03MO1qnZdYdgyfeuILPmQ==".to_string()]);
+/// assert_eq!(report[0].method, Some(HashMethod::Md5));
+/// assert!(!report[0].is_secure);
+/// ```
+pub fn classify_userpassword(values: &[String]) -> Vec<HashClassification> {
+    values
+        .iter()
+        .enumerate()
+        .map(|(index, value)| {
+            let method = HashMethod::detect_or_plain(unpeppered(value));
+            HashClassification {
+                index,
+                method,
+                is_secure: method.is_some_and(|m| PasswordHash::new(m, value.clone()).is_secure()),
+            }
+        })
+        .collect()
+}
+
+/// Returns true if any value uses an insecure or unrecognized scheme.
+pub fn has_weak_password(values: &[String]) -> bool {
+    classify_userpassword(values).iter().any(|c| !c.is_secure)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto::hash_password;
+
+    #[test]
+    fn test_classify_mixed_values() {
+        let secure = hash_password("secret", HashMethod::Ssha512).unwrap().hash;
+        let legacy = hash_password("secret", HashMethod::Md5).unwrap().hash;
+
+        let report = classify_userpassword(&[secure, legacy]);
+
+        assert_eq!(report.len(), 2);
+        assert_eq!(report[0].index, 0);
+        assert_eq!(report[0].method, Some(HashMethod::Ssha512));
+        assert!(report[0].is_secure);
+        assert_eq!(report[1].index, 1);
+        assert_eq!(report[1].method, Some(HashMethod::Md5));
+        assert!(!report[1].is_secure);
+    }
+
+    #[test]
+    fn test_classify_plain_and_unknown() {
+        let report = classify_userpassword(&[
+            "hunter2".to_string(),
+            "{UNKNOWN}abc".to_string(),
+            "{CRYPT}$1$abc$BXBqpb9BZcZhXLgbee.0s/".to_string(),
+            "bin\u{0}ary".to_string(),
+        ]);
+
+        assert_eq!(report[0].method, Some(HashMethod::Plain));
+        assert!(!report[0].is_secure);
+        assert_eq!(report[1].method, None);
+        assert!(!report[1].is_secure);
+        assert_eq!(report[2].method, Some(HashMethod::Crypt));
+        assert!(!report[2].is_secure);
+        // Unprintable data is not mistaken for a cleartext password.
+        assert_eq!(report[3].method, None);
+        assert!(!report[3].is_secure);
+    }
+
+    #[test]
+    fn test_classify_peppered() {
+        let argon2 = hash_password("secret", HashMethod::Argon2id).unwrap().hash;
+        let md5 = hash_password("secret", HashMethod::Md5).unwrap().hash;
+        let report = classify_userpassword(&[
+            format!("{{PEPPERED}}{}", argon2),
+            format!("{{PEPPERED}}{}", md5),
+        ]);
+
+        assert_eq!(report[0].method, Some(HashMethod::Argon2id));
+        assert!(report[0].is_secure);
+        assert_eq!(report[1].method, Some(HashMethod::Md5));
+        assert!(!report[1].is_secure);
+    }
+
+    #[test]
+    fn test_has_weak_password() {
+        let secure = hash_password("secret", HashMethod::Ssha).unwrap().hash;
+        assert!(!has_weak_password(std::slice::from_ref(&secure)));
+        assert!(has_weak_password(&[secure, "plain".to_string()]));
+        assert!(!has_weak_password(&[]));
+    }
+}
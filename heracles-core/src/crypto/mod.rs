@@ -10,9 +10,20 @@
 //! - SHA-512 / SHA-256 - Standard hashes
 //! - SSHA-512 / SSHA-256 - Salted SHA variants
 //! - MD5 / SMD5 - Legacy support only (not recommended)
+//! - PBKDF2-SHA256 / PBKDF2-SHA512 - passlib/Django compatible
+//! - CRYPT - SHA-512/SHA-256 crypt; MD5 crypt is verify-only
+//!
+//! [`nt_hash`] produces the bare NT hash used by Samba's `sambaNTPassword`.
 
+pub mod audit;
 pub mod password;
 
+pub use audit::{classify_userpassword, has_weak_password, HashClassification};
 pub use password::{
-    hash_password, verify_password, HashMethod, PasswordHash, PasswordHasher, PasswordVerifier,
+    benchmark_method, hash_password, hash_password_with_params, hash_ssha_with, needs_rehash,
+    needs_rehash_with_params, nt_hash, verify, verify_against_values, verify_and_upgrade,
+    verify_password, verify_password_detailed, verify_password_with_options, verify_with_policy,
+    Argon2Params, HashMethod, HashMethodInfo, HashParams, PasswordHash, PasswordHasher,
+    PasswordVerifier, PepperedPasswordHasher, SshaOptions, VerifyOptions, VerifyOutcome,
+    VerifyPolicy, VerifyResult, VerifyVerdict, PEPPER_MARKER, SSHA_MIN_SALT_LEN,
 };
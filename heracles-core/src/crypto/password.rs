@@ -6,13 +6,17 @@
 use crate::errors::{HeraclesError, Result};
 use argon2::{
     password_hash::{rand_core::OsRng, PasswordHasher as Argon2Hasher, SaltString},
-    Argon2, PasswordVerifier as Argon2Verifier,
+    Algorithm, Argon2, Params, PasswordVerifier as Argon2Verifier, Version,
 };
 use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
 use bcrypt::{hash as bcrypt_hash, verify as bcrypt_verify, DEFAULT_COST};
+use hmac::{Hmac, Mac};
 use rand::RngCore;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use sha2::{Digest, Sha256, Sha512};
 use std::fmt;
+use std::sync::OnceLock;
+use std::time::{Duration, Instant};
 
 /// Supported password hash methods.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
@@ -38,9 +42,112 @@ pub enum HashMethod {
     Smd5,
     /// Plain text (for testing only, never use in production)
     Plain,
+    /// PBKDF2-HMAC-SHA256 (passlib/Django compatible)
+    Pbkdf2Sha256,
+    /// PBKDF2-HMAC-SHA512
+    Pbkdf2Sha512,
+    /// `{CRYPT}` with SHA-512 (`$6$`) or SHA-256 (`$5$`) crypt; MD5 crypt
+    /// (`$1$`) is verify-only
+    Crypt,
+}
+
+/// Every recognized spelling of each hash method.
+///
+/// Entries in braces are `{SCHEME}` storage prefixes, recognized by both
+/// [`HashMethod::detect`] and [`HashMethod::from_str`] (with or without the
+/// braces); bare entries are method names accepted by `from_str` only.
+/// Matching ignores case.
+const SCHEME_TABLE: &[(&str, HashMethod)] = &[
+    ("{SSHA}", HashMethod::Ssha),
+    ("{ARGON2}", HashMethod::Argon2id),
+    ("ARGON2ID", HashMethod::Argon2id),
+    ("{BCRYPT}", HashMethod::Bcrypt),
+    ("{SHA512}", HashMethod::Sha512),
+    ("{SSHA512}", HashMethod::Ssha512),
+    ("{SHA256}", HashMethod::Sha256),
+    ("{SSHA256}", HashMethod::Ssha256),
+    ("{MD5}", HashMethod::Md5),
+    ("{SMD5}", HashMethod::Smd5),
+    ("PLAIN", HashMethod::Plain),
+    ("CLEAR", HashMethod::Plain),
+    ("CLEARTEXT", HashMethod::Plain),
+    ("{PBKDF2-SHA256}", HashMethod::Pbkdf2Sha256),
+    ("PBKDF2", HashMethod::Pbkdf2Sha256),
+    ("PBKDF2_SHA256", HashMethod::Pbkdf2Sha256),
+    ("{PBKDF2-SHA512}", HashMethod::Pbkdf2Sha512),
+    ("PBKDF2_SHA512", HashMethod::Pbkdf2Sha512),
+    ("{CRYPT}", HashMethod::Crypt),
+    // FusionDirectory's name for its default crypt method.
+    ("CRYPT/SHA-512", HashMethod::Crypt),
+];
+
+/// A scheme table spelling without its braces.
+fn spelling_name(spelling: &str) -> &str {
+    spelling
+        .strip_prefix('{')
+        .and_then(|rest| rest.strip_suffix('}'))
+        .unwrap_or(spelling)
+}
+
+/// Security metadata about a [`HashMethod`], from [`HashMethod::describe`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HashMethodInfo {
+    /// LDAP scheme prefix (empty for plain text).
+    pub scheme: &'static str,
+    /// Same as [`HashMethod::is_secure`].
+    pub secure: bool,
+    /// Whether each hash embeds a random salt.
+    pub salted: bool,
+    /// Kept for existing directories only; not for new hashes.
+    pub legacy: bool,
 }
 
 impl HashMethod {
+    /// Every supported method, strongest first.
+    pub fn all() -> &'static [HashMethod] {
+        &[
+            HashMethod::Argon2id,
+            HashMethod::Bcrypt,
+            HashMethod::Pbkdf2Sha512,
+            HashMethod::Pbkdf2Sha256,
+            HashMethod::Crypt,
+            HashMethod::Ssha512,
+            HashMethod::Ssha256,
+            HashMethod::Ssha,
+            HashMethod::Sha512,
+            HashMethod::Sha256,
+            HashMethod::Smd5,
+            HashMethod::Md5,
+            HashMethod::Plain,
+        ]
+    }
+
+    /// Returns true if this method ranks above `other` in [`all`](Self::all).
+    pub fn is_stronger_than(&self, other: HashMethod) -> bool {
+        let rank = |m: HashMethod| Self::all().iter().position(|&x| x == m);
+        rank(*self) < rank(other)
+    }
+
+    /// Returns security metadata for this method.
+    pub fn describe(&self) -> HashMethodInfo {
+        HashMethodInfo {
+            scheme: self.scheme(),
+            secure: self.is_secure(),
+            salted: !matches!(
+                self,
+                HashMethod::Sha512 | HashMethod::Sha256 | HashMethod::Md5 | HashMethod::Plain
+            ),
+            legacy: matches!(
+                self,
+                HashMethod::Ssha
+                    | HashMethod::Sha512
+                    | HashMethod::Sha256
+                    | HashMethod::Md5
+                    | HashMethod::Smd5
+            ),
+        }
+    }
+
     /// Returns the LDAP scheme prefix for this method.
     pub fn scheme(&self) -> &'static str {
         match self {
@@ -54,54 +161,90 @@ impl HashMethod {
             HashMethod::Md5 => "{MD5}",
             HashMethod::Smd5 => "{SMD5}",
             HashMethod::Plain => "",
+            HashMethod::Pbkdf2Sha256 => "{PBKDF2-SHA256}",
+            HashMethod::Pbkdf2Sha512 => "{PBKDF2-SHA512}",
+            HashMethod::Crypt => "{CRYPT}",
         }
     }
 
     /// Parses a hash method from a string.
+    ///
+    /// Accepts any spelling in the scheme table, with or without braces
+    /// and in any case: `{SSHA512}`, `ssha512`, `pbkdf2_sha256`, `clear`.
     #[allow(clippy::should_implement_trait)]
     pub fn from_str(s: &str) -> Option<Self> {
-        match s.to_uppercase().as_str() {
-            "SSHA" | "{SSHA}" => Some(HashMethod::Ssha),
-            "ARGON2" | "ARGON2ID" | "{ARGON2}" => Some(HashMethod::Argon2id),
-            "BCRYPT" | "{BCRYPT}" => Some(HashMethod::Bcrypt),
-            "SHA512" | "{SHA512}" => Some(HashMethod::Sha512),
-            "SSHA512" | "{SSHA512}" => Some(HashMethod::Ssha512),
-            "SHA256" | "{SHA256}" => Some(HashMethod::Sha256),
-            "SSHA256" | "{SSHA256}" => Some(HashMethod::Ssha256),
-            "MD5" | "{MD5}" => Some(HashMethod::Md5),
-            "SMD5" | "{SMD5}" => Some(HashMethod::Smd5),
-            "PLAIN" | "CLEAR" | "CLEARTEXT" => Some(HashMethod::Plain),
-            _ => None,
-        }
+        let name = spelling_name(s);
+        SCHEME_TABLE
+            .iter()
+            .find(|(spelling, _)| spelling_name(spelling).eq_ignore_ascii_case(name))
+            .map(|&(_, method)| method)
     }
 
     /// Detects the hash method from an LDAP password hash.
+    ///
+    /// Recognizes every `{SCHEME}` prefix in the scheme table (ignoring
+    /// case), plus bare bcrypt (`$2`) and crypt (`$1$`, `$5$`, `$6$`) hashes.
     pub fn detect(hash: &str) -> Option<Self> {
-        let upper = hash.to_uppercase();
-        if upper.starts_with("{SSHA512}") {
-            Some(HashMethod::Ssha512)
-        } else if upper.starts_with("{SSHA256}") {
-            Some(HashMethod::Ssha256)
-        } else if upper.starts_with("{SSHA}") {
-            Some(HashMethod::Ssha)
-        } else if upper.starts_with("{SHA512}") {
-            Some(HashMethod::Sha512)
-        } else if upper.starts_with("{SHA256}") {
-            Some(HashMethod::Sha256)
-        } else if upper.starts_with("{ARGON2}") {
-            Some(HashMethod::Argon2id)
-        } else if upper.starts_with("{BCRYPT}") || hash.starts_with("$2") {
-            Some(HashMethod::Bcrypt)
-        } else if upper.starts_with("{SMD5}") {
-            Some(HashMethod::Smd5)
-        } else if upper.starts_with("{MD5}") {
-            Some(HashMethod::Md5)
-        } else {
+        SCHEME_TABLE
+            .iter()
+            .filter(|(spelling, _)| spelling.starts_with('{'))
+            .find(|(prefix, _)| {
+                hash.get(..prefix.len())
+                    .is_some_and(|head| head.eq_ignore_ascii_case(prefix))
+            })
+            .map(|&(_, method)| method)
+            .or_else(|| {
+                if hash.starts_with("$2") {
+                    Some(HashMethod::Bcrypt)
+                } else if is_crypt_id(hash) {
+                    Some(HashMethod::Crypt)
+                } else {
+                    None
+                }
+            })
+    }
+
+    /// Like [`detect`](Self::detect), but treats a schemeless value as
+    /// plain text, as OpenLDAP does for `userPassword`.
+    ///
+    /// Returns `Some(HashMethod::Plain)` when the value has no `{SCHEME}`
+    /// prefix and contains no control characters, and `None` for an unknown
+    /// scheme or unprintable data, so corrupt values stay distinguishable
+    /// from cleartext.
+    ///
+    /// **Security:** a `Plain` result means the password is stored in
+    /// cleartext and anyone able to read the attribute can log in. Only
+    /// use this where such legacy values are expected, and rehash them on
+    /// the next successful login.
+    pub fn detect_or_plain(hash: &str) -> Option<Self> {
+        if let Some(method) = Self::detect(hash) {
+            return Some(method);
+        }
+
+        let has_scheme = hash.starts_with('{') && hash.contains('}');
+        if has_scheme || hash.chars().any(char::is_control) {
             None
+        } else {
+            Some(HashMethod::Plain)
         }
     }
 
+    /// Every `{SCHEME}` storage prefix recognized by [`detect`](Self::detect).
+    pub fn all_schemes() -> &'static [&'static str] {
+        static SCHEMES: OnceLock<Vec<&'static str>> = OnceLock::new();
+        SCHEMES.get_or_init(|| {
+            SCHEME_TABLE
+                .iter()
+                .map(|&(spelling, _)| spelling)
+                .filter(|spelling| spelling.starts_with('{'))
+                .collect()
+        })
+    }
+
     /// Returns true if this is a secure hash method.
+    ///
+    /// `{CRYPT}` is not: its security depends on the embedded algorithm id,
+    /// which only [`PasswordHash::is_secure`] can see.
     pub fn is_secure(&self) -> bool {
         matches!(
             self,
@@ -110,6 +253,8 @@ impl HashMethod {
                 | HashMethod::Ssha512
                 | HashMethod::Ssha256
                 | HashMethod::Ssha
+                | HashMethod::Pbkdf2Sha256
+                | HashMethod::Pbkdf2Sha512
         )
     }
 }
@@ -120,8 +265,31 @@ impl fmt::Display for HashMethod {
     }
 }
 
+/// Serializes as the scheme string, e.g. `"{ARGON2}"` (`""` for plain).
+impl Serialize for HashMethod {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.scheme())
+    }
+}
+
+/// Accepts any spelling known to [`HashMethod::from_str`], or `""` for plain.
+impl<'de> Deserialize<'de> for HashMethod {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+        let scheme = String::deserialize(deserializer)?;
+        if scheme.is_empty() {
+            return Ok(HashMethod::Plain);
+        }
+        HashMethod::from_str(&scheme)
+            .ok_or_else(|| serde::de::Error::custom(format!("unknown hash method: {}", scheme)))
+    }
+}
+
 /// Represents a password hash with its method.
-#[derive(Debug, Clone)]
+///
+/// Deserializing checks that `hash` carries the scheme of `method` (plain
+/// hashes are not checked) and fails on a mismatch.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(try_from = "RawPasswordHash")]
 pub struct PasswordHash {
     /// The hash method used.
     pub method: HashMethod,
@@ -136,8 +304,11 @@ impl PasswordHash {
     }
 
     /// Parses an LDAP password hash.
+    ///
+    /// Hashes carrying the [`PEPPER_MARKER`] parse as their inner method,
+    /// but only a [`PepperedPasswordHasher`] can verify them.
     pub fn parse(hash: &str) -> Result<Self> {
-        let method = HashMethod::detect(hash)
+        let method = HashMethod::detect(unpeppered(hash))
             .ok_or_else(|| HeraclesError::UnsupportedHashMethod(hash.to_string()))?;
         Ok(Self {
             method,
@@ -145,14 +316,136 @@ impl PasswordHash {
         })
     }
 
-    /// Returns the hash value without the scheme prefix.
+    /// Returns the hash value without the scheme prefix (and without the
+    /// [`PEPPER_MARKER`] of a peppered hash).
     pub fn value(&self) -> &str {
+        let hash = unpeppered(&self.hash);
         let scheme_len = self.method.scheme().len();
-        if self.hash.len() > scheme_len {
-            &self.hash[scheme_len..]
+        if hash.len() > scheme_len {
+            &hash[scheme_len..]
         } else {
-            &self.hash
+            hash
+        }
+    }
+
+    /// Returns true if this hash is considered secure.
+    ///
+    /// Same as [`HashMethod::is_secure`], except that `{CRYPT}` hashes are
+    /// secure when they carry a SHA-crypt (`$5$`, `$6$`) or bcrypt id.
+    pub fn is_secure(&self) -> bool {
+        if self.method == HashMethod::Crypt {
+            let value = strip_scheme(unpeppered(&self.hash), "{CRYPT}");
+            return ["$5$", "$6$", "$2a$", "$2b$", "$2y$"]
+                .iter()
+                .any(|id| value.starts_with(id));
+        }
+        self.method.is_secure()
+    }
+}
+
+/// Unvalidated form of [`PasswordHash`] used during deserialization.
+#[derive(Deserialize)]
+struct RawPasswordHash {
+    method: HashMethod,
+    hash: String,
+}
+
+impl TryFrom<RawPasswordHash> for PasswordHash {
+    type Error = HeraclesError;
+
+    fn try_from(raw: RawPasswordHash) -> Result<Self> {
+        if raw.method != HashMethod::Plain {
+            let detected = PasswordHash::parse(&raw.hash)?.method;
+            if detected != raw.method {
+                return Err(HeraclesError::PasswordHash(format!(
+                    "hash scheme {} does not match method {}",
+                    detected, raw.method
+                )));
+            }
+        }
+        Ok(PasswordHash::new(raw.method, raw.hash))
+    }
+}
+
+/// Argon2id cost parameters.
+///
+/// Defaults match the `argon2` crate defaults (OWASP recommended minimum).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Argon2Params {
+    /// Memory cost in KiB.
+    pub m_cost: u32,
+    /// Number of iterations.
+    pub t_cost: u32,
+    /// Degree of parallelism.
+    pub p_cost: u32,
+}
+
+impl Argon2Params {
+    /// Creates new Argon2 parameters.
+    pub fn new(m_cost: u32, t_cost: u32, p_cost: u32) -> Self {
+        Self {
+            m_cost,
+            t_cost,
+            p_cost,
+        }
+    }
+
+    /// Validates the parameters against the Argon2 limits.
+    pub fn validate(&self) -> Result<()> {
+        self.to_argon2_params().map(|_| ())
+    }
+
+    fn to_argon2_params(self) -> Result<Params> {
+        Params::new(self.m_cost, self.t_cost, self.p_cost, None)
+            .map_err(|e| HeraclesError::PasswordHash(format!("Invalid Argon2 parameters: {}", e)))
+    }
+}
+
+impl Default for Argon2Params {
+    fn default() -> Self {
+        Self {
+            m_cost: Params::DEFAULT_M_COST,
+            t_cost: Params::DEFAULT_T_COST,
+            p_cost: Params::DEFAULT_P_COST,
+        }
+    }
+}
+
+/// Minimum bcrypt cost accepted by the `bcrypt` crate.
+pub const BCRYPT_MIN_COST: u32 = 4;
+
+/// Maximum bcrypt cost accepted by the `bcrypt` crate.
+pub const BCRYPT_MAX_COST: u32 = 31;
+
+/// Default PBKDF2 iteration count (passlib's default for PBKDF2-SHA256).
+pub const PBKDF2_DEFAULT_ITERATIONS: u32 = 29_000;
+
+/// Tunable cost parameters for hashing.
+///
+/// Fields left as `None` fall back to the library defaults.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct HashParams {
+    /// Argon2id cost parameters.
+    pub argon2: Option<Argon2Params>,
+    /// bcrypt cost factor.
+    pub bcrypt_cost: Option<u32>,
+}
+
+impl HashParams {
+    /// Validates all parameters that are set.
+    pub fn validate(&self) -> Result<()> {
+        if let Some(argon2) = &self.argon2 {
+            argon2.validate()?;
+        }
+        if let Some(cost) = self.bcrypt_cost {
+            if !(BCRYPT_MIN_COST..=BCRYPT_MAX_COST).contains(&cost) {
+                return Err(HeraclesError::PasswordHash(format!(
+                    "Invalid bcrypt cost {}: must be between {} and {}",
+                    cost, BCRYPT_MIN_COST, BCRYPT_MAX_COST
+                )));
+            }
         }
+        Ok(())
     }
 }
 
@@ -184,62 +477,498 @@ impl PasswordVerifier for DefaultPasswordHasher {
     }
 }
 
+impl DefaultPasswordHasher {
+    /// Returns a hasher that peppers passwords with a server-side `key`.
+    pub fn with_pepper(key: Vec<u8>) -> PepperedPasswordHasher {
+        PepperedPasswordHasher { key }
+    }
+}
+
+/// Prefix marking a hash produced by a [`PepperedPasswordHasher`].
+pub const PEPPER_MARKER: &str = "{PEPPERED}";
+
+/// Password hasher that HMACs passwords with a server-side secret first.
+///
+/// The password is replaced by the base64 HMAC-SHA256 of the password under
+/// the pepper key, then hashed with the chosen method as usual. A leaked
+/// directory dump alone can't be brute-forced without the key.
+///
+/// Peppered hashes are stored as [`PEPPER_MARKER`] followed by the regular
+/// value, e.g. `{PEPPERED}{ARGON2}$argon2id$...`. Plain verification
+/// rejects them with an error rather than reporting a mismatch, and this
+/// hasher rejects unmarked hashes the same way. The LDAP server itself
+/// can't verify peppered hashes, so simple binds won't work against them.
+#[derive(Clone)]
+pub struct PepperedPasswordHasher {
+    key: Vec<u8>,
+}
+
+impl PepperedPasswordHasher {
+    fn pepper(&self, password: &str) -> String {
+        let mut mac =
+            Hmac::<Sha256>::new_from_slice(&self.key).expect("HMAC accepts keys of any length");
+        mac.update(password.as_bytes());
+        BASE64.encode(mac.finalize().into_bytes())
+    }
+}
+
+impl fmt::Debug for PepperedPasswordHasher {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PepperedPasswordHasher")
+            .field("key", &"<redacted>")
+            .finish()
+    }
+}
+
+impl PasswordHasher for PepperedPasswordHasher {
+    fn hash(&self, password: &str, method: HashMethod) -> Result<PasswordHash> {
+        let inner = hash_password(&self.pepper(password), method)?;
+        Ok(PasswordHash::new(
+            method,
+            format!("{}{}", PEPPER_MARKER, inner.hash),
+        ))
+    }
+}
+
+impl PasswordVerifier for PepperedPasswordHasher {
+    fn verify(&self, password: &str, hash: &PasswordHash) -> Result<bool> {
+        let inner = hash.hash.strip_prefix(PEPPER_MARKER).ok_or_else(|| {
+            HeraclesError::PasswordVerify("hash was not created with a pepper".to_string())
+        })?;
+        verify_password(
+            &self.pepper(password),
+            &PasswordHash::new(hash.method, inner.to_string()),
+        )
+    }
+}
+
 /// Hashes a password using the specified method.
 pub fn hash_password(password: &str, method: HashMethod) -> Result<PasswordHash> {
+    hash_password_with_params(password, method, &HashParams::default())
+}
+
+/// Hashes a password using the specified method and cost parameters.
+///
+/// Parameters that don't apply to `method` are ignored.
+pub fn hash_password_with_params(
+    password: &str,
+    method: HashMethod,
+    params: &HashParams,
+) -> Result<PasswordHash> {
+    params.validate()?;
+
     let hash = match method {
-        HashMethod::Ssha => hash_ssha(password)?,
-        HashMethod::Argon2id => hash_argon2(password)?,
-        HashMethod::Bcrypt => hash_bcrypt(password)?,
+        HashMethod::Ssha | HashMethod::Ssha512 | HashMethod::Ssha256 | HashMethod::Smd5 => {
+            hash_ssha_with(password, &SshaOptions::new(method))?.hash
+        }
+        HashMethod::Argon2id => hash_argon2(password, params.argon2.unwrap_or_default())?,
+        HashMethod::Bcrypt => hash_bcrypt(password, params.bcrypt_cost.unwrap_or(DEFAULT_COST))?,
         HashMethod::Sha512 => hash_sha512(password),
-        HashMethod::Ssha512 => hash_ssha512(password)?,
         HashMethod::Sha256 => hash_sha256(password),
-        HashMethod::Ssha256 => hash_ssha256(password)?,
         HashMethod::Md5 => hash_md5(password),
-        HashMethod::Smd5 => hash_smd5(password)?,
         HashMethod::Plain => password.to_string(),
+        HashMethod::Pbkdf2Sha256 | HashMethod::Pbkdf2Sha512 => hash_pbkdf2(password, method),
+        HashMethod::Crypt => hash_crypt(password)?,
     };
 
     Ok(PasswordHash::new(method, hash))
 }
 
+/// Length of the dummy password hashed by [`benchmark_method`].
+const BENCHMARK_PASSWORD_LEN: usize = 16;
+
+/// Times a single hash with `method` and `params` on this machine.
+///
+/// Hashes a fixed-length dummy password once, so a startup self-tune can
+/// pick parameters that hit a latency target (around 250ms for logins).
+pub fn benchmark_method(method: HashMethod, params: &HashParams) -> Result<Duration> {
+    params.validate()?;
+    let password = "x".repeat(BENCHMARK_PASSWORD_LEN);
+
+    let start = Instant::now();
+    hash_password_with_params(&password, method, params)?;
+    Ok(start.elapsed())
+}
+
+/// Minimum salt length accepted by [`hash_ssha_with`].
+pub const SSHA_MIN_SALT_LEN: usize = 4;
+
+/// Options for the salted digest schemes ({SSHA}, {SSHA256}, {SSHA512},
+/// {SMD5}).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SshaOptions {
+    /// Salted digest scheme to hash with. Defaults to {SSHA}.
+    pub method: HashMethod,
+    /// Salt length in bytes. `None` keeps the method default: 8 bytes for
+    /// {SSHA} and {SMD5}, 16 for {SSHA256} and {SSHA512}.
+    pub salt_len: Option<usize>,
+}
+
+impl SshaOptions {
+    /// Options for `method` with its default salt length.
+    pub fn new(method: HashMethod) -> Self {
+        Self {
+            method,
+            salt_len: None,
+        }
+    }
+
+    /// Sets the salt length in bytes.
+    pub fn salt_len(mut self, len: usize) -> Self {
+        self.salt_len = Some(len);
+        self
+    }
+}
+
+impl Default for SshaOptions {
+    fn default() -> Self {
+        Self::new(HashMethod::Ssha)
+    }
+}
+
+/// Hashes a password with a salted digest scheme and explicit salt length.
+///
+/// `options.method` must be one of the salted digest schemes; the salt must
+/// be at least [`SSHA_MIN_SALT_LEN`] bytes. Verification reads the salt
+/// length from the stored value, so any length verifies.
+pub fn hash_ssha_with(password: &str, options: &SshaOptions) -> Result<PasswordHash> {
+    let method = options.method;
+    let default_len = match method {
+        HashMethod::Ssha | HashMethod::Smd5 => 8,
+        HashMethod::Ssha256 | HashMethod::Ssha512 => 16,
+        other => {
+            return Err(HeraclesError::UnsupportedHashMethod(format!(
+                "{} is not a salted digest scheme",
+                other
+            )))
+        }
+    };
+
+    let salt_len = options.salt_len.unwrap_or(default_len);
+    if salt_len < SSHA_MIN_SALT_LEN {
+        return Err(HeraclesError::PasswordHash(format!(
+            "Salt length {} is below the minimum of {} bytes",
+            salt_len, SSHA_MIN_SALT_LEN
+        )));
+    }
+
+    let mut salt = vec![0u8; salt_len];
+    rand::thread_rng().fill_bytes(&mut salt);
+
+    let hash = match method {
+        HashMethod::Ssha => salted_digest::<sha1::Sha1>(password, &salt),
+        HashMethod::Ssha256 => salted_digest::<Sha256>(password, &salt),
+        HashMethod::Ssha512 => salted_digest::<Sha512>(password, &salt),
+        _ => salted_digest::<md5::Md5>(password, &salt),
+    };
+    Ok(PasswordHash::new(
+        method,
+        format!("{}{}", method.scheme(), BASE64.encode(hash)),
+    ))
+}
+
+/// Options for password verification.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct VerifyOptions {
+    /// Also accept salted hashes stored as `salt || digest`.
+    ///
+    /// RFC 2307 style salted schemes ({SSHA}, {SSHA256}, {SSHA512}, {SMD5})
+    /// store `digest || salt`, but some FusionDirectory-era tools write the
+    /// salt first. The two layouts can't be told apart from the bytes alone,
+    /// so when enabled the salt-first layout is tried only after the standard
+    /// one fails to match.
+    pub lenient_salt_layout: bool,
+}
+
 /// Verifies a password against a hash.
 pub fn verify_password(password: &str, hash: &PasswordHash) -> Result<bool> {
+    verify_password_with_options(password, hash, &VerifyOptions::default())
+}
+
+/// Verifies a password against a stored hash string.
+///
+/// Detects the method from the scheme, then verifies. Returns
+/// `HeraclesError::UnsupportedHashMethod` if the method can't be detected.
+/// Use [`verify_password`] when the hash is already parsed.
+pub fn verify(password: &str, stored: &str) -> Result<bool> {
+    verify_password(password, &PasswordHash::parse(stored)?)
+}
+
+/// Verifies a password against every stored value of a multi-valued
+/// `userPassword`, returning the method of the first value that matches.
+///
+/// Meant for dual-write migrations where an old and a new hash coexist;
+/// the returned method tells the caller which value to keep. Every value
+/// is verified even after a match so the time taken doesn't reveal its
+/// position. A match wins over errors from other values; otherwise the
+/// first error (e.g. an unsupported scheme) is returned.
+pub fn verify_against_values(password: &str, values: &[String]) -> Result<Option<HashMethod>> {
+    let mut matched = None;
+    let mut first_err = None;
+
+    for value in values {
+        let result = PasswordHash::parse(value)
+            .and_then(|hash| verify_password(password, &hash).map(|ok| ok.then_some(hash.method)));
+        match result {
+            Ok(Some(method)) => {
+                matched = matched.or(Some(method));
+            }
+            Ok(None) => {}
+            Err(e) => {
+                first_err = first_err.or(Some(e));
+            }
+        }
+    }
+
+    match (matched, first_err) {
+        (Some(method), _) => Ok(Some(method)),
+        (None, Some(e)) => Err(e),
+        (None, None) => Ok(None),
+    }
+}
+
+/// Verifies a password against a hash with explicit options.
+pub fn verify_password_with_options(
+    password: &str,
+    hash: &PasswordHash,
+    options: &VerifyOptions,
+) -> Result<bool> {
+    if hash.hash.starts_with(PEPPER_MARKER) {
+        return Err(HeraclesError::PasswordVerify(
+            "hash is peppered; verify it with the pepper key".to_string(),
+        ));
+    }
+
+    let lenient = options.lenient_salt_layout;
     match hash.method {
-        HashMethod::Ssha => verify_ssha(password, &hash.hash),
+        HashMethod::Ssha => verify_ssha(password, &hash.hash, lenient),
         HashMethod::Argon2id => verify_argon2(password, &hash.hash),
         HashMethod::Bcrypt => verify_bcrypt(password, &hash.hash),
         HashMethod::Sha512 => Ok(verify_sha512(password, &hash.hash)),
-        HashMethod::Ssha512 => verify_ssha512(password, &hash.hash),
+        HashMethod::Ssha512 => verify_ssha512(password, &hash.hash, lenient),
         HashMethod::Sha256 => Ok(verify_sha256(password, &hash.hash)),
-        HashMethod::Ssha256 => verify_ssha256(password, &hash.hash),
+        HashMethod::Ssha256 => verify_ssha256(password, &hash.hash, lenient),
         HashMethod::Md5 => Ok(verify_md5(password, &hash.hash)),
-        HashMethod::Smd5 => verify_smd5(password, &hash.hash),
+        HashMethod::Smd5 => verify_smd5(password, &hash.hash, lenient),
         HashMethod::Plain => Ok(password == hash.hash),
+        HashMethod::Pbkdf2Sha256 | HashMethod::Pbkdf2Sha512 => verify_pbkdf2(password, hash),
+        HashMethod::Crypt => verify_crypt(password, &hash.hash),
     }
 }
 
-// ============ SSHA (Salted SHA-1) ============
+/// Outcome of a detailed password verification.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct VerifyResult {
+    /// Whether the password matched the stored hash.
+    pub matched: bool,
+    /// The method detected from the stored hash.
+    pub method: HashMethod,
+    /// Whether the detected method is considered secure.
+    pub is_secure: bool,
+}
+
+/// Verifies a password against a stored hash string and reports the
+/// detected method alongside the result.
+pub fn verify_password_detailed(password: &str, hash_str: &str) -> Result<VerifyResult> {
+    let hash = PasswordHash::parse(hash_str)?;
+    let matched = verify_password(password, &hash)?;
+    Ok(VerifyResult {
+        matched,
+        method: hash.method,
+        is_secure: hash.is_secure(),
+    })
+}
 
-fn hash_ssha(password: &str) -> Result<String> {
-    use sha1::{Digest as Sha1Digest, Sha1};
+/// How successful verifications against insecure methods are reported.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum VerifyPolicy {
+    /// Report any match as [`VerifyVerdict::Matched`].
+    #[default]
+    Permissive,
+    /// Report a match against a method failing [`HashMethod::is_secure`] as
+    /// [`VerifyVerdict::MatchedButInsecure`], so the caller can flag the
+    /// account or force a reset instead of silently re-hashing.
+    FlagInsecure,
+}
 
-    let mut salt = [0u8; 8];
-    rand::thread_rng().fill_bytes(&mut salt);
+/// Outcome of [`verify_with_policy`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VerifyVerdict {
+    /// The password does not match.
+    Mismatch,
+    /// The password matches.
+    Matched,
+    /// The password matches, but the stored hash uses an insecure method.
+    MatchedButInsecure(HashMethod),
+}
 
-    let mut hasher = Sha1::new();
-    hasher.update(password.as_bytes());
-    hasher.update(salt);
-    let digest = hasher.finalize();
+impl VerifyVerdict {
+    /// Returns true if the password matched, whatever the method.
+    pub fn matched(&self) -> bool {
+        !matches!(self, VerifyVerdict::Mismatch)
+    }
+
+    /// Returns true if the password matched an insecure hash.
+    pub fn matched_but_insecure(&self) -> bool {
+        matches!(self, VerifyVerdict::MatchedButInsecure(_))
+    }
+}
 
-    let mut hash_with_salt = Vec::with_capacity(digest.len() + salt.len());
-    hash_with_salt.extend_from_slice(&digest);
-    hash_with_salt.extend_from_slice(&salt);
+/// Verifies a password and reports insecure matches according to `policy`.
+pub fn verify_with_policy(
+    password: &str,
+    hash: &PasswordHash,
+    policy: VerifyPolicy,
+) -> Result<VerifyVerdict> {
+    if !verify_password(password, hash)? {
+        return Ok(VerifyVerdict::Mismatch);
+    }
+
+    if policy == VerifyPolicy::FlagInsecure && !hash.is_secure() {
+        return Ok(VerifyVerdict::MatchedButInsecure(hash.method));
+    }
 
-    Ok(format!("{{SSHA}}{}", BASE64.encode(&hash_with_salt)))
+    Ok(VerifyVerdict::Matched)
 }
 
-fn verify_ssha(password: &str, hash: &str) -> Result<bool> {
-    use sha1::{Digest as Sha1Digest, Sha1};
+/// Returns true if `hash` should be re-hashed with `target` on next login.
+///
+/// A hash needs rehashing when its method differs from `target`, when an
+/// Argon2 hash has any m/t/p cost below the defaults, or when a bcrypt
+/// hash has a cost below `DEFAULT_COST`. See [`needs_rehash_with_params`]
+/// to compare against other minimums.
+pub fn needs_rehash(hash: &PasswordHash, target: HashMethod) -> bool {
+    needs_rehash_with_params(hash, target, &HashParams::default())
+}
+
+/// Like [`needs_rehash`], using `min` as the minimum costs.
+///
+/// Unset fields of `min` fall back to the defaults. Hashes whose costs
+/// can't be read are always reported as needing a rehash.
+pub fn needs_rehash_with_params(hash: &PasswordHash, target: HashMethod, min: &HashParams) -> bool {
+    if hash.method != target {
+        return true;
+    }
+
+    match hash.method {
+        HashMethod::Argon2id => match argon2_costs(&hash.hash) {
+            Some(costs) => {
+                let min = min.argon2.unwrap_or_default();
+                costs.m_cost < min.m_cost || costs.t_cost < min.t_cost || costs.p_cost < min.p_cost
+            }
+            None => true,
+        },
+        HashMethod::Bcrypt => match bcrypt_cost(&hash.hash) {
+            Some(cost) => cost < min.bcrypt_cost.unwrap_or(DEFAULT_COST),
+            None => true,
+        },
+        _ => false,
+    }
+}
+
+/// Outcome of [`verify_and_upgrade`].
+#[derive(Debug, Clone)]
+pub struct VerifyOutcome {
+    /// Whether the password matched the stored hash.
+    pub matched: bool,
+    /// A fresh `target` hash to persist, when the stored one was weaker.
+    pub upgraded: Option<PasswordHash>,
+}
+
+/// Verifies a password and re-hashes it when the stored hash is weak.
+///
+/// On a match, the stored hash is upgraded to `target` when its method is
+/// weaker than `target` (see [`HashMethod::is_stronger_than`]), when it is
+/// insecure and a fresh `target` hash is secure (e.g. `{CRYPT}$1$` with a
+/// `Crypt` target, which now produces `$6$`), or when it uses `target` with
+/// costs below the defaults (see [`needs_rehash`]). A stronger stored
+/// method is never downgraded. The new hash is returned for the caller to
+/// write back; on a mismatch `upgraded` is always `None`.
+pub fn verify_and_upgrade(
+    password: &str,
+    stored: &str,
+    target: HashMethod,
+) -> Result<VerifyOutcome> {
+    let hash = PasswordHash::parse(stored)?;
+    if !verify_password(password, &hash)? {
+        return Ok(VerifyOutcome {
+            matched: false,
+            upgraded: None,
+        });
+    }
+
+    let upgraded = if target.is_stronger_than(hash.method)
+        || (hash.method == target && needs_rehash(&hash, target))
+    {
+        Some(hash_password(password, target)?)
+    } else if !hash.is_secure() {
+        // Compare the actual hashes: `Crypt` covers both MD5-crypt and SHA-crypt
+        Some(hash_password(password, target)?).filter(PasswordHash::is_secure)
+    } else {
+        None
+    };
+    Ok(VerifyOutcome {
+        matched: true,
+        upgraded,
+    })
+}
+
+/// Computes the NT hash of a password for Samba's `sambaNTPassword`.
+///
+/// The password is UTF-16LE encoded and hashed with MD4, matching
+/// `smbpasswd`. Returns a bare 32-character uppercase hex string, not a
+/// `{SCHEME}` userPassword value, so it is not recognized by
+/// [`verify_password`] or [`HashMethod::detect`].
+pub fn nt_hash(password: &str) -> String {
+    let utf16: Vec<u8> = password.encode_utf16().flat_map(u16::to_le_bytes).collect();
+    md4::Md4::digest(&utf16)
+        .iter()
+        .map(|b| format!("{:02X}", b))
+        .collect()
+}
+
+/// Reads the cost parameters of an encoded Argon2id hash.
+fn argon2_costs(hash: &str) -> Option<Argon2Params> {
+    let value = strip_scheme(unpeppered(hash), "{ARGON2}");
+    let parsed = argon2::PasswordHash::new(value).ok()?;
+    if parsed.algorithm != Algorithm::Argon2id.ident() {
+        return None;
+    }
+    let params = Params::try_from(&parsed).ok()?;
+    Some(Argon2Params::new(
+        params.m_cost(),
+        params.t_cost(),
+        params.p_cost(),
+    ))
+}
+
+/// Reads the cost factor of a bcrypt hash (`$2b$12$...`).
+fn bcrypt_cost(hash: &str) -> Option<u32> {
+    strip_scheme(unpeppered(hash), "{BCRYPT}")
+        .strip_prefix('$')?
+        .split('$')
+        .nth(1)?
+        .parse()
+        .ok()
+}
+
+/// Strips the [`PEPPER_MARKER`], if present, leaving the inner hash.
+pub(crate) fn unpeppered(hash: &str) -> &str {
+    hash.strip_prefix(PEPPER_MARKER).unwrap_or(hash)
+}
+
+/// Strips a `{SCHEME}` prefix, ignoring case, if present.
+fn strip_scheme<'a>(hash: &'a str, scheme: &str) -> &'a str {
+    match hash.get(..scheme.len()) {
+        Some(prefix) if prefix.eq_ignore_ascii_case(scheme) => &hash[scheme.len()..],
+        _ => hash,
+    }
+}
+
+// ============ SSHA (Salted SHA-1) ============
+
+fn verify_ssha(password: &str, hash: &str, lenient: bool) -> Result<bool> {
+    use sha1::Sha1;
 
     let hash_value = hash
         .strip_prefix("{SSHA}")
@@ -256,21 +985,18 @@ fn verify_ssha(password: &str, hash: &str) -> Result<bool> {
         ));
     }
 
-    let (stored_hash, salt) = decoded.split_at(20);
-
-    let mut hasher = Sha1::new();
-    hasher.update(password.as_bytes());
-    hasher.update(salt);
-    let computed = hasher.finalize();
-
-    Ok(constant_time_eq(&computed, stored_hash))
+    Ok(salted_digest_matches::<Sha1>(password, &decoded, lenient))
 }
 
 // ============ Argon2 ============
 
-fn hash_argon2(password: &str) -> Result<String> {
+fn hash_argon2(password: &str, params: Argon2Params) -> Result<String> {
     let salt = SaltString::generate(&mut OsRng);
-    let argon2 = Argon2::default();
+    let argon2 = Argon2::new(
+        Algorithm::Argon2id,
+        Version::V0x13,
+        params.to_argon2_params()?,
+    );
 
     let hash = argon2
         .hash_password(password.as_bytes(), &salt)
@@ -296,20 +1022,16 @@ fn verify_argon2(password: &str, hash: &str) -> Result<bool> {
 
 // ============ bcrypt ============
 
-fn hash_bcrypt(password: &str) -> Result<String> {
-    let hash = bcrypt_hash(password, DEFAULT_COST)
+fn hash_bcrypt(password: &str, cost: u32) -> Result<String> {
+    let hash = bcrypt_hash(password, cost)
         .map_err(|e| HeraclesError::PasswordHash(format!("bcrypt hash failed: {}", e)))?;
 
     Ok(format!("{{BCRYPT}}{}", hash))
 }
 
 fn verify_bcrypt(password: &str, hash: &str) -> Result<bool> {
-    let hash_value = hash
-        .strip_prefix("{BCRYPT}")
-        .or_else(|| hash.strip_prefix("{bcrypt}"))
-        .unwrap_or(hash);
-
-    bcrypt_verify(password, hash_value)
+    // The bcrypt crate accepts the $2a$, $2b$, $2x$ and $2y$ variants.
+    bcrypt_verify(password, strip_scheme(hash, "{BCRYPT}"))
         .map_err(|e| HeraclesError::PasswordVerify(format!("bcrypt verify failed: {}", e)))
 }
 
@@ -336,23 +1058,7 @@ fn verify_sha512(password: &str, hash: &str) -> bool {
     hash_value == computed_b64
 }
 
-fn hash_ssha512(password: &str) -> Result<String> {
-    let mut salt = [0u8; 16];
-    rand::thread_rng().fill_bytes(&mut salt);
-
-    let mut hasher = Sha512::new();
-    hasher.update(password.as_bytes());
-    hasher.update(salt);
-    let digest = hasher.finalize();
-
-    let mut hash_with_salt = Vec::with_capacity(digest.len() + salt.len());
-    hash_with_salt.extend_from_slice(&digest);
-    hash_with_salt.extend_from_slice(&salt);
-
-    Ok(format!("{{SSHA512}}{}", BASE64.encode(&hash_with_salt)))
-}
-
-fn verify_ssha512(password: &str, hash: &str) -> Result<bool> {
+fn verify_ssha512(password: &str, hash: &str, lenient: bool) -> Result<bool> {
     let hash_value = hash
         .strip_prefix("{SSHA512}")
         .or_else(|| hash.strip_prefix("{ssha512}"))
@@ -368,14 +1074,7 @@ fn verify_ssha512(password: &str, hash: &str) -> Result<bool> {
         ));
     }
 
-    let (stored_hash, salt) = decoded.split_at(64);
-
-    let mut hasher = Sha512::new();
-    hasher.update(password.as_bytes());
-    hasher.update(salt);
-    let computed = hasher.finalize();
-
-    Ok(constant_time_eq(&computed, stored_hash))
+    Ok(salted_digest_matches::<Sha512>(password, &decoded, lenient))
 }
 
 // ============ SHA-256 ============
@@ -401,23 +1100,7 @@ fn verify_sha256(password: &str, hash: &str) -> bool {
     hash_value == computed_b64
 }
 
-fn hash_ssha256(password: &str) -> Result<String> {
-    let mut salt = [0u8; 16];
-    rand::thread_rng().fill_bytes(&mut salt);
-
-    let mut hasher = Sha256::new();
-    hasher.update(password.as_bytes());
-    hasher.update(salt);
-    let digest = hasher.finalize();
-
-    let mut hash_with_salt = Vec::with_capacity(digest.len() + salt.len());
-    hash_with_salt.extend_from_slice(&digest);
-    hash_with_salt.extend_from_slice(&salt);
-
-    Ok(format!("{{SSHA256}}{}", BASE64.encode(&hash_with_salt)))
-}
-
-fn verify_ssha256(password: &str, hash: &str) -> Result<bool> {
+fn verify_ssha256(password: &str, hash: &str, lenient: bool) -> Result<bool> {
     let hash_value = hash
         .strip_prefix("{SSHA256}")
         .or_else(|| hash.strip_prefix("{ssha256}"))
@@ -433,14 +1116,7 @@ fn verify_ssha256(password: &str, hash: &str) -> Result<bool> {
         ));
     }
 
-    let (stored_hash, salt) = decoded.split_at(32);
-
-    let mut hasher = Sha256::new();
-    hasher.update(password.as_bytes());
-    hasher.update(salt);
-    let computed = hasher.finalize();
-
-    Ok(constant_time_eq(&computed, stored_hash))
+    Ok(salted_digest_matches::<Sha256>(password, &decoded, lenient))
 }
 
 // ============ MD5 (Legacy) ============
@@ -468,25 +1144,7 @@ fn verify_md5(password: &str, hash: &str) -> bool {
     hash_value == computed_b64
 }
 
-fn hash_smd5(password: &str) -> Result<String> {
-    use md5::Digest;
-    let mut salt = [0u8; 8];
-    rand::thread_rng().fill_bytes(&mut salt);
-
-    let mut hasher = md5::Md5::new();
-    hasher.update(password.as_bytes());
-    hasher.update(salt);
-    let digest = hasher.finalize();
-
-    let mut hash_with_salt = Vec::with_capacity(16 + salt.len());
-    hash_with_salt.extend_from_slice(&digest);
-    hash_with_salt.extend_from_slice(&salt);
-
-    Ok(format!("{{SMD5}}{}", BASE64.encode(&hash_with_salt)))
-}
-
-fn verify_smd5(password: &str, hash: &str) -> Result<bool> {
-    use md5::Digest;
+fn verify_smd5(password: &str, hash: &str, lenient: bool) -> Result<bool> {
     let hash_value = hash
         .strip_prefix("{SMD5}")
         .or_else(|| hash.strip_prefix("{smd5}"))
@@ -502,18 +1160,228 @@ fn verify_smd5(password: &str, hash: &str) -> Result<bool> {
         ));
     }
 
-    let (stored_hash, salt) = decoded.split_at(16);
+    Ok(salted_digest_matches::<md5::Md5>(
+        password, &decoded, lenient,
+    ))
+}
 
-    let mut hasher = md5::Md5::new();
-    hasher.update(password.as_bytes());
-    hasher.update(salt);
-    let computed = hasher.finalize();
+// ============ PBKDF2 ============
+
+/// passlib "adapted base64": standard alphabet with `.` for `+`, unpadded.
+fn ab64_encode(data: &[u8]) -> String {
+    base64::engine::general_purpose::STANDARD_NO_PAD
+        .encode(data)
+        .replace('+', ".")
+}
+
+fn ab64_decode(data: &str) -> Result<Vec<u8>> {
+    base64::engine::general_purpose::STANDARD_NO_PAD
+        .decode(data.replace('.', "+").trim_end_matches('='))
+        .map_err(|e| HeraclesError::PasswordVerify(format!("Invalid base64: {}", e)))
+}
+
+/// Derives `out.len()` bytes with the PRF matching `method`.
+fn pbkdf2_derive(method: HashMethod, password: &str, salt: &[u8], iterations: u32, out: &mut [u8]) {
+    if method == HashMethod::Pbkdf2Sha512 {
+        pbkdf2::pbkdf2_hmac::<Sha512>(password.as_bytes(), salt, iterations, out);
+    } else {
+        pbkdf2::pbkdf2_hmac::<Sha256>(password.as_bytes(), salt, iterations, out);
+    }
+}
+
+/// Produces `{PBKDF2-SHA...}iterations$salt$checksum`.
+fn hash_pbkdf2(password: &str, method: HashMethod) -> String {
+    let mut salt = [0u8; 16];
+    rand::thread_rng().fill_bytes(&mut salt);
+
+    let key_len = if method == HashMethod::Pbkdf2Sha512 {
+        64
+    } else {
+        32
+    };
+    let mut key = vec![0u8; key_len];
+    pbkdf2_derive(method, password, &salt, PBKDF2_DEFAULT_ITERATIONS, &mut key);
+
+    format!(
+        "{}{}${}${}",
+        method.scheme(),
+        PBKDF2_DEFAULT_ITERATIONS,
+        ab64_encode(&salt),
+        ab64_encode(&key)
+    )
+}
+
+/// Verifies using the iteration count and salt stored in the hash.
+fn verify_pbkdf2(password: &str, hash: &PasswordHash) -> Result<bool> {
+    let mut parts = hash.value().split('$');
+    let (Some(iterations), Some(salt), Some(checksum), None) =
+        (parts.next(), parts.next(), parts.next(), parts.next())
+    else {
+        return Err(HeraclesError::PasswordVerify(
+            "Invalid PBKDF2 hash format".to_string(),
+        ));
+    };
+
+    let iterations: u32 =
+        iterations.parse().ok().filter(|&n| n > 0).ok_or_else(|| {
+            HeraclesError::PasswordVerify("Invalid PBKDF2 iteration count".into())
+        })?;
+    let salt = ab64_decode(salt)?;
+    let expected = ab64_decode(checksum)?;
+    if expected.is_empty() {
+        return Err(HeraclesError::PasswordVerify(
+            "Invalid PBKDF2 checksum".to_string(),
+        ));
+    }
 
-    Ok(constant_time_eq(&computed, stored_hash))
+    let mut key = vec![0u8; expected.len()];
+    pbkdf2_derive(hash.method, password, &salt, iterations, &mut key);
+
+    Ok(constant_time_eq(&key, &expected))
+}
+
+// ============ CRYPT ============
+
+/// Returns true for bare crypt(3) strings with a supported id.
+fn is_crypt_id(hash: &str) -> bool {
+    ["$1$", "$5$", "$6$"].iter().any(|id| hash.starts_with(id))
+}
+
+fn hash_crypt(password: &str) -> Result<String> {
+    let hash = sha_crypt::sha512_simple(password, &sha_crypt::Sha512Params::default())
+        .map_err(|e| HeraclesError::PasswordHash(format!("SHA-512 crypt failed: {:?}", e)))?;
+    Ok(format!("{{CRYPT}}{}", hash))
+}
+
+fn verify_crypt(password: &str, hash: &str) -> Result<bool> {
+    let value = strip_scheme(hash, "{CRYPT}");
+
+    if value.starts_with("$6$") {
+        Ok(sha_crypt::sha512_check(password, value).is_ok())
+    } else if value.starts_with("$5$") {
+        Ok(sha_crypt::sha256_check(password, value).is_ok())
+    } else if let Some(rest) = value.strip_prefix("$1$") {
+        let salt = rest.split('$').next().unwrap_or_default();
+        let computed = md5_crypt(password.as_bytes(), salt.as_bytes());
+        Ok(constant_time_eq(computed.as_bytes(), value.as_bytes()))
+    } else if value.starts_with("$2") {
+        verify_bcrypt(password, value)
+    } else {
+        Err(HeraclesError::UnsupportedHashMethod(format!(
+            "Unsupported crypt format: {}",
+            value.chars().take(4).collect::<String>()
+        )))
+    }
+}
+
+/// crypt(3) base64 alphabet.
+const CRYPT_ALPHABET: &[u8] = b"./0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz";
+
+/// MD5 crypt (`$1$`), as in FreeBSD's `crypt_md5`. Verify-only.
+fn md5_crypt(password: &[u8], salt: &[u8]) -> String {
+    use md5::{Digest, Md5};
+
+    let salt = &salt[..salt.len().min(8)];
+
+    let mut alt = Md5::new();
+    alt.update(password);
+    alt.update(salt);
+    alt.update(password);
+    let alt = alt.finalize();
+
+    let mut ctx = Md5::new();
+    ctx.update(password);
+    ctx.update(b"$1$");
+    ctx.update(salt);
+    for chunk in (0..password.len()).step_by(16) {
+        ctx.update(&alt[..(password.len() - chunk).min(16)]);
+    }
+    let mut i = password.len();
+    while i > 0 {
+        if i & 1 == 1 {
+            ctx.update([0u8]);
+        } else {
+            ctx.update(&password[..1]);
+        }
+        i >>= 1;
+    }
+    let mut digest = ctx.finalize();
+
+    for round in 0..1000 {
+        let mut ctx = Md5::new();
+        if round & 1 == 1 {
+            ctx.update(password);
+        } else {
+            ctx.update(digest);
+        }
+        if round % 3 != 0 {
+            ctx.update(salt);
+        }
+        if round % 7 != 0 {
+            ctx.update(password);
+        }
+        if round & 1 == 1 {
+            ctx.update(digest);
+        } else {
+            ctx.update(password);
+        }
+        digest = ctx.finalize();
+    }
+
+    let mut out = format!("$1${}$", String::from_utf8_lossy(salt));
+    let mut push = |value: u32, chars: usize| {
+        let mut v = value;
+        for _ in 0..chars {
+            out.push(CRYPT_ALPHABET[(v & 0x3f) as usize] as char);
+            v >>= 6;
+        }
+    };
+    for (a, b, c) in [(0, 6, 12), (1, 7, 13), (2, 8, 14), (3, 9, 15), (4, 10, 5)] {
+        push(
+            (digest[a] as u32) << 16 | (digest[b] as u32) << 8 | digest[c] as u32,
+            4,
+        );
+    }
+    push(digest[11] as u32, 2);
+    out
 }
 
 // ============ Utilities ============
 
+/// Returns `digest(password || salt) || salt`, the RFC 2307 salted layout.
+fn salted_digest<D: Digest>(password: &str, salt: &[u8]) -> Vec<u8> {
+    let mut hasher = D::new();
+    hasher.update(password.as_bytes());
+    hasher.update(salt);
+    let mut out = hasher.finalize().to_vec();
+    out.extend_from_slice(salt);
+    out
+}
+
+/// Checks a salted digest stored as `digest || salt`, falling back to
+/// `salt || digest` when `lenient` is set.
+fn salted_digest_matches<D: Digest>(password: &str, decoded: &[u8], lenient: bool) -> bool {
+    let digest_len = <D as Digest>::output_size();
+    let check = |stored: &[u8], salt: &[u8]| {
+        let mut hasher = D::new();
+        hasher.update(password.as_bytes());
+        hasher.update(salt);
+        constant_time_eq(&hasher.finalize(), stored)
+    };
+
+    let (stored, salt) = decoded.split_at(digest_len);
+    if check(stored, salt) {
+        return true;
+    }
+
+    if lenient && decoded.len() > digest_len {
+        let (salt, stored) = decoded.split_at(decoded.len() - digest_len);
+        return check(stored, salt);
+    }
+
+    false
+}
+
 /// Constant-time comparison to prevent timing attacks.
 fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
     if a.len() != b.len() {
@@ -621,6 +1489,81 @@ mod tests {
         assert!(!verify_password("wrong", &hash).unwrap());
     }
 
+    #[test]
+    fn test_ssha_custom_salt_length() {
+        for method in [
+            HashMethod::Ssha,
+            HashMethod::Ssha256,
+            HashMethod::Ssha512,
+            HashMethod::Smd5,
+        ] {
+            let options = SshaOptions::new(method).salt_len(32);
+            let hash = hash_ssha_with("secret123", &options).unwrap();
+            assert_eq!(hash.method, method);
+            assert_eq!(HashMethod::detect(&hash.hash), Some(method));
+
+            let decoded = BASE64.decode(hash.value()).unwrap();
+            let digest_len = match method {
+                HashMethod::Ssha => 20,
+                HashMethod::Ssha256 => 32,
+                HashMethod::Ssha512 => 64,
+                _ => 16,
+            };
+            assert_eq!(decoded.len(), digest_len + 32);
+
+            assert!(verify_password("secret123", &hash).unwrap());
+            assert!(!verify_password("wrong", &hash).unwrap());
+        }
+    }
+
+    #[test]
+    fn test_ssha_salt_length_defaults_and_limits() {
+        let ssha = hash_ssha_with("x", &SshaOptions::default()).unwrap();
+        assert_eq!(ssha.method, HashMethod::Ssha);
+        assert_eq!(BASE64.decode(ssha.value()).unwrap().len(), 20 + 8);
+        let ssha512 = hash_password("x", HashMethod::Ssha512).unwrap();
+        assert_eq!(BASE64.decode(ssha512.value()).unwrap().len(), 64 + 16);
+
+        let too_short = SshaOptions::default().salt_len(2);
+        assert!(hash_ssha_with("x", &too_short).is_err());
+        assert!(matches!(
+            hash_ssha_with("x", &SshaOptions::new(HashMethod::Bcrypt)),
+            Err(HeraclesError::UnsupportedHashMethod(_))
+        ));
+    }
+
+    #[test]
+    fn test_password_hash_serde_roundtrip() {
+        for method in [HashMethod::Argon2id, HashMethod::Ssha] {
+            let hash = hash_password("secret123", method).unwrap();
+            let json = serde_json::to_string(&hash).unwrap();
+            assert!(json.contains(&format!("\"method\":\"{}\"", method.scheme())));
+
+            let restored: PasswordHash = serde_json::from_str(&json).unwrap();
+            assert_eq!(restored.method, method);
+            assert_eq!(restored.hash, hash.hash);
+            assert!(verify_password("secret123", &restored).unwrap());
+        }
+
+        assert_eq!(serde_json::to_string(&HashMethod::Plain).unwrap(), "\"\"");
+        assert_eq!(
+            serde_json::from_str::<HashMethod>("\"\"").unwrap(),
+            HashMethod::Plain
+        );
+    }
+
+    #[test]
+    fn test_password_hash_serde_rejects_mismatch() {
+        let ssha = hash_password("secret123", HashMethod::Ssha).unwrap();
+        let json = format!(
+            r#"{{"method":"{{ARGON2}}","hash":{}}}"#,
+            serde_json::to_string(&ssha.hash).unwrap()
+        );
+        assert!(serde_json::from_str::<PasswordHash>(&json).is_err());
+
+        assert!(serde_json::from_str::<HashMethod>("\"{NOPE}\"").is_err());
+    }
+
     #[test]
     fn test_hash_method_detection() {
         assert_eq!(HashMethod::detect("{SSHA}abc123"), Some(HashMethod::Ssha));
@@ -639,6 +1582,65 @@ mod tests {
         assert_eq!(HashMethod::detect("plaintext"), None);
     }
 
+    #[test]
+    fn test_schemes_round_trip() {
+        for &method in HashMethod::all() {
+            if method == HashMethod::Plain {
+                continue;
+            }
+            let scheme = method.scheme();
+            assert_eq!(HashMethod::from_str(scheme), Some(method), "{}", scheme);
+            assert_eq!(
+                HashMethod::from_str(&scheme.to_lowercase()),
+                Some(method),
+                "{}",
+                scheme
+            );
+            assert_eq!(
+                HashMethod::from_str(spelling_name(scheme)),
+                Some(method),
+                "{}",
+                scheme
+            );
+            assert_eq!(
+                HashMethod::detect(&format!("{}abc", scheme.to_lowercase())),
+                Some(method),
+                "{}",
+                scheme
+            );
+            assert!(HashMethod::all_schemes().contains(&scheme), "{}", scheme);
+        }
+
+        assert_eq!(HashMethod::all_schemes().len(), HashMethod::all().len() - 1);
+        assert_eq!(HashMethod::from_str("clear"), Some(HashMethod::Plain));
+        assert_eq!(
+            HashMethod::from_str("crypt/sha-512"),
+            Some(HashMethod::Crypt)
+        );
+        assert_eq!(HashMethod::from_str("{CLEAR}"), Some(HashMethod::Plain));
+        assert_eq!(HashMethod::detect("{CLEAR}secret"), None);
+        assert_eq!(HashMethod::from_str("{NOPE}"), None);
+    }
+
+    #[test]
+    fn test_detect_or_plain() {
+        assert_eq!(
+            HashMethod::detect_or_plain("{SSHA}abc"),
+            Some(HashMethod::Ssha)
+        );
+        assert_eq!(
+            HashMethod::detect_or_plain("$6$salt$hash"),
+            Some(HashMethod::Crypt)
+        );
+        assert_eq!(HashMethod::detect("secret123"), None);
+        assert_eq!(
+            HashMethod::detect_or_plain("secret123"),
+            Some(HashMethod::Plain)
+        );
+        assert_eq!(HashMethod::detect_or_plain("{UNKNOWN}abc"), None);
+        assert_eq!(HashMethod::detect_or_plain("bad\u{0}data"), None);
+    }
+
     #[test]
     fn test_hash_method_is_secure() {
         assert!(HashMethod::Argon2id.is_secure());
@@ -647,6 +1649,7 @@ mod tests {
         assert!(HashMethod::Ssha.is_secure());
         assert!(!HashMethod::Md5.is_secure());
         assert!(!HashMethod::Plain.is_secure());
+        assert!(!HashMethod::Crypt.is_secure());
     }
 
     #[test]
@@ -654,6 +1657,10 @@ mod tests {
         let hash = PasswordHash::parse("{SSHA}abc123xyz").unwrap();
         assert_eq!(hash.method, HashMethod::Ssha);
         assert_eq!(hash.value(), "abc123xyz");
+
+        let peppered = PasswordHash::parse("{PEPPERED}{SSHA512}abc123xyz").unwrap();
+        assert_eq!(peppered.method, HashMethod::Ssha512);
+        assert_eq!(peppered.value(), "abc123xyz");
     }
 
     #[test]
@@ -663,6 +1670,458 @@ mod tests {
         assert!(!constant_time_eq(b"hello", b"hell"));
     }
 
+    #[test]
+    fn test_argon2_custom_params() {
+        let params = HashParams {
+            argon2: Some(Argon2Params::new(1024, 1, 1)),
+            ..Default::default()
+        };
+        let hash =
+            hash_password_with_params("argon2_params", HashMethod::Argon2id, &params).unwrap();
+
+        assert!(hash.hash.contains("m=1024,t=1,p=1"));
+        assert!(verify_password("argon2_params", &hash).unwrap());
+        assert!(!verify_password("wrong", &hash).unwrap());
+    }
+
+    #[test]
+    fn test_argon2_defaults_and_mixed_params_verify() {
+        let defaults = Argon2Params::default();
+        let default_hash = hash_password("argon2_mixed", HashMethod::Argon2id).unwrap();
+        assert!(default_hash.hash.contains(&format!(
+            "m={},t={},p={}",
+            defaults.m_cost, defaults.t_cost, defaults.p_cost
+        )));
+
+        let params = HashParams {
+            argon2: Some(Argon2Params::new(2048, 3, 2)),
+            ..Default::default()
+        };
+        let tuned_hash =
+            hash_password_with_params("argon2_mixed", HashMethod::Argon2id, &params).unwrap();
+
+        // Verification reads the cost from each encoded hash, so hashes made
+        // with different parameters keep verifying.
+        assert!(verify_password("argon2_mixed", &default_hash).unwrap());
+        assert!(verify_password("argon2_mixed", &tuned_hash).unwrap());
+    }
+
+    #[test]
+    fn test_verify_detects_method() {
+        let hash = hash_password("secret123", HashMethod::Ssha512).unwrap();
+        assert!(verify("secret123", &hash.hash).unwrap());
+        assert!(!verify("wrong", &hash.hash).unwrap());
+
+        assert!(matches!(
+            verify("secret123", "{NOPE}abc"),
+            Err(HeraclesError::UnsupportedHashMethod(_))
+        ));
+    }
+
+    #[test]
+    fn test_verify_and_upgrade() {
+        let weak = hash_password("secret123", HashMethod::Md5).unwrap();
+        let outcome = verify_and_upgrade("secret123", &weak.hash, HashMethod::Ssha512).unwrap();
+        assert!(outcome.matched);
+        let upgraded = outcome.upgraded.unwrap();
+        assert_eq!(upgraded.method, HashMethod::Ssha512);
+        assert!(verify_password("secret123", &upgraded).unwrap());
+
+        let outcome = verify_and_upgrade("wrong", &weak.hash, HashMethod::Ssha512).unwrap();
+        assert!(!outcome.matched);
+        assert!(outcome.upgraded.is_none());
+
+        let current = hash_password("secret123", HashMethod::Ssha512).unwrap();
+        let outcome = verify_and_upgrade("secret123", &current.hash, HashMethod::Ssha512).unwrap();
+        assert!(outcome.matched);
+        assert!(outcome.upgraded.is_none());
+    }
+
+    #[test]
+    fn test_verify_and_upgrade_never_downgrades() {
+        let strong = hash_password("secret123", HashMethod::Argon2id).unwrap();
+        for target in [HashMethod::Ssha512, HashMethod::Pbkdf2Sha256] {
+            let outcome = verify_and_upgrade("secret123", &strong.hash, target).unwrap();
+            assert!(outcome.matched);
+            assert!(outcome.upgraded.is_none(), "{:?}", target);
+        }
+
+        // Same method with a lower cost is still upgraded
+        let cheap = hash_password_with_params(
+            "secret123",
+            HashMethod::Bcrypt,
+            &HashParams {
+                bcrypt_cost: Some(4),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        let outcome = verify_and_upgrade("secret123", &cheap.hash, HashMethod::Bcrypt).unwrap();
+        assert_eq!(outcome.upgraded.unwrap().method, HashMethod::Bcrypt);
+
+        // An insecure target never replaces an insecure hash
+        let weak = hash_password("secret123", HashMethod::Sha256).unwrap();
+        let outcome = verify_and_upgrade("secret123", &weak.hash, HashMethod::Md5).unwrap();
+        assert!(outcome.upgraded.is_none());
+
+        assert!(HashMethod::Argon2id.is_stronger_than(HashMethod::Ssha512));
+        assert!(!HashMethod::Ssha512.is_stronger_than(HashMethod::Ssha512));
+    }
+
+    #[test]
+    fn test_verify_against_values() {
+        let old = hash_password("secret123", HashMethod::Ssha).unwrap();
+        let new = hash_password("secret123", HashMethod::Argon2id).unwrap();
+        let stale = hash_password("previous", HashMethod::Ssha).unwrap();
+
+        let values = vec![stale.hash.clone(), new.hash.clone()];
+        assert_eq!(
+            verify_against_values("secret123", &values).unwrap(),
+            Some(HashMethod::Argon2id)
+        );
+
+        let values = vec![old.hash, new.hash.clone()];
+        assert_eq!(
+            verify_against_values("secret123", &values).unwrap(),
+            Some(HashMethod::Ssha)
+        );
+        assert_eq!(verify_against_values("wrong", &values).unwrap(), None);
+        assert_eq!(verify_against_values("secret123", &[]).unwrap(), None);
+    }
+
+    #[test]
+    fn test_verify_against_values_errors() {
+        let new = hash_password("secret123", HashMethod::Argon2id).unwrap();
+        let values = vec!["{UNKNOWN}abc".to_string(), new.hash];
+        assert_eq!(
+            verify_against_values("secret123", &values).unwrap(),
+            Some(HashMethod::Argon2id)
+        );
+        assert!(verify_against_values("wrong", &values).is_err());
+    }
+
+    #[test]
+    fn test_nt_hash() {
+        assert_eq!(nt_hash("password"), "8846F7EAEE8FB117AD06BDD830B7586C");
+        assert_eq!(nt_hash(""), "31D6CFE0D16AE931B73C59D7E0C089C0");
+        assert_eq!(HashMethod::detect(&nt_hash("password")), None);
+    }
+
+    #[test]
+    fn test_needs_rehash_method_change() {
+        let ssha = hash_password("rehash", HashMethod::Ssha).unwrap();
+        assert!(needs_rehash(&ssha, HashMethod::Argon2id));
+        assert!(!needs_rehash(&ssha, HashMethod::Ssha));
+
+        let md5 = PasswordHash::parse("{MD5}X03MO1qnZdYdgyfeuILPmQ==").unwrap();
+        assert!(needs_rehash(&md5, HashMethod::Argon2id));
+    }
+
+    #[test]
+    fn test_needs_rehash_argon2_costs() {
+        let current = hash_password("rehash", HashMethod::Argon2id).unwrap();
+        assert!(!needs_rehash(&current, HashMethod::Argon2id));
+
+        let weak = hash_password_with_params(
+            "rehash",
+            HashMethod::Argon2id,
+            &HashParams {
+                argon2: Some(Argon2Params::new(1024, 1, 1)),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        assert!(needs_rehash(&weak, HashMethod::Argon2id));
+
+        let lowered = HashParams {
+            argon2: Some(Argon2Params::new(1024, 1, 1)),
+            ..Default::default()
+        };
+        assert!(!needs_rehash_with_params(
+            &weak,
+            HashMethod::Argon2id,
+            &lowered
+        ));
+
+        let garbage = PasswordHash::new(HashMethod::Argon2id, "{ARGON2}nope".into());
+        assert!(needs_rehash(&garbage, HashMethod::Argon2id));
+    }
+
+    #[test]
+    fn test_needs_rehash_bcrypt_cost() {
+        let cheap = hash_password_with_params(
+            "rehash",
+            HashMethod::Bcrypt,
+            &HashParams {
+                bcrypt_cost: Some(4),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        assert!(needs_rehash(&cheap, HashMethod::Bcrypt));
+
+        let raw = PasswordHash::new(
+            HashMethod::Bcrypt,
+            format!("$2b${}$abcdefghijklmnopqrstuv", DEFAULT_COST),
+        );
+        assert!(!needs_rehash(&raw, HashMethod::Bcrypt));
+        assert_eq!(bcrypt_cost("{bcrypt}$2y$10$abc"), Some(10));
+    }
+
+    #[test]
+    fn test_needs_rehash_peppered() {
+        let peppered = |hash: &PasswordHash| {
+            PasswordHash::parse(&format!("{}{}", PEPPER_MARKER, hash.hash)).unwrap()
+        };
+
+        let argon2 = peppered(&hash_password("rehash", HashMethod::Argon2id).unwrap());
+        assert_eq!(argon2.method, HashMethod::Argon2id);
+        assert!(!needs_rehash(&argon2, HashMethod::Argon2id));
+
+        let cheap = peppered(
+            &hash_password_with_params(
+                "rehash",
+                HashMethod::Bcrypt,
+                &HashParams {
+                    bcrypt_cost: Some(4),
+                    ..Default::default()
+                },
+            )
+            .unwrap(),
+        );
+        assert!(needs_rehash(&cheap, HashMethod::Bcrypt));
+        assert_eq!(bcrypt_cost(&cheap.hash), Some(4));
+
+        let crypt = PasswordHash::parse(&format!("{}{}", PEPPER_MARKER, CRYPT_SHA512)).unwrap();
+        assert!(crypt.is_secure());
+    }
+
+    // Vectors computed with Python's hashlib.pbkdf2_hmac and passlib's
+    // adapted base64, salt "saltsaltsaltsalt".
+    const PBKDF2_SHA256: &str =
+        "{PBKDF2-SHA256}29000$c2FsdHNhbHRzYWx0c2FsdA$7xwbY5rCP.qJhnvJ80W3FI7hSRg8wNnl3S9rczjVuCk";
+    const PBKDF2_SHA512: &str = "{PBKDF2-SHA512}29000$c2FsdHNhbHRzYWx0c2FsdA$E.DNzWOZyzyEwyxpw5tyJ1SDJszAFjP43apL2NPsMZxfU51Qi0mTArHxoJ.JmenfU4/FRPZcsZth/RRuEyv0/w";
+
+    #[test]
+    fn test_pbkdf2_known_vectors() {
+        for (vector, method) in [
+            (PBKDF2_SHA256, HashMethod::Pbkdf2Sha256),
+            (PBKDF2_SHA512, HashMethod::Pbkdf2Sha512),
+        ] {
+            let hash = PasswordHash::parse(vector).unwrap();
+            assert_eq!(hash.method, method);
+            assert!(verify_password("password", &hash).unwrap());
+            assert!(!verify_password("Password", &hash).unwrap());
+        }
+
+        // The stored iteration count is honoured.
+        let low = PasswordHash::parse(
+            "{PBKDF2-SHA256}1000$c2FsdHNhbHRzYWx0c2FsdA$8nX7hwFEzIB8aPajJTYK8weHQc5Ngz0pFVAKvSu4jQA",
+        )
+        .unwrap();
+        assert!(verify_password("password", &low).unwrap());
+    }
+
+    #[test]
+    fn test_pbkdf2_roundtrip() {
+        for method in [HashMethod::Pbkdf2Sha256, HashMethod::Pbkdf2Sha512] {
+            let hash = hash_password("pbkdf2_pass", method).unwrap();
+            assert!(hash.hash.starts_with(&format!(
+                "{}{}$",
+                method.scheme(),
+                PBKDF2_DEFAULT_ITERATIONS
+            )));
+            assert!(verify_password("pbkdf2_pass", &hash).unwrap());
+            assert!(!verify_password("wrong", &hash).unwrap());
+            assert!(method.is_secure());
+        }
+    }
+
+    #[test]
+    fn test_pbkdf2_malformed() {
+        for bad in [
+            "{PBKDF2-SHA256}29000$c2FsdA",
+            "{PBKDF2-SHA256}0$c2FsdA$c2FsdA",
+            "{PBKDF2-SHA256}abc$c2FsdA$c2FsdA",
+            "{PBKDF2-SHA256}29000$c2FsdA$",
+        ] {
+            let hash = PasswordHash::parse(bad).unwrap();
+            assert!(verify_password("password", &hash).is_err(), "{}", bad);
+        }
+        assert_eq!(
+            HashMethod::from_str("pbkdf2_sha512"),
+            Some(HashMethod::Pbkdf2Sha512)
+        );
+    }
+
+    // Vectors generated with `openssl passwd -6/-5/-1 -salt ...`, which
+    // matches `mkpasswd` output.
+    const CRYPT_SHA512: &str = "{CRYPT}$6$saltstring$svn8UoSVapNtMuq1ukKS4tPQd8iKwSMHWjl/O817G3uBnIFNjnQJuesI68u4OTLiBFdcbYEdFCoEOfaS35inz1";
+    const CRYPT_SHA256: &str = "{CRYPT}$5$saltstring$5B8vYYiY.CVt1RlTTf8KbXBH3hsxY/GNooZaBBGWEc5";
+    const CRYPT_MD5: &str = "{CRYPT}$1$saltstri$YMyguxXMBpd2TEZ.vS/3q1";
+
+    #[test]
+    fn test_verify_and_upgrade_md5_crypt_to_crypt() {
+        let outcome = verify_and_upgrade("Hello world!", CRYPT_MD5, HashMethod::Crypt).unwrap();
+        assert!(outcome.matched);
+        let upgraded = outcome.upgraded.unwrap();
+        assert_eq!(upgraded.method, HashMethod::Crypt);
+        assert!(upgraded.value().starts_with("$6$"));
+        assert!(verify_password("Hello world!", &upgraded).unwrap());
+
+        let outcome = verify_and_upgrade("Hello world!", CRYPT_SHA512, HashMethod::Crypt).unwrap();
+        assert!(outcome.upgraded.is_none());
+    }
+
+    #[test]
+    fn test_crypt_known_vectors() {
+        for vector in [CRYPT_SHA512, CRYPT_SHA256, CRYPT_MD5] {
+            let hash = PasswordHash::parse(vector).unwrap();
+            assert_eq!(hash.method, HashMethod::Crypt);
+            assert!(
+                verify_password("Hello world!", &hash).unwrap(),
+                "{}",
+                vector
+            );
+            assert!(
+                !verify_password("Hello world", &hash).unwrap(),
+                "{}",
+                vector
+            );
+        }
+
+        assert_eq!(
+            md5_crypt(b"password", b"abc"),
+            "$1$abc$BXBqpb9BZcZhXLgbee.0s/"
+        );
+    }
+
+    #[test]
+    fn test_crypt_roundtrip() {
+        let hash = hash_password("crypt_pass", HashMethod::Crypt).unwrap();
+        assert!(hash.hash.starts_with("{CRYPT}$6$"));
+        let salt = hash.hash["{CRYPT}$6$".len()..].split('$').next().unwrap();
+        assert_eq!(salt.len(), 16);
+
+        assert!(verify_password("crypt_pass", &hash).unwrap());
+        assert!(!verify_password("wrong", &hash).unwrap());
+    }
+
+    #[test]
+    fn test_crypt_detect_and_security() {
+        assert_eq!(HashMethod::detect("$6$salt$hash"), Some(HashMethod::Crypt));
+        assert_eq!(
+            HashMethod::detect("{crypt}$1$salt$hash"),
+            Some(HashMethod::Crypt)
+        );
+        assert_eq!(HashMethod::from_str("crypt"), Some(HashMethod::Crypt));
+
+        assert!(PasswordHash::parse(CRYPT_SHA512).unwrap().is_secure());
+        assert!(!PasswordHash::parse(CRYPT_MD5).unwrap().is_secure());
+
+        let des = PasswordHash::parse("{CRYPT}abJnggxhB/yWI").unwrap();
+        assert!(verify_password("x", &des).is_err());
+    }
+
+    #[test]
+    fn test_bcrypt_custom_cost() {
+        let params = HashParams {
+            bcrypt_cost: Some(4),
+            ..Default::default()
+        };
+        let hash = hash_password_with_params("bcrypt_cost", HashMethod::Bcrypt, &params).unwrap();
+
+        assert!(hash.hash.starts_with("{BCRYPT}$2b$04$"));
+        assert!(verify_password("bcrypt_cost", &hash).unwrap());
+    }
+
+    // crypt_blowfish test vector, under each variant prefix.
+    const BCRYPT_VECTOR: &str = "$05$CCCCCCCCCCCCCCCCCCCCC.E5YPO9kmyuRGyh0XouQYb4YMJKvyOeW";
+
+    #[test]
+    fn test_bcrypt_variant_prefixes() {
+        for variant in ["$2a", "$2b", "$2y"] {
+            let bare = format!("{}{}", variant, BCRYPT_VECTOR);
+            for stored in [
+                bare.clone(),
+                format!("{{BCRYPT}}{}", bare),
+                format!("{{Bcrypt}}{}", bare),
+            ] {
+                let hash = PasswordHash::parse(&stored).unwrap();
+                assert_eq!(hash.method, HashMethod::Bcrypt, "{}", stored);
+                assert!(verify_password("U*U", &hash).unwrap(), "{}", stored);
+                assert!(!verify_password("U*V", &hash).unwrap(), "{}", stored);
+            }
+        }
+    }
+
+    #[test]
+    fn test_bare_2y_hash_detected() {
+        let php = format!("$2y{}", BCRYPT_VECTOR);
+        assert_eq!(HashMethod::detect(&php), Some(HashMethod::Bcrypt));
+        assert!(verify("U*U", &php).unwrap());
+        assert_eq!(bcrypt_cost(&php), Some(5));
+    }
+
+    #[test]
+    fn test_benchmark_method() {
+        let small = HashParams {
+            argon2: Some(Argon2Params::new(1024, 1, 1)),
+            ..Default::default()
+        };
+        let large = HashParams {
+            argon2: Some(Argon2Params::new(16 * 1024, 1, 1)),
+            ..Default::default()
+        };
+
+        let fast = benchmark_method(HashMethod::Argon2id, &small).unwrap();
+        let slow = benchmark_method(HashMethod::Argon2id, &large).unwrap();
+        assert!(fast > Duration::ZERO);
+        assert!(slow > fast, "{:?} <= {:?}", slow, fast);
+
+        let bad = HashParams {
+            bcrypt_cost: Some(2),
+            ..Default::default()
+        };
+        assert!(benchmark_method(HashMethod::Bcrypt, &bad).is_err());
+    }
+
+    #[test]
+    fn test_invalid_hash_params() {
+        let bad_argon2 = HashParams {
+            argon2: Some(Argon2Params::new(1, 0, 1)),
+            ..Default::default()
+        };
+        assert!(bad_argon2.validate().is_err());
+
+        let bad_bcrypt = HashParams {
+            bcrypt_cost: Some(32),
+            ..Default::default()
+        };
+        assert!(hash_password_with_params("x", HashMethod::Bcrypt, &bad_bcrypt).is_err());
+    }
+
+    #[test]
+    fn test_all_methods_described() {
+        let all = HashMethod::all();
+        assert_eq!(all.len(), 13);
+        assert_eq!(all[0], HashMethod::Argon2id);
+
+        let argon2 = HashMethod::Argon2id.describe();
+        assert_eq!(argon2.scheme, "{ARGON2}");
+        assert!(argon2.secure && argon2.salted && !argon2.legacy);
+
+        let md5 = HashMethod::Md5.describe();
+        assert!(!md5.secure && !md5.salted && md5.legacy);
+
+        let ssha = HashMethod::Ssha.describe();
+        assert!(ssha.secure && ssha.salted && ssha.legacy);
+
+        for method in all {
+            assert_eq!(method.describe().secure, method.is_secure());
+        }
+    }
+
     #[test]
     fn test_default_hasher() {
         let hasher = DefaultPasswordHasher;
@@ -670,4 +2129,105 @@ mod tests {
         let hash = hasher.hash(password, HashMethod::Ssha).unwrap();
         assert!(hasher.verify(password, &hash).unwrap());
     }
+
+    #[test]
+    fn test_peppered_hasher() {
+        let hasher = DefaultPasswordHasher::with_pepper(b"server-secret".to_vec());
+        let hash = hasher.hash("test123", HashMethod::Ssha512).unwrap();
+
+        assert!(hash.hash.starts_with("{PEPPERED}{SSHA512}"));
+        assert!(hasher.verify("test123", &hash).unwrap());
+        assert!(!hasher.verify("wrong", &hash).unwrap());
+
+        let parsed = PasswordHash::parse(&hash.hash).unwrap();
+        assert_eq!(parsed.method, HashMethod::Ssha512);
+        assert!(hasher.verify("test123", &parsed).unwrap());
+
+        // A rotated key doesn't match.
+        let rotated = DefaultPasswordHasher::with_pepper(b"new-secret".to_vec());
+        assert!(!rotated.verify("test123", &hash).unwrap());
+
+        // Without the pepper, verification errors instead of mismatching.
+        assert!(DefaultPasswordHasher.verify("test123", &hash).is_err());
+        assert!(verify("test123", &hash.hash).is_err());
+
+        // And the peppered hasher refuses unpeppered hashes.
+        let plain = hash_password("test123", HashMethod::Ssha512).unwrap();
+        assert!(hasher.verify("test123", &plain).is_err());
+    }
+
+    #[test]
+    fn test_verify_password_detailed() {
+        let ssha = hash_password("secret", HashMethod::Ssha).unwrap();
+        let result = verify_password_detailed("secret", &ssha.hash).unwrap();
+        assert!(result.matched);
+        assert_eq!(result.method, HashMethod::Ssha);
+        assert_eq!(result.is_secure, HashMethod::Ssha.is_secure());
+
+        let argon2 = hash_password("secret", HashMethod::Argon2id).unwrap();
+        let result = verify_password_detailed("wrong", &argon2.hash).unwrap();
+        assert!(!result.matched);
+        assert_eq!(result.method, HashMethod::Argon2id);
+        assert!(result.is_secure);
+
+        let md5 = hash_password("secret", HashMethod::Md5).unwrap();
+        let result = verify_password_detailed("secret", &md5.hash).unwrap();
+        assert!(result.matched);
+        assert_eq!(result.method, HashMethod::Md5);
+        assert!(!result.is_secure);
+
+        assert!(verify_password_detailed("secret", "{UNKNOWN}abc").is_err());
+    }
+
+    #[test]
+    fn test_lenient_salt_layout() {
+        use sha1::Sha1;
+
+        let salt = *b"saltsalt";
+        let mut hasher = Sha1::new();
+        hasher.update(b"secret");
+        hasher.update(salt);
+        let digest = hasher.finalize();
+
+        let mut salt_first = salt.to_vec();
+        salt_first.extend_from_slice(&digest);
+        let hash = PasswordHash::parse(&format!("{{SSHA}}{}", BASE64.encode(&salt_first))).unwrap();
+
+        let lenient = VerifyOptions {
+            lenient_salt_layout: true,
+        };
+        assert!(!verify_password("secret", &hash).unwrap());
+        assert!(verify_password_with_options("secret", &hash, &lenient).unwrap());
+        assert!(!verify_password_with_options("wrong", &hash, &lenient).unwrap());
+
+        // Standard layout still verifies under the lenient flag.
+        let standard = hash_password("secret", HashMethod::Ssha512).unwrap();
+        assert!(verify_password_with_options("secret", &standard, &lenient).unwrap());
+    }
+
+    #[test]
+    fn test_verify_policy_flags_insecure() {
+        let md5 = hash_password("secret", HashMethod::Md5).unwrap();
+        let verdict = verify_with_policy("secret", &md5, VerifyPolicy::FlagInsecure).unwrap();
+        assert_eq!(verdict, VerifyVerdict::MatchedButInsecure(HashMethod::Md5));
+        assert!(verdict.matched());
+        assert!(verdict.matched_but_insecure());
+
+        assert_eq!(
+            verify_with_policy("secret", &md5, VerifyPolicy::Permissive).unwrap(),
+            VerifyVerdict::Matched
+        );
+        assert_eq!(
+            verify_with_policy("wrong", &md5, VerifyPolicy::FlagInsecure).unwrap(),
+            VerifyVerdict::Mismatch
+        );
+    }
+
+    #[test]
+    fn test_verify_policy_secure_method() {
+        let argon2 = hash_password("secret", HashMethod::Argon2id).unwrap();
+        let verdict = verify_with_policy("secret", &argon2, VerifyPolicy::FlagInsecure).unwrap();
+        assert_eq!(verdict, VerifyVerdict::Matched);
+        assert!(!verdict.matched_but_insecure());
+    }
 }
@@ -2,6 +2,8 @@
 //!
 //! This module defines all error types used throughout the library.
 
+use serde::Serialize;
+use std::fmt;
 use thiserror::Error;
 
 /// Result type alias for Heracles operations.
@@ -22,6 +24,10 @@ pub enum HeraclesError {
     #[error("LDAP search failed: {0}")]
     LdapSearch(String),
 
+    /// Search matched more entries than the size limit allows
+    #[error("LDAP size limit exceeded: {0}")]
+    SizeLimitExceeded(String),
+
     /// LDAP add operation error
     #[error("LDAP add failed: {0}")]
     LdapAdd(String),
@@ -42,6 +48,10 @@ pub enum HeraclesError {
     #[error("LDAP entry already exists: {0}")]
     LdapAlreadyExists(String),
 
+    /// Server requires TLS or stronger authentication (result codes 8 and 13)
+    #[error("LDAP security required: {0}")]
+    SecurityRequired(String),
+
     /// Invalid DN format
     #[error("Invalid DN format: {0}")]
     InvalidDN(String),
@@ -50,6 +60,10 @@ pub enum HeraclesError {
     #[error("Invalid LDAP filter: {0}")]
     InvalidFilter(String),
 
+    /// Invalid permission bitmap encoding
+    #[error("Invalid permission bitmap: {0}")]
+    InvalidPermissions(String),
+
     /// Password hashing error
     #[error("Password hashing failed: {0}")]
     PasswordHash(String),
@@ -91,6 +105,37 @@ pub enum HeraclesError {
     Config(String),
 }
 
+/// A validation problem tied to a single input field.
+///
+/// `code` is a stable machine-readable identifier (e.g. `required`) that a
+/// UI can map to its own messages; `message` is a human-readable default.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct FieldError {
+    /// Name of the offending field (e.g. `base_dn`, `read_uris[1]`).
+    pub field: String,
+    /// Human-readable description of the problem.
+    pub message: String,
+    /// Machine-readable error code.
+    pub code: &'static str,
+}
+
+impl FieldError {
+    /// Creates a new field error.
+    pub fn new(field: impl Into<String>, code: &'static str, message: impl Into<String>) -> Self {
+        Self {
+            field: field.into(),
+            message: message.into(),
+            code,
+        }
+    }
+}
+
+impl fmt::Display for FieldError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.field, self.message)
+    }
+}
+
 impl From<ldap3::LdapError> for HeraclesError {
     fn from(err: ldap3::LdapError) -> Self {
         HeraclesError::Internal(err.to_string())
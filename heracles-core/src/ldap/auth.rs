@@ -0,0 +1,310 @@
+//! Login flow: bind as the user, then load and compile their ACL.
+//!
+//! Codifies the documented login pattern so callers only supply the ACL
+//! row fetcher (typically a database query). [`test_bind`] checks
+//! credentials on a dedicated connection, leaving the pool untouched.
+
+use std::future::Future;
+
+use crate::acl::{compile, AclRow, UserAcl};
+use crate::errors::Result;
+use crate::ldap::config::LdapConfig;
+use crate::ldap::connection::{auth_outcome, AuthOutcome, LdapConnection};
+use crate::ldap::pool::{LdapPool, LdapPoolExt, PooledConnection};
+use ldap3::LdapResult;
+use tracing::{debug, instrument, warn};
+
+/// Authenticates `user_dn` and, on success, compiles their ACL.
+///
+/// Binds on a pooled connection with password policy information
+/// requested (see [`LdapConnection::authenticate_detailed`]), then rebinds
+/// that connection with the service credentials before returning it to the
+/// pool. If the rebind fails, or the future is dropped before it completes
+/// (e.g. a request timeout), the connection is detached from the pool so it
+/// is never reused under the user's identity. `fetch_rows` is only called when the bind succeeds, and its rows
+/// are compiled into the returned [`UserAcl`]. Rejected credentials yield
+/// `(outcome, None)` so the caller can still inspect the policy details.
+///
+/// An empty password is rejected without contacting the server, since it
+/// would be an unauthenticated bind that many servers accept.
+///
+/// [`LdapConnection::authenticate_detailed`]: crate::ldap::LdapConnection::authenticate_detailed
+#[instrument(skip(pool, password, fetch_rows), fields(dn = %user_dn))]
+pub async fn login<F, Fut>(
+    pool: &LdapPool,
+    user_dn: &str,
+    password: &str,
+    fetch_rows: F,
+) -> Result<(AuthOutcome, Option<UserAcl>)>
+where
+    F: FnOnce() -> Fut,
+    Fut: Future<Output = Result<Vec<AclRow>>>,
+{
+    let outcome = if password.is_empty() {
+        AuthOutcome {
+            authenticated: false,
+            policy: None,
+        }
+    } else {
+        let mut conn = UserBound(Some(pool.get_connection().await?));
+        let outcome = conn.get().authenticate_detailed(user_dn, password).await;
+        restore_service_bind(conn).await;
+        outcome?
+    };
+
+    complete_login(user_dn, outcome, fetch_rows).await
+}
+
+/// Checks `dn`'s password on a short-lived dedicated connection.
+///
+/// Meant for steps like "confirm your current password", where rebinding a
+/// pooled, service-bound connection would be disruptive. The connection is
+/// opened from `config`, bound as the user with password policy information
+/// requested, then unbound and closed. As with [`login`], rejected
+/// credentials (including an empty password, which is never sent) yield
+/// `authenticated: false` rather than an error.
+#[instrument(skip(config, password), fields(dn = %dn))]
+pub async fn test_bind(config: &LdapConfig, dn: &str, password: &str) -> Result<AuthOutcome> {
+    test_bind_with(dn, password, |dn, password| async move {
+        let mut conn = LdapConnection::new(config.clone()).await?;
+        let result = conn.policy_bind(dn, password).await;
+        conn.close().await;
+        result
+    })
+    .await
+}
+
+/// Runs a one-off bind through `bind` and interprets its result.
+async fn test_bind_with<'a, B, Fut>(dn: &'a str, password: &'a str, bind: B) -> Result<AuthOutcome>
+where
+    B: FnOnce(&'a str, &'a str) -> Fut,
+    Fut: Future<Output = Result<LdapResult>>,
+{
+    if password.is_empty() {
+        return Ok(AuthOutcome {
+            authenticated: false,
+            policy: None,
+        });
+    }
+
+    let outcome = auth_outcome(bind(dn, password).await?)?;
+    debug!(
+        "Test bind for {}: authenticated={}",
+        dn, outcome.authenticated
+    );
+    Ok(outcome)
+}
+
+/// Fetches and compiles the ACL once the bind outcome is known.
+async fn complete_login<F, Fut>(
+    user_dn: &str,
+    outcome: AuthOutcome,
+    fetch_rows: F,
+) -> Result<(AuthOutcome, Option<UserAcl>)>
+where
+    F: FnOnce() -> Fut,
+    Fut: Future<Output = Result<Vec<AclRow>>>,
+{
+    if !outcome.authenticated {
+        debug!("Login rejected for: {}", user_dn);
+        return Ok((outcome, None));
+    }
+
+    let rows = fetch_rows().await?;
+    debug!("Compiling {} ACL rows for: {}", rows.len(), user_dn);
+    Ok((outcome, Some(compile(user_dn, rows))))
+}
+
+/// A pooled connection that may be bound as an end user.
+///
+/// Dropping it detaches the connection from the pool, unless
+/// [`release`](Self::release) was called after restoring the service bind.
+struct UserBound(Option<PooledConnection>);
+
+impl UserBound {
+    fn get(&mut self) -> &mut PooledConnection {
+        self.0.as_mut().expect("connection already released")
+    }
+
+    /// Returns the connection to the pool.
+    fn release(mut self) {
+        self.0.take();
+    }
+}
+
+impl Drop for UserBound {
+    fn drop(&mut self) {
+        if let Some(conn) = self.0.take() {
+            warn!("Discarding connection that may still be bound as a user");
+            let _ = PooledConnection::take(conn);
+        }
+    }
+}
+
+/// Rebinds a connection used for a user bind, detaching it from the pool
+/// if that fails so it is never reused under the user's identity.
+async fn restore_service_bind(mut conn: UserBound) {
+    match conn.get().bind().await {
+        Ok(()) => conn.release(),
+        Err(e) => warn!("Failed to restore service bind: {}", e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::errors::HeraclesError;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    const USER: &str = "uid=alice,ou=users,dc=example,dc=com";
+
+    fn rows() -> Vec<AclRow> {
+        vec![AclRow {
+            policy_name: "Basic User".to_string(),
+            perm_low: 0b11,
+            perm_high: 0,
+            scope_dn: String::new(),
+            scope_type: "subtree".to_string(),
+            self_only: false,
+            deny: false,
+            priority: 0,
+            attr_rules: vec![],
+        }]
+    }
+
+    fn outcome(authenticated: bool) -> AuthOutcome {
+        AuthOutcome {
+            authenticated,
+            policy: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_complete_login_compiles_on_success() {
+        let calls = AtomicUsize::new(0);
+        let (outcome, acl) = complete_login(USER, outcome(true), || async {
+            calls.fetch_add(1, Ordering::SeqCst);
+            Ok(rows())
+        })
+        .await
+        .unwrap();
+
+        assert!(outcome.authenticated);
+        let acl = acl.unwrap();
+        assert_eq!(acl.user_dn(), USER);
+        assert!(acl.global_allow().has_bit(1));
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_complete_login_skips_fetch_on_rejection() {
+        let calls = AtomicUsize::new(0);
+        let (outcome, acl) = complete_login(USER, outcome(false), || async {
+            calls.fetch_add(1, Ordering::SeqCst);
+            Ok(rows())
+        })
+        .await
+        .unwrap();
+
+        assert!(!outcome.authenticated);
+        assert!(acl.is_none());
+        assert_eq!(calls.load(Ordering::SeqCst), 0);
+    }
+
+    #[tokio::test]
+    async fn test_complete_login_propagates_fetch_error() {
+        let result = complete_login(USER, outcome(true), || async {
+            Err(HeraclesError::Internal("database unavailable".into()))
+        })
+        .await;
+        assert!(matches!(result, Err(HeraclesError::Internal(_))));
+    }
+
+    fn bind_result(rc: u32) -> LdapResult {
+        LdapResult {
+            rc,
+            matched: String::new(),
+            text: String::new(),
+            refs: vec![],
+            ctrls: vec![],
+        }
+    }
+
+    #[tokio::test]
+    async fn test_bind_maps_result_codes() {
+        let good = test_bind_with(USER, "secret", |_, _| async { Ok(bind_result(0)) })
+            .await
+            .unwrap();
+        assert!(good.authenticated);
+
+        let bad = test_bind_with(USER, "wrong", |_, _| async { Ok(bind_result(49)) })
+            .await
+            .unwrap();
+        assert!(!bad.authenticated);
+
+        let busy = test_bind_with(USER, "secret", |_, _| async { Ok(bind_result(51)) }).await;
+        assert!(matches!(busy, Err(HeraclesError::LdapBind(_))));
+    }
+
+    #[tokio::test]
+    async fn test_bind_empty_password_skips_bind() {
+        let calls = AtomicUsize::new(0);
+        let outcome = test_bind_with(USER, "", |_, _| async {
+            calls.fetch_add(1, Ordering::SeqCst);
+            Ok(bind_result(0))
+        })
+        .await
+        .unwrap();
+        assert!(!outcome.authenticated);
+        assert_eq!(calls.load(Ordering::SeqCst), 0);
+    }
+
+    #[tokio::test]
+    async fn test_login_returns_rebound_connection_to_pool() {
+        let (uri, _) = crate::ldap::test_server::spawn(0).await;
+        let pool = crate::ldap::create_pool(LdapConfig::default().with_uris([uri])).unwrap();
+
+        let (outcome, acl) = login(&pool, USER, "secret", || async { Ok(rows()) })
+            .await
+            .unwrap();
+        assert!(outcome.authenticated);
+        assert!(acl.is_some());
+        assert_eq!(pool.status().size, 1);
+        assert_eq!(
+            pool.get_connection()
+                .await
+                .unwrap()
+                .info()
+                .bound_dn
+                .as_deref(),
+            Some("cn=admin,dc=example,dc=com")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_user_bound_connection_detached_on_drop() {
+        let (uri, _) = crate::ldap::test_server::spawn(0).await;
+        let pool = crate::ldap::create_pool(LdapConfig::default().with_uris([uri])).unwrap();
+
+        // As if the login future were dropped mid-way
+        let mut conn = UserBound(Some(pool.get_connection().await.unwrap()));
+        conn.get().bind_as(USER, "secret").await.unwrap();
+        drop(conn);
+        assert_eq!(pool.status().size, 0);
+    }
+
+    #[tokio::test]
+    async fn test_login_empty_password_skips_server() {
+        let config = crate::ldap::LdapConfig {
+            uri: "ldap://127.0.0.1:1".into(),
+            ..Default::default()
+        };
+        let pool = crate::ldap::create_pool(config).unwrap();
+
+        let (outcome, acl) = login(&pool, USER, "", || async { Ok(rows()) })
+            .await
+            .unwrap();
+        assert!(!outcome.authenticated);
+        assert!(acl.is_none());
+    }
+}
@@ -1,8 +1,9 @@
 //! LDAP configuration management.
 
-use crate::errors::{HeraclesError, Result};
+use crate::errors::{FieldError, HeraclesError, Result};
 use serde::{Deserialize, Serialize};
 use std::env;
+use std::path::PathBuf;
 use std::time::Duration;
 
 /// LDAP connection configuration.
@@ -11,6 +12,14 @@ pub struct LdapConfig {
     /// LDAP server URI (e.g., "ldap://localhost:389" or "ldaps://ldap.example.com:636")
     pub uri: String,
 
+    /// Server URIs tried in order when connecting; `uri` alone is used when empty
+    #[serde(default)]
+    pub uris: Vec<String>,
+
+    /// Read-only replica URIs; searches are load-balanced across them when set
+    #[serde(default)]
+    pub read_uris: Vec<String>,
+
     /// Base DN for searches (e.g., "dc=example,dc=com")
     pub base_dn: String,
 
@@ -25,6 +34,10 @@ pub struct LdapConfig {
     #[serde(default)]
     pub use_tls: bool,
 
+    /// Certificates and verification for LDAPS and STARTTLS
+    #[serde(default)]
+    pub tls: TlsConfig,
+
     /// Connection pool size
     #[serde(default = "default_pool_size")]
     pub pool_size: usize,
@@ -33,6 +46,11 @@ pub struct LdapConfig {
     #[serde(default = "default_timeout")]
     pub timeout_seconds: u64,
 
+    /// Bind timeout in seconds, applied to each bind independently of the connect timeout.
+    /// Defaults to `timeout_seconds` when unset.
+    #[serde(default)]
+    pub bind_timeout_seconds: Option<u64>,
+
     /// Search size limit (0 = no limit)
     #[serde(default)]
     pub size_limit: i32,
@@ -40,6 +58,64 @@ pub struct LdapConfig {
     /// Search time limit in seconds (0 = no limit)
     #[serde(default)]
     pub time_limit: i32,
+
+    /// How many times a read is retried on a fresh connection after the
+    /// server connection drops (0 = never)
+    #[serde(default = "default_retry_count")]
+    pub retry_count: u32,
+
+    /// Whether pooled connections are probed with a Root DSE read before
+    /// being handed out again
+    #[serde(default = "default_health_check_on_recycle")]
+    pub health_check_on_recycle: bool,
+
+    /// Whether searches follow referrals to other servers
+    #[serde(default)]
+    pub chase_referrals: bool,
+
+    /// Maximum number of referral hops followed from one search
+    #[serde(default = "default_max_referral_depth")]
+    pub max_referral_depth: u32,
+
+    /// Hosts (`host` or `host:port`) referrals may be chased to, besides
+    /// the servers in `uri`, `uris` and `read_uris`
+    #[serde(default)]
+    pub referral_hosts: Vec<String>,
+}
+
+/// TLS settings for LDAPS and STARTTLS connections.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TlsConfig {
+    /// PEM bundle of CAs to trust instead of the system store
+    #[serde(default)]
+    pub ca_file: Option<PathBuf>,
+
+    /// PEM client certificate chain for mutual TLS
+    #[serde(default)]
+    pub client_cert: Option<PathBuf>,
+
+    /// PEM private key matching `client_cert`
+    #[serde(default)]
+    pub client_key: Option<PathBuf>,
+
+    /// Whether to verify the server certificate; only disable in a lab
+    #[serde(default = "default_tls_verify")]
+    pub verify: bool,
+}
+
+impl Default for TlsConfig {
+    fn default() -> Self {
+        Self {
+            ca_file: None,
+            client_cert: None,
+            client_key: None,
+            verify: default_tls_verify(),
+        }
+    }
+}
+
+fn default_tls_verify() -> bool {
+    true
 }
 
 fn default_pool_size() -> usize {
@@ -50,6 +126,18 @@ fn default_timeout() -> u64 {
     30
 }
 
+fn default_retry_count() -> u32 {
+    1
+}
+
+fn default_max_referral_depth() -> u32 {
+    3
+}
+
+fn default_health_check_on_recycle() -> bool {
+    true
+}
+
 impl LdapConfig {
     /// Creates a new LDAP configuration.
     pub fn new(
@@ -60,21 +148,30 @@ impl LdapConfig {
     ) -> Self {
         Self {
             uri: uri.into(),
+            uris: Vec::new(),
+            read_uris: Vec::new(),
             base_dn: base_dn.into(),
             bind_dn: bind_dn.into(),
             bind_password: bind_password.into(),
             use_tls: false,
+            tls: TlsConfig::default(),
             pool_size: default_pool_size(),
             timeout_seconds: default_timeout(),
+            bind_timeout_seconds: None,
             size_limit: 0,
             time_limit: 0,
+            retry_count: default_retry_count(),
+            health_check_on_recycle: default_health_check_on_recycle(),
+            chase_referrals: false,
+            max_referral_depth: default_max_referral_depth(),
+            referral_hosts: Vec::new(),
         }
     }
 
     /// Creates configuration from environment variables.
     ///
     /// Required environment variables:
-    /// - `LDAP_URI`: LDAP server URI
+    /// - `LDAP_URI`: LDAP server URI, or a comma-separated failover list
     /// - `LDAP_BASE_DN`: Base DN
     /// - `LDAP_BIND_DN`: Bind DN
     /// - `LDAP_BIND_PASSWORD`: Bind password
@@ -83,97 +180,295 @@ impl LdapConfig {
     /// - `LDAP_USE_TLS`: "true" or "false" (default: false)
     /// - `LDAP_POOL_SIZE`: Pool size (default: 10)
     /// - `LDAP_TIMEOUT`: Timeout in seconds (default: 30)
+    /// - `LDAP_BIND_TIMEOUT`: Bind timeout in seconds (default: `LDAP_TIMEOUT`)
+    /// - `LDAP_READ_URIS`: Comma-separated read replica URIs (default: none)
+    /// - `LDAP_RETRY_COUNT`: Retries after a dropped connection (default: 1)
+    /// - `LDAP_HEALTH_CHECK_ON_RECYCLE`: "true" or "false" (default: true)
+    /// - `LDAP_CHASE_REFERRALS`: "true" or "false" (default: false)
+    /// - `LDAP_MAX_REFERRAL_DEPTH`: Referral hops followed per search (default: 3)
+    /// - `LDAP_REFERRAL_HOSTS`: Comma-separated extra hosts referrals may go to (default: none)
+    /// - `LDAP_TLS_CA_FILE`: PEM CA bundle to trust (default: system store)
+    /// - `LDAP_TLS_CLIENT_CERT` / `LDAP_TLS_CLIENT_KEY`: PEM client certificate and key
+    /// - `LDAP_TLS_VERIFY`: "false" disables certificate verification (default: true)
     pub fn from_env() -> Result<Self> {
-        let uri = env::var("LDAP_URI")
-            .map_err(|_| HeraclesError::Configuration("LDAP_URI not set".into()))?;
+        Self::from_lookup(|name| env::var(name).ok())
+    }
+
+    /// Builds the configuration from variables returned by `var`, using the
+    /// names and defaults documented on [`from_env`](Self::from_env).
+    fn from_lookup(var: impl Fn(&str) -> Option<String>) -> Result<Self> {
+        let mut uris = var("LDAP_URI")
+            .map(|v| parse_uri_list(&v))
+            .unwrap_or_default();
+        if uris.is_empty() {
+            return Err(HeraclesError::Configuration("LDAP_URI not set".into()));
+        }
+        let uri = uris[0].clone();
+        if uris.len() == 1 {
+            uris.clear();
+        }
+
+        let read_uris = var("LDAP_READ_URIS")
+            .map(|v| parse_uri_list(&v))
+            .unwrap_or_default();
 
-        let base_dn = env::var("LDAP_BASE_DN")
-            .map_err(|_| HeraclesError::Configuration("LDAP_BASE_DN not set".into()))?;
+        let base_dn = var("LDAP_BASE_DN")
+            .ok_or_else(|| HeraclesError::Configuration("LDAP_BASE_DN not set".into()))?;
 
-        let bind_dn = env::var("LDAP_BIND_DN")
-            .map_err(|_| HeraclesError::Configuration("LDAP_BIND_DN not set".into()))?;
+        let bind_dn = var("LDAP_BIND_DN")
+            .ok_or_else(|| HeraclesError::Configuration("LDAP_BIND_DN not set".into()))?;
 
-        let bind_password = env::var("LDAP_BIND_PASSWORD")
-            .map_err(|_| HeraclesError::Configuration("LDAP_BIND_PASSWORD not set".into()))?;
+        let bind_password = var("LDAP_BIND_PASSWORD")
+            .ok_or_else(|| HeraclesError::Configuration("LDAP_BIND_PASSWORD not set".into()))?;
 
-        let use_tls = env::var("LDAP_USE_TLS")
+        let use_tls = var("LDAP_USE_TLS")
             .map(|v| v.to_lowercase() == "true")
             .unwrap_or(false);
 
-        let pool_size = env::var("LDAP_POOL_SIZE")
-            .ok()
+        let pool_size = var("LDAP_POOL_SIZE")
             .and_then(|v| v.parse().ok())
             .unwrap_or(default_pool_size());
 
-        let timeout_seconds = env::var("LDAP_TIMEOUT")
-            .ok()
+        let timeout_seconds = var("LDAP_TIMEOUT")
             .and_then(|v| v.parse().ok())
             .unwrap_or(default_timeout());
 
+        let bind_timeout_seconds = var("LDAP_BIND_TIMEOUT").and_then(|v| v.parse().ok());
+
+        let retry_count = var("LDAP_RETRY_COUNT")
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(default_retry_count());
+
+        let health_check_on_recycle = var("LDAP_HEALTH_CHECK_ON_RECYCLE")
+            .map(|v| v.to_lowercase() != "false")
+            .unwrap_or(default_health_check_on_recycle());
+
+        let chase_referrals = var("LDAP_CHASE_REFERRALS")
+            .map(|v| v.to_lowercase() == "true")
+            .unwrap_or(false);
+
+        let max_referral_depth = var("LDAP_MAX_REFERRAL_DEPTH")
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(default_max_referral_depth());
+
+        let referral_hosts = var("LDAP_REFERRAL_HOSTS")
+            .map(|v| parse_uri_list(&v))
+            .unwrap_or_default();
+
+        let tls = TlsConfig {
+            ca_file: var("LDAP_TLS_CA_FILE").map(PathBuf::from),
+            client_cert: var("LDAP_TLS_CLIENT_CERT").map(PathBuf::from),
+            client_key: var("LDAP_TLS_CLIENT_KEY").map(PathBuf::from),
+            verify: var("LDAP_TLS_VERIFY")
+                .map(|v| v.to_lowercase() != "false")
+                .unwrap_or(default_tls_verify()),
+        };
+
         Ok(Self {
             uri,
+            uris,
+            read_uris,
             base_dn,
             bind_dn,
             bind_password,
             use_tls,
+            tls,
             pool_size,
             timeout_seconds,
+            bind_timeout_seconds,
             size_limit: 0,
             time_limit: 0,
+            retry_count,
+            health_check_on_recycle,
+            chase_referrals,
+            max_referral_depth,
+            referral_hosts,
         })
     }
 
+    /// Returns true if read replicas are configured.
+    pub fn has_read_replicas(&self) -> bool {
+        !self.read_uris.is_empty()
+    }
+
+    /// Returns a copy of this configuration targeting another server URI.
+    pub fn with_uri(&self, uri: impl Into<String>) -> Self {
+        Self {
+            uri: uri.into(),
+            uris: Vec::new(),
+            read_uris: Vec::new(),
+            ..self.clone()
+        }
+    }
+
+    /// Sets the ordered failover list; `uri` becomes its first entry.
+    pub fn with_uris<I, S>(mut self, uris: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.uris = uris.into_iter().map(Into::into).collect();
+        if let Some(first) = self.uris.first() {
+            self.uri = first.clone();
+        }
+        self
+    }
+
+    /// Returns the server URIs to try when connecting, in order.
+    pub fn server_uris(&self) -> Vec<&str> {
+        if self.uris.is_empty() {
+            vec![self.uri.as_str()]
+        } else {
+            self.uris.iter().map(String::as_str).collect()
+        }
+    }
+
     /// Returns the connection timeout as a Duration.
     pub fn timeout(&self) -> Duration {
         Duration::from_secs(self.timeout_seconds)
     }
 
+    /// Returns the bind timeout as a Duration, falling back to the
+    /// connection timeout when no bind timeout is set.
+    pub fn bind_timeout(&self) -> Duration {
+        Duration::from_secs(self.bind_timeout_seconds.unwrap_or(self.timeout_seconds))
+    }
+
     /// Validates the configuration.
+    ///
+    /// Reports every problem at once, joined into a single
+    /// `Configuration` error; see [`validate_detailed`](Self::validate_detailed)
+    /// for per-field errors.
     pub fn validate(&self) -> Result<()> {
+        self.validate_detailed().map_err(|errors| {
+            let messages: Vec<&str> = errors.iter().map(|e| e.message.as_str()).collect();
+            HeraclesError::Configuration(messages.join("; "))
+        })
+    }
+
+    /// Validates the configuration, returning one [`FieldError`] per
+    /// offending field so a form can highlight each input.
+    pub fn validate_detailed(&self) -> std::result::Result<(), Vec<FieldError>> {
+        let mut errors = Vec::new();
+
         if self.uri.is_empty() {
-            return Err(HeraclesError::Configuration("URI cannot be empty".into()));
+            errors.push(FieldError::new("uri", "required", "URI cannot be empty"));
+        } else if !is_ldap_uri(&self.uri) {
+            errors.push(FieldError::new(
+                "uri",
+                "invalid_scheme",
+                "URI must start with ldap:// or ldaps://",
+            ));
         }
 
-        if !self.uri.starts_with("ldap://") && !self.uri.starts_with("ldaps://") {
-            return Err(HeraclesError::Configuration(
-                "URI must start with ldap:// or ldaps://".into(),
-            ));
+        for (i, uri) in self.uris.iter().enumerate() {
+            if !is_ldap_uri(uri) {
+                errors.push(FieldError::new(
+                    format!("uris[{}]", i),
+                    "invalid_scheme",
+                    format!("URI must start with ldap:// or ldaps://: {}", uri),
+                ));
+            }
+        }
+
+        for (i, uri) in self.read_uris.iter().enumerate() {
+            if !is_ldap_uri(uri) {
+                errors.push(FieldError::new(
+                    format!("read_uris[{}]", i),
+                    "invalid_scheme",
+                    format!("Read URI must start with ldap:// or ldaps://: {}", uri),
+                ));
+            }
         }
 
         if self.base_dn.is_empty() {
-            return Err(HeraclesError::Configuration(
-                "Base DN cannot be empty".into(),
+            errors.push(FieldError::new(
+                "base_dn",
+                "required",
+                "Base DN cannot be empty",
             ));
         }
 
         if self.bind_dn.is_empty() {
-            return Err(HeraclesError::Configuration(
-                "Bind DN cannot be empty".into(),
+            errors.push(FieldError::new(
+                "bind_dn",
+                "required",
+                "Bind DN cannot be empty",
+            ));
+        }
+
+        if self.tls.client_cert.is_some() != self.tls.client_key.is_some() {
+            let field = if self.tls.client_cert.is_some() {
+                "tls.client_key"
+            } else {
+                "tls.client_cert"
+            };
+            errors.push(FieldError::new(
+                field,
+                "required",
+                "Client certificate and key must be set together",
+            ));
+        }
+
+        if self.bind_timeout_seconds == Some(0) {
+            errors.push(FieldError::new(
+                "bind_timeout_seconds",
+                "must_be_positive",
+                "Bind timeout must be greater than 0",
             ));
         }
 
         if self.pool_size == 0 {
-            return Err(HeraclesError::Configuration(
-                "Pool size must be greater than 0".into(),
+            errors.push(FieldError::new(
+                "pool_size",
+                "must_be_positive",
+                "Pool size must be greater than 0",
             ));
         }
 
-        Ok(())
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
     }
 }
 
+fn is_ldap_uri(uri: &str) -> bool {
+    uri.starts_with("ldap://") || uri.starts_with("ldaps://")
+}
+
+/// Splits a comma-separated URI list, dropping empty entries.
+fn parse_uri_list(value: &str) -> Vec<String> {
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .map(String::from)
+        .collect()
+}
+
 impl Default for LdapConfig {
     fn default() -> Self {
         Self {
             uri: "ldap://localhost:389".into(),
+            uris: Vec::new(),
+            read_uris: Vec::new(),
             base_dn: "dc=example,dc=com".into(),
             bind_dn: "cn=admin,dc=example,dc=com".into(),
             bind_password: String::new(),
             use_tls: false,
+            tls: TlsConfig::default(),
             pool_size: default_pool_size(),
             timeout_seconds: default_timeout(),
+            bind_timeout_seconds: None,
             size_limit: 0,
             time_limit: 0,
+            retry_count: default_retry_count(),
+            health_check_on_recycle: default_health_check_on_recycle(),
+            chase_referrals: false,
+            max_referral_depth: default_max_referral_depth(),
+            referral_hosts: Vec::new(),
         }
     }
 }
@@ -181,6 +476,7 @@ impl Default for LdapConfig {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::collections::HashMap;
 
     #[test]
     fn test_config_new() {
@@ -209,6 +505,7 @@ mod tests {
     }
 
     #[test]
+    #[allow(clippy::field_reassign_with_default)]
     fn test_config_validate_invalid_uri() {
         let mut config = LdapConfig::default();
         config.uri = "invalid://localhost".into();
@@ -217,6 +514,7 @@ mod tests {
     }
 
     #[test]
+    #[allow(clippy::field_reassign_with_default)]
     fn test_config_validate_empty_base_dn() {
         let mut config = LdapConfig::default();
         config.uri = "ldap://localhost:389".into();
@@ -224,4 +522,188 @@ mod tests {
 
         assert!(config.validate().is_err());
     }
+
+    #[test]
+    fn test_config_validate_detailed_reports_all() {
+        let config = LdapConfig {
+            uri: "http://localhost".into(),
+            read_uris: vec!["ldap://ok:389".into(), "replica:389".into()],
+            base_dn: String::new(),
+            pool_size: 0,
+            ..Default::default()
+        };
+
+        let errors = config.validate_detailed().unwrap_err();
+        let fields: Vec<(&str, &str)> = errors.iter().map(|e| (e.field.as_str(), e.code)).collect();
+        assert_eq!(
+            fields,
+            vec![
+                ("uri", "invalid_scheme"),
+                ("read_uris[1]", "invalid_scheme"),
+                ("base_dn", "required"),
+                ("pool_size", "must_be_positive"),
+            ]
+        );
+
+        let message = config.validate().unwrap_err().to_string();
+        assert!(message.contains("URI must start with ldap://"));
+        assert!(message.contains("Pool size must be greater than 0"));
+        assert!(LdapConfig::default().validate_detailed().is_ok());
+    }
+
+    #[test]
+    fn test_config_validate_invalid_read_uri() {
+        let config = LdapConfig {
+            read_uris: vec!["ldap://replica1:389".into(), "http://replica2".into()],
+            ..Default::default()
+        };
+
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn test_config_bind_timeout() {
+        let config = LdapConfig::default();
+        assert_eq!(config.bind_timeout(), Duration::from_secs(30));
+
+        let config = LdapConfig {
+            timeout_seconds: 5,
+            ..Default::default()
+        };
+        assert_eq!(config.bind_timeout(), Duration::from_secs(5));
+
+        let config = LdapConfig {
+            timeout_seconds: 5,
+            bind_timeout_seconds: Some(2),
+            ..Default::default()
+        };
+        assert_eq!(config.bind_timeout(), Duration::from_secs(2));
+
+        let config: LdapConfig = serde_json::from_str(
+            r#"{"uri": "ldap://localhost", "base_dn": "dc=x", "bind_dn": "cn=a,dc=x", "bind_password": "", "timeout_seconds": 7}"#,
+        )
+        .unwrap();
+        assert_eq!(config.bind_timeout(), Duration::from_secs(7));
+
+        let config = LdapConfig {
+            bind_timeout_seconds: Some(0),
+            ..Default::default()
+        };
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn test_config_retry_count_default() {
+        assert_eq!(LdapConfig::default().retry_count, 1);
+
+        let config: LdapConfig = serde_json::from_str(
+            r#"{"uri": "ldap://localhost", "base_dn": "dc=x", "bind_dn": "cn=a,dc=x", "bind_password": ""}"#,
+        )
+        .unwrap();
+        assert_eq!(config.retry_count, 1);
+        assert!(config.health_check_on_recycle);
+    }
+
+    #[test]
+    fn test_parse_uri_list() {
+        assert_eq!(
+            parse_uri_list("ldap://r1:389, ldap://r2:389,,"),
+            vec!["ldap://r1:389", "ldap://r2:389"]
+        );
+        assert!(parse_uri_list("").is_empty());
+    }
+
+    #[test]
+    fn test_config_with_uri() {
+        let config = LdapConfig {
+            read_uris: vec!["ldap://replica1:389".into()],
+            ..Default::default()
+        };
+        let replica = config.with_uri("ldap://replica1:389");
+
+        assert_eq!(replica.uri, "ldap://replica1:389");
+        assert!(!replica.has_read_replicas());
+        assert_eq!(replica.base_dn, config.base_dn);
+    }
+
+    fn lookup(vars: &[(&str, &str)]) -> impl Fn(&str) -> Option<String> {
+        let vars: HashMap<String, String> = vars
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect();
+        move |name| vars.get(name).cloned()
+    }
+
+    const REQUIRED_VARS: [(&str, &str); 3] = [
+        ("LDAP_BASE_DN", "dc=example,dc=com"),
+        ("LDAP_BIND_DN", "cn=admin,dc=example,dc=com"),
+        ("LDAP_BIND_PASSWORD", "secret"),
+    ];
+
+    #[test]
+    fn test_config_from_env_parses_uri_list() {
+        let mut vars = REQUIRED_VARS.to_vec();
+        vars.push(("LDAP_URI", "ldap://primary:389, ldaps://backup:636"));
+        let config = LdapConfig::from_lookup(lookup(&vars)).unwrap();
+
+        assert_eq!(config.uri, "ldap://primary:389");
+        assert_eq!(
+            config.server_uris(),
+            vec!["ldap://primary:389", "ldaps://backup:636"]
+        );
+        assert!(config.validate().is_ok());
+    }
+
+    #[test]
+    fn test_config_from_env_requires_uri() {
+        let result = LdapConfig::from_lookup(lookup(&REQUIRED_VARS));
+        assert!(matches!(result, Err(HeraclesError::Configuration(_))));
+    }
+
+    #[test]
+    fn test_config_from_env_bind_timeout_follows_timeout() {
+        let mut vars = REQUIRED_VARS.to_vec();
+        vars.extend([("LDAP_URI", "ldap://localhost"), ("LDAP_TIMEOUT", "12")]);
+        let config = LdapConfig::from_lookup(lookup(&vars)).unwrap();
+        assert_eq!(config.bind_timeout(), Duration::from_secs(12));
+
+        vars.push(("LDAP_BIND_TIMEOUT", "3"));
+        let config = LdapConfig::from_lookup(lookup(&vars)).unwrap();
+        assert_eq!(config.bind_timeout(), Duration::from_secs(3));
+        assert_eq!(config.timeout(), Duration::from_secs(12));
+    }
+
+    #[test]
+    fn test_config_server_uris() {
+        let config = LdapConfig::default();
+        assert_eq!(config.server_uris(), vec!["ldap://localhost:389"]);
+
+        let config = config.with_uris(["ldap://a:389", "ldap://b:389"]);
+        assert_eq!(config.uri, "ldap://a:389");
+        assert_eq!(config.server_uris(), vec!["ldap://a:389", "ldap://b:389"]);
+        assert!(config.with_uri("ldap://c:389").uris.is_empty());
+
+        let errors = config
+            .with_uris(["ldap://a:389", "b:389"])
+            .validate_detailed()
+            .unwrap_err();
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].field, "uris[1]");
+    }
+
+    #[test]
+    fn test_config_tls_defaults_to_verify() {
+        let config: LdapConfig = serde_json::from_str(
+            r#"{"uri": "ldaps://localhost", "base_dn": "dc=x", "bind_dn": "cn=a,dc=x", "bind_password": "", "tls": {"ca_file": "/etc/ssl/ca.pem"}}"#,
+        )
+        .unwrap();
+        assert!(config.tls.verify);
+        assert_eq!(config.tls.ca_file, Some(PathBuf::from("/etc/ssl/ca.pem")));
+        assert!(LdapConfig::default().tls.verify);
+
+        let mut config = LdapConfig::default();
+        config.tls.client_cert = Some("/etc/ssl/client.pem".into());
+        let errors = config.validate_detailed().unwrap_err();
+        assert_eq!(errors[0].field, "tls.client_key");
+    }
 }
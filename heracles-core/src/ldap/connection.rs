@@ -1,63 +1,812 @@
 //! LDAP connection management.
 
+use crate::acl::UserAcl;
 use crate::errors::{HeraclesError, Result};
 use crate::ldap::config::LdapConfig;
-use crate::ldap::operations::{LdapEntry, LdapModification};
-use ldap3::{Ldap, LdapConnAsync, LdapConnSettings, Scope, SearchEntry};
+use crate::ldap::dn::{DistinguishedName, RdnComponent};
+use crate::ldap::filter::LdapFilter;
+use crate::ldap::ldif::ldif_records;
+use crate::ldap::operations::{
+    validate_attribute_name, LdapEntry, LdapModification, SearchBuilder,
+};
+use crate::ldap::ppolicy::{self, PasswordPolicyInfo};
+use crate::ldap::referral;
+use crate::ldap::result_code::LdapResultCode;
+use crate::ldap::sort;
+use crate::ldap::tls;
+use futures::future::BoxFuture;
+use futures::{Stream, TryStreamExt};
+use ldap3::adapters::{Adapter, EntriesOnly, PagedResults as PagedAdapter};
+use ldap3::asn1::{parse_tag, TagClass, Types, PL};
+use ldap3::controls::{Control, PagedResults, RawControl};
+use ldap3::exop::{Exop, WhoAmI};
+use ldap3::{
+    Ldap, LdapConnAsync, LdapConnSettings, LdapError, LdapResult, ResultEntry, Scope, SearchEntry,
+    SearchOptions,
+};
 use std::collections::HashMap;
-use std::time::Duration;
-use tracing::{debug, error, instrument, trace};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tracing::{debug, error, info, instrument, trace, warn};
+
+/// Maps result codes that demand TLS/stronger auth to `SecurityRequired`,
+/// deferring to `fallback` for everything else.
+fn map_result_error(
+    err: LdapError,
+    fallback: impl FnOnce(LdapError) -> HeraclesError,
+) -> HeraclesError {
+    if let LdapError::LdapResult { result } = &err {
+        match LdapResultCode::from_u32(result.rc) {
+            LdapResultCode::StrongerAuthRequired => {
+                return HeraclesError::SecurityRequired(format!(
+                    "stronger authentication required (rc=8), consider enabling TLS: {}",
+                    result.text
+                ));
+            }
+            LdapResultCode::ConfidentialityRequired => {
+                return HeraclesError::SecurityRequired(format!(
+                    "confidentiality required (rc=13), enable TLS (ldaps:// or use_tls): {}",
+                    result.text
+                ));
+            }
+            LdapResultCode::SizeLimitExceeded => {
+                return HeraclesError::SizeLimitExceeded(format!(
+                    "more entries matched than the size limit allows (rc=4): {}",
+                    result.text
+                ));
+            }
+            LdapResultCode::TimeLimitExceeded => {
+                return HeraclesError::Timeout(format!(
+                    "search time limit exceeded (rc=3): {}",
+                    result.text
+                ));
+            }
+            _ => {}
+        }
+    }
+    fallback(err)
+}
+
+/// Builds search options from the configured size and time limits, with
+/// `size_limit` (e.g. from a [`SearchBuilder`]) overriding the configured one.
+fn search_options(config: &LdapConfig, size_limit: Option<usize>) -> SearchOptions {
+    let size_limit = size_limit
+        .map(|limit| i32::try_from(limit).unwrap_or(i32::MAX))
+        .unwrap_or(config.size_limit);
+    SearchOptions::new()
+        .sizelimit(size_limit)
+        .timelimit(config.time_limit)
+}
+
+/// Returns true for ldap3 errors meaning the connection to the server is
+/// gone (socket closed or reset, driver stopped), as opposed to a result
+/// code returned by a live server.
+fn is_transport_error(err: &LdapError) -> bool {
+    matches!(
+        err,
+        LdapError::Io { .. }
+            | LdapError::OpSend { .. }
+            | LdapError::ResultRecv { .. }
+            | LdapError::IdScrubSend { .. }
+            | LdapError::EndOfStream
+    )
+}
+
+/// Maps transport failures to `LdapConnection`, so [`retry_transient`] can
+/// recognize them, deferring to `fallback` for everything else.
+fn map_transport_error(
+    err: LdapError,
+    fallback: impl FnOnce(LdapError) -> HeraclesError,
+) -> HeraclesError {
+    if is_transport_error(&err) {
+        HeraclesError::LdapConnection(format!("connection lost: {}", err))
+    } else {
+        fallback(err)
+    }
+}
+
+/// A connection [`retry_transient`] can re-establish.
+trait Reconnect {
+    /// Whether [`reconnect`](Self::reconnect) would restore the current
+    /// identity, i.e. the connection is (or is about to be) bound with the
+    /// service credentials.
+    fn can_reconnect(&self) -> bool;
+
+    /// Opens a fresh connection to the server and binds it with the
+    /// service credentials.
+    async fn reconnect(&mut self) -> Result<()>;
+}
+
+/// Runs `op`, reconnecting and running it again up to `retries` times
+/// while it fails with `LdapConnection` (a dropped connection).
+///
+/// Any other error, including bind failures such as invalid credentials
+/// (rc 49) during the reconnect, is returned immediately. So is the
+/// connection error of a connection bound as someone other than the
+/// service account, which must not be silently rebound as the service.
+async fn retry_transient<C, T, F>(conn: &mut C, retries: u32, mut op: F) -> Result<T>
+where
+    C: Reconnect,
+    F: for<'a> FnMut(&'a mut C) -> BoxFuture<'a, Result<T>>,
+{
+    let mut attempt = 0;
+    loop {
+        match op(conn).await {
+            Err(HeraclesError::LdapConnection(e)) if attempt < retries && conn.can_reconnect() => {
+                attempt += 1;
+                warn!(
+                    "LDAP connection failed ({}), reconnecting (retry {}/{})",
+                    e, attempt, retries
+                );
+                conn.reconnect().await?;
+            }
+            result => return result,
+        }
+    }
+}
+
+/// Opens a connection to the first reachable server in
+/// `config.server_uris()`, returning it with the URI that was used.
+///
+/// If every server fails, the last error is returned. TLS settings are
+/// checked first, so a bad certificate path fails without connecting.
+async fn open_ldap(config: &LdapConfig) -> Result<(Ldap, String)> {
+    let tls = tls::client_config(&config.tls)?;
+    let mut last_err = None;
+    for uri in config.server_uris() {
+        match connect_uri(config, uri, tls.clone()).await {
+            Ok(ldap) => {
+                info!("Connected to LDAP server: {}", uri);
+                return Ok((ldap, uri.to_string()));
+            }
+            Err(e) => {
+                warn!("Failed to connect to {}: {}", uri, e);
+                last_err = Some(e);
+            }
+        }
+    }
+    Err(last_err
+        .unwrap_or_else(|| HeraclesError::LdapConnection("no server URI configured".into())))
+}
+
+/// Opens a connection to `uri` and spawns its driver.
+async fn connect_uri(
+    config: &LdapConfig,
+    uri: &str,
+    tls: Option<Arc<rustls::ClientConfig>>,
+) -> Result<Ldap> {
+    let mut settings = LdapConnSettings::new()
+        .set_conn_timeout(Duration::from_secs(config.timeout_seconds))
+        .set_starttls(config.use_tls)
+        .set_no_tls_verify(!config.tls.verify);
+    if let Some(tls) = tls {
+        settings = settings.set_config(tls);
+    }
+
+    debug!("Connecting to LDAP server: {}", uri);
+
+    let (conn, ldap) = LdapConnAsync::with_settings(settings, uri)
+        .await
+        .map_err(|e| HeraclesError::LdapConnection(format!("{}: {}", uri, e)))?;
+
+    // Spawn the connection driver
+    tokio::spawn(async move {
+        if let Err(e) = conn.drive().await {
+            error!("LDAP connection error: {}", e);
+        }
+    });
+
+    Ok(ldap)
+}
+
+fn is_encrypted(config: &LdapConfig, uri: &str) -> bool {
+    config.use_tls || uri.starts_with("ldaps://")
+}
+
+/// Awaits `fut`, failing with `HeraclesError::Timeout` if it takes longer
+/// than `limit`.
+async fn with_timeout<F, T>(limit: Duration, what: &str, fut: F) -> Result<T>
+where
+    F: std::future::Future<Output = Result<T>>,
+{
+    tokio::time::timeout(limit, fut).await.map_err(|_| {
+        HeraclesError::Timeout(format!("{} exceeded {}s", what, limit.as_secs_f64()))
+    })?
+}
+
+/// Resolves `base` against `base_dn`; see [`LdapConnection::resolve_search_base`].
+fn resolve_base(base: &str, base_dn: &str) -> Result<String> {
+    let base = base.trim();
+    if base.is_empty() {
+        return Ok(base_dn.to_string());
+    }
+    validate_base(base)?;
+    Ok(base.to_string())
+}
+
+/// Appends `relative` to `base_dn`; see [`LdapConnection::resolve_relative_base`].
+fn resolve_relative(relative: &str, base_dn: &str) -> Result<String> {
+    let relative = relative.trim();
+    if relative.is_empty() {
+        return Ok(base_dn.to_string());
+    }
+    validate_base(relative)?;
+    Ok(format!("{},{}", relative, base_dn))
+}
+
+/// Checks that `base` is a DN with no empty attribute types or values.
+fn validate_base(base: &str) -> Result<()> {
+    let parsed = DistinguishedName::parse(base)
+        .map_err(|_| HeraclesError::InvalidDN(format!("Invalid search base: {}", base)))?;
+    if parsed
+        .components
+        .iter()
+        .any(|c| c.attr_type.is_empty() || c.attr_value.is_empty())
+    {
+        return Err(HeraclesError::InvalidDN(format!(
+            "Invalid search base: {}",
+            base
+        )));
+    }
+    Ok(())
+}
+
+/// Configuration for chasing a referral to `server`, and whether the
+/// service credentials may be sent there.
+///
+/// Untrusted servers are refused with `LdapSearch`; trusted ones get the
+/// service bind only when the connection is encrypted.
+fn referral_target(config: &LdapConfig, server: &str) -> Result<(LdapConfig, bool)> {
+    if !referral::is_trusted(server, config) {
+        return Err(HeraclesError::LdapSearch(format!(
+            "Refusing referral to untrusted server {}",
+            server
+        )));
+    }
+    let target = config.with_uri(server);
+    let service_bind = is_encrypted(&target, server);
+    if !service_bind {
+        warn!("Chasing referral to {} anonymously: not encrypted", server);
+    }
+    Ok((target, service_bind))
+}
+
+/// Splits raw search results into entries and continuation referral URIs.
+///
+/// A `referral` (rc=10) final result is not an error here; its URIs are
+/// returned alongside any search result references.
+fn split_search_results(
+    results: Vec<ResultEntry>,
+    result: LdapResult,
+) -> Result<(Vec<LdapEntry>, Vec<String>)> {
+    let code = LdapResultCode::from_u32(result.rc);
+    if !code.is_success() && code != LdapResultCode::Referral {
+        return Err(map_result_error(LdapError::LdapResult { result }, |e| {
+            HeraclesError::LdapSearch(e.to_string())
+        }));
+    }
+
+    let (entries, mut referrals) = partition_results(results);
+    referrals.extend(result.refs);
+
+    Ok((entries, referrals))
+}
+
+/// Separates search result entries from search result references.
+///
+/// References (continuations to other servers) yield their URIs instead of
+/// being decoded as entries; intermediate responses are dropped.
+fn partition_results(results: Vec<ResultEntry>) -> (Vec<LdapEntry>, Vec<String>) {
+    let mut entries = Vec::new();
+    let mut referrals = Vec::new();
+    for entry in results {
+        if entry.is_ref() {
+            referrals.extend(ldap3::parse_refs(entry.0));
+        } else if !entry.is_intermediate() {
+            entries.push(LdapEntry::from(SearchEntry::construct(entry)));
+        }
+    }
+    (entries, referrals)
+}
+
+/// Interprets a one-level, size-limited child search for
+/// [`LdapConnection::is_container`].
+///
+/// `sizeLimitExceeded` means more children than the limit, and a
+/// continuation reference points at a child held on another server.
+fn has_children(results: &[ResultEntry], result: LdapResult) -> Result<bool> {
+    match LdapResultCode::from_u32(result.rc) {
+        LdapResultCode::SizeLimitExceeded => Ok(true),
+        LdapResultCode::Success => Ok(results.iter().any(|r| !r.is_intermediate())),
+        _ => Err(map_result_error(LdapError::LdapResult { result }, |e| {
+            HeraclesError::LdapSearch(e.to_string())
+        })),
+    }
+}
+
+/// Filter matching the entry with the given `entryUUID`.
+fn uuid_filter(uuid: &str) -> String {
+    LdapFilter::eq("entryUUID", uuid).to_string()
+}
+
+/// Filter matching any of `dns` by `entryDN` (RFC 5020) or, on Active
+/// Directory, `distinguishedName`.
+fn dn_filter(dns: &[DistinguishedName]) -> String {
+    LdapFilter::or(
+        dns.iter()
+            .flat_map(|dn| {
+                let dn = dn.to_string();
+                [
+                    LdapFilter::eq("entryDN", dn.clone()),
+                    LdapFilter::eq("distinguishedName", dn),
+                ]
+            })
+            .collect(),
+    )
+    .to_string()
+}
+
+/// Lines `entries` up with `dns`, `None` where no entry has that DN.
+fn order_by_dn(dns: &[DistinguishedName], entries: Vec<LdapEntry>) -> Vec<Option<LdapEntry>> {
+    let mut found: HashMap<DistinguishedName, LdapEntry> = entries
+        .into_iter()
+        .filter_map(|e| Some((DistinguishedName::parse(&e.dn).ok()?.normalized(), e)))
+        .collect();
+    let mut ordered: Vec<Option<LdapEntry>> = Vec::with_capacity(dns.len());
+    for (i, dn) in dns.iter().enumerate() {
+        let key = dn.normalized();
+        // A repeated DN gets a copy of the entry handed out the first time.
+        let entry = found.remove(&key).or_else(|| {
+            dns[..i]
+                .iter()
+                .position(|d| d.eq_normalized(dn))
+                .and_then(|j| ordered[j].clone())
+        });
+        ordered.push(entry);
+    }
+    ordered
+}
+
+/// Interprets a base search for one entry; `noSuchObject` means no entry.
+fn base_entry_outcome(results: Vec<ResultEntry>, result: LdapResult) -> Result<Option<LdapEntry>> {
+    if LdapResultCode::from_u32(result.rc) == LdapResultCode::NoSuchObject {
+        return Ok(None);
+    }
+    result
+        .success()
+        .map_err(|e| map_result_error(e, |e| HeraclesError::LdapSearch(e.to_string())))?;
+    Ok(partition_results(results).0.pop())
+}
+
+/// Returns the only entry found for `uuid`, erroring if there are several.
+fn single_uuid_match(mut entries: Vec<LdapEntry>, uuid: &str) -> Result<Option<LdapEntry>> {
+    match entries.len() {
+        0 | 1 => Ok(entries.pop()),
+        n => Err(HeraclesError::LdapSearch(format!(
+            "{} entries match entryUUID {}",
+            n, uuid
+        ))),
+    }
+}
+
+/// Checks that the root DSE `features` include modify-increment.
+fn require_increment(features: &[String]) -> Result<()> {
+    if features.iter().any(|f| f == INCREMENT_FEATURE_OID) {
+        Ok(())
+    } else {
+        Err(HeraclesError::LdapModify(format!(
+            "Server does not support the modify-increment extension ({})",
+            INCREMENT_FEATURE_OID
+        )))
+    }
+}
+
+/// Maps a failed add result, reporting an existing entry as already-exists.
+fn map_add_error(dn: &str, err: LdapError) -> HeraclesError {
+    map_result_error(err, |e| {
+        if LdapResultCode::from_error(&e) == Some(LdapResultCode::EntryAlreadyExists) {
+            HeraclesError::LdapAlreadyExists(dn.to_string())
+        } else {
+            HeraclesError::LdapAdd(e.to_string())
+        }
+    })
+}
+
+/// Interprets the result of an add that tolerates an existing entry:
+/// `true` when the entry was created, `false` when it already existed.
+fn absent_outcome(result: Result<()>) -> Result<bool> {
+    match result {
+        Ok(()) => Ok(true),
+        Err(HeraclesError::LdapAlreadyExists(_)) => Ok(false),
+        Err(e) => Err(e),
+    }
+}
+
+/// Maps a failed modify result, reporting a missing entry as not-found.
+fn map_modify_error(dn: &str, err: LdapError) -> HeraclesError {
+    map_result_error(err, |e| {
+        if LdapResultCode::from_error(&e) == Some(LdapResultCode::NoSuchObject) {
+            HeraclesError::LdapNotFound(dn.to_string())
+        } else {
+            HeraclesError::LdapModify(e.to_string())
+        }
+    })
+}
+
+/// Maps a failed modify DN result: a missing source entry is not-found and
+/// an existing target is already-exists, as for `add`.
+fn map_modify_dn_error(dn: &str, target: &str, err: LdapError) -> HeraclesError {
+    map_result_error(err, |e| match LdapResultCode::from_error(&e) {
+        Some(LdapResultCode::NoSuchObject) => HeraclesError::LdapNotFound(dn.to_string()),
+        Some(LdapResultCode::EntryAlreadyExists) => {
+            HeraclesError::LdapAlreadyExists(target.to_string())
+        }
+        _ => HeraclesError::LdapModify(e.to_string()),
+    })
+}
+
+/// DN an entry ends up at after [`LdapConnection::modify_dn`].
+fn modify_dn_target(dn: &str, new_rdn: &str, new_superior: Option<&str>) -> String {
+    let superior = match new_superior {
+        Some(superior) => superior.to_string(),
+        None => DistinguishedName::parse(dn)
+            .ok()
+            .and_then(|d| d.parent())
+            .map(|p| p.to_string())
+            .unwrap_or_default(),
+    };
+    if superior.is_empty() {
+        new_rdn.to_string()
+    } else {
+        format!("{},{}", new_rdn, superior)
+    }
+}
+
+/// OID of the Simple Paged Results control (RFC 2696).
+const PAGED_RESULTS_OID: &str = "1.2.840.113556.1.4.319";
+
+/// Extracts the cookie from a paged results response control.
+///
+/// An empty cookie means the last page was returned; a missing control
+/// (a server that ignored the non-critical request) means the same.
+fn paged_cookie(ctrls: &[Control]) -> Result<Vec<u8>> {
+    let Some(value) = ctrls
+        .iter()
+        .find(|Control(_, raw)| raw.ctype == PAGED_RESULTS_OID)
+        .map(|Control(_, raw)| raw.val.as_deref().unwrap_or_default())
+    else {
+        return Ok(Vec::new());
+    };
+
+    let malformed = || HeraclesError::LdapSearch("Malformed paged results control".to_string());
+    let (_, tag) = parse_tag(value).map_err(|_| malformed())?;
+    let mut elements = tag.expect_constructed().ok_or_else(malformed)?.into_iter();
+    let size = elements.next().ok_or_else(malformed)?;
+    let cookie = elements.next().ok_or_else(malformed)?;
+    if size.class != TagClass::Universal || size.id != Types::Integer as u64 {
+        return Err(malformed());
+    }
+    match cookie.payload {
+        PL::P(bytes) if cookie.id == Types::OctetString as u64 => Ok(bytes),
+        _ => Err(malformed()),
+    }
+}
+
+/// Number of DNs looked up per search by [`LdapConnection::get_many`].
+pub const GET_MANY_BATCH: usize = 100;
+
+/// `supportedFeatures` OID of the modify-increment extension (RFC 4525).
+pub const INCREMENT_FEATURE_OID: &str = "1.3.6.1.1.14";
+
+/// Page size requested by [`LdapConnection::search_stream`].
+pub const STREAM_PAGE_SIZE: i32 = 500;
+
+/// Search state carried between pages by [`LdapConnection::search_paged`].
+struct PagedSearch {
+    ldap: Ldap,
+    base: String,
+    scope: Scope,
+    filter: String,
+    attrs: Vec<String>,
+    page_size: i32,
+    cookie: Vec<u8>,
+}
+
+impl PagedSearch {
+    /// Fetches the next page, returning its entries and whether more follow.
+    async fn next_page(&mut self) -> Result<(Vec<LdapEntry>, bool)> {
+        let control = RawControl::from(PagedResults {
+            size: self.page_size,
+            cookie: std::mem::take(&mut self.cookie),
+        });
+        let ldap3::SearchResult(results, result) = self
+            .ldap
+            .with_controls(control)
+            .search(&self.base, self.scope, &self.filter, &self.attrs)
+            .await
+            .map_err(|e| HeraclesError::LdapSearch(e.to_string()))?;
+        let result = result
+            .success()
+            .map_err(|e| map_result_error(e, |e| HeraclesError::LdapSearch(e.to_string())))?;
+
+        let (entries, _) = partition_results(results);
+        self.cookie = paged_cookie(&result.ctrls)?;
+        Ok((entries, !self.cookie.is_empty()))
+    }
+}
+
+/// Maps a failed sorted search, explaining a server without sort support.
+fn map_sort_error(err: LdapError) -> HeraclesError {
+    map_result_error(err, |e| {
+        if LdapResultCode::from_error(&e) == Some(LdapResultCode::UnavailableCriticalExtension) {
+            HeraclesError::LdapSearch(format!(
+                "server-side sorting is not supported by the server: {}",
+                e
+            ))
+        } else {
+            HeraclesError::LdapSearch(e.to_string())
+        }
+    })
+}
+
+/// Reads the authzId from a "Who am I?" response; an absent value is the
+/// anonymous identity.
+fn whoami_authzid(exop: Exop) -> Result<String> {
+    match exop.val {
+        None => Ok(String::new()),
+        Some(val) => String::from_utf8(val).map_err(|_| {
+            HeraclesError::LdapConnection("Who am I? returned a non-UTF-8 authzId".to_string())
+        }),
+    }
+}
+
+/// Attribute list sent by [`LdapConnection::search_readable`].
+///
+/// Explicit lists are narrowed to what `acl` lets the user read on `base`;
+/// if nothing is left, `1.1` (RFC 4511 "no attributes") is requested so the
+/// server doesn't fall back to returning everything. Empty lists and the
+/// `*`/`+` wildcards can't be narrowed and are returned unchanged.
+fn readable_search_attrs(
+    acl: &UserAcl,
+    base: &str,
+    object_type: &str,
+    attrs: &[&str],
+) -> Vec<String> {
+    if attrs.is_empty() || attrs.iter().any(|a| *a == "*" || *a == "+") {
+        return attrs.iter().map(|a| a.to_string()).collect();
+    }
+
+    let readable = acl.readable_attributes(base, object_type, attrs);
+    if readable.is_empty() {
+        vec!["1.1".to_string()]
+    } else {
+        readable
+    }
+}
+
+/// New RDN and DN for [`LdapConnection::rename_rdn_and_attr`], keeping the
+/// current RDN attribute type and any further pairs of a multi-valued RDN.
+fn rename_target(dn: &str, new_rdn_value: &str) -> Result<(RdnComponent, String)> {
+    let parsed = DistinguishedName::parse(dn)?;
+    let mut components = parsed.components;
+    let rdn = components
+        .first_mut()
+        .ok_or_else(|| HeraclesError::InvalidDN(format!("cannot rename empty DN: {:?}", dn)))?;
+    rdn.attr_value = new_rdn_value.to_string();
+
+    let rdn = rdn.clone();
+    Ok((
+        rdn,
+        DistinguishedName::from_components(components).to_string(),
+    ))
+}
+
+/// Modification issued by [`LdapConnection::set_attribute`].
+fn set_attribute_modification(attr: &str, values: Vec<String>) -> LdapModification {
+    LdapModification::replace(attr, values)
+}
+
+/// Modification issued by [`LdapConnection::clear_attribute`].
+fn clear_attribute_modification(attr: &str) -> LdapModification {
+    LdapModification::delete_all(attr)
+}
+
+/// Interprets the result of clearing an attribute; `noSuchAttribute` means
+/// there was nothing to clear.
+fn clear_outcome(dn: &str, result: LdapResult) -> Result<()> {
+    if LdapResultCode::from_u32(result.rc) == LdapResultCode::NoSuchAttribute {
+        return Ok(());
+    }
+    result
+        .success()
+        .map(|_| ())
+        .map_err(|e| map_modify_error(dn, e))
+}
+
+/// Result of a user bind, including password policy details.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AuthOutcome {
+    /// Whether the credentials were accepted.
+    pub authenticated: bool,
+    /// Password policy information returned by the server, if any.
+    pub policy: Option<PasswordPolicyInfo>,
+}
+
+impl AuthOutcome {
+    /// Seconds until the password expires, if the server warned about it.
+    pub fn time_before_expiration(&self) -> Option<u64> {
+        self.policy.and_then(|p| p.time_before_expiration)
+    }
+
+    /// Grace logins left after expiry, if the server reported them.
+    pub fn grace_auths_remaining(&self) -> Option<u64> {
+        self.policy.and_then(|p| p.grace_auths_remaining)
+    }
+}
+
+/// Interprets a bind response: `invalidCredentials` is a failed
+/// authentication rather than an error.
+///
+/// The result code decides the outcome; a password policy control that
+/// can't be decoded is logged and treated as absent.
+pub(crate) fn auth_outcome(result: LdapResult) -> Result<AuthOutcome> {
+    let authenticated = match LdapResultCode::from_u32(result.rc) {
+        LdapResultCode::Success => true,
+        LdapResultCode::InvalidCredentials => false,
+        _ => {
+            return Err(map_result_error(LdapError::LdapResult { result }, |e| {
+                HeraclesError::LdapBind(e.to_string())
+            }))
+        }
+    };
+    let policy = PasswordPolicyInfo::from_controls(&result.ctrls).unwrap_or_else(|e| {
+        warn!("Ignoring undecodable password policy control: {}", e);
+        None
+    });
+    Ok(AuthOutcome {
+        authenticated,
+        policy,
+    })
+}
+
+/// Point-in-time description of a connection, for debugging churn.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConnInfo {
+    /// Time since the connection was opened.
+    pub age: Duration,
+    /// DN of the last successful bind, `None` if anonymous.
+    pub bound_dn: Option<String>,
+    /// Whether the connection uses LDAPS or STARTTLS.
+    pub encrypted: bool,
+    /// URI the connection was opened to.
+    pub uri: String,
+}
+
+/// Identity details of a connection, shared with the pool's registry.
+#[derive(Debug)]
+pub(crate) struct ConnState {
+    created: Instant,
+    uri: String,
+    encrypted: bool,
+    bound_dn: Option<String>,
+}
+
+impl ConnState {
+    pub(crate) fn new(uri: &str, encrypted: bool) -> Self {
+        Self {
+            created: Instant::now(),
+            uri: uri.to_string(),
+            encrypted,
+            bound_dn: None,
+        }
+    }
+
+    pub(crate) fn set_uri(&mut self, uri: &str, encrypted: bool) {
+        self.uri = uri.to_string();
+        self.encrypted = encrypted;
+    }
+
+    pub(crate) fn set_bound_dn(&mut self, dn: Option<&str>) {
+        self.bound_dn = dn.map(str::to_string);
+    }
+
+    pub(crate) fn info(&self) -> ConnInfo {
+        ConnInfo {
+            age: self.created.elapsed(),
+            bound_dn: self.bound_dn.clone(),
+            encrypted: self.encrypted,
+            uri: self.uri.clone(),
+        }
+    }
+}
 
 /// An LDAP connection that can perform operations.
 pub struct LdapConnection {
     ldap: Ldap,
     config: LdapConfig,
     bound: bool,
+    read_only: bool,
+    state: Arc<Mutex<ConnState>>,
 }
 
 impl LdapConnection {
     /// Creates a new LDAP connection.
+    ///
+    /// Servers in [`LdapConfig::server_uris`] are tried in order until one
+    /// accepts the connection.
     #[instrument(skip(config), fields(uri = %config.uri))]
     pub async fn new(config: LdapConfig) -> Result<Self> {
         config.validate()?;
+        let (ldap, uri) = open_ldap(&config).await?;
 
-        let settings = LdapConnSettings::new()
-            .set_conn_timeout(Duration::from_secs(config.timeout_seconds))
-            .set_starttls(config.use_tls);
-
-        debug!("Connecting to LDAP server: {}", config.uri);
-
-        let (conn, ldap) = LdapConnAsync::with_settings(settings, &config.uri)
-            .await
-            .map_err(|e| HeraclesError::LdapConnection(e.to_string()))?;
-
-        // Spawn the connection driver
-        tokio::spawn(async move {
-            if let Err(e) = conn.drive().await {
-                error!("LDAP connection error: {}", e);
-            }
-        });
+        let encrypted = is_encrypted(&config, &uri);
+        let state = Arc::new(Mutex::new(ConnState::new(&uri, encrypted)));
 
         Ok(Self {
             ldap,
             config,
             bound: false,
+            read_only: false,
+            state,
         })
     }
 
+    /// Returns true if writes are refused on this connection, as on
+    /// connections to a read replica.
+    pub fn is_read_only(&self) -> bool {
+        self.read_only
+    }
+
+    /// Refuses add, modify, modify DN and delete on this connection.
+    pub(crate) fn set_read_only(&mut self, read_only: bool) {
+        self.read_only = read_only;
+    }
+
+    /// Fails with `error` before anything is sent on a read-only connection.
+    fn ensure_writable(&self, error: fn(String) -> HeraclesError) -> Result<()> {
+        if self.read_only {
+            return Err(error(format!(
+                "connection to read replica {} does not accept writes",
+                self.info().uri
+            )));
+        }
+        Ok(())
+    }
+
+    /// Returns the connection's age, bound DN, encryption and URI.
+    pub fn info(&self) -> ConnInfo {
+        self.state.lock().unwrap().info()
+    }
+
+    /// Shared state read by the pool's connection registry.
+    pub(crate) fn state(&self) -> &Arc<Mutex<ConnState>> {
+        &self.state
+    }
+
+    fn set_bound_dn(&self, dn: Option<&str>) {
+        self.state.lock().unwrap().set_bound_dn(dn);
+    }
+
     /// Binds to the LDAP server using the configured credentials.
     #[instrument(skip(self))]
     pub async fn bind(&mut self) -> Result<()> {
         debug!("Binding as: {}", self.config.bind_dn);
 
-        self.ldap
-            .simple_bind(&self.config.bind_dn, &self.config.bind_password)
-            .await
-            .map_err(|e| HeraclesError::LdapBind(e.to_string()))?
-            .success()
-            .map_err(|e| HeraclesError::LdapBind(e.to_string()))?;
+        let ldap = &mut self.ldap;
+        let (bind_dn, bind_password) = (&self.config.bind_dn, &self.config.bind_password);
+        with_timeout(self.config.bind_timeout(), "bind", async move {
+            ldap.simple_bind(bind_dn, bind_password)
+                .await
+                .map_err(|e| map_transport_error(e, |e| HeraclesError::LdapBind(e.to_string())))?
+                .success()
+                .map_err(|e| map_result_error(e, |e| HeraclesError::LdapBind(e.to_string())))?;
+            Ok(())
+        })
+        .await
+        .inspect_err(|_| self.set_bound_dn(None))?;
 
         self.bound = true;
+        self.set_bound_dn(Some(&self.config.bind_dn));
         debug!("LDAP bind successful");
         Ok(())
     }
@@ -67,25 +816,83 @@ impl LdapConnection {
     pub async fn bind_as(&mut self, dn: &str, password: &str) -> Result<()> {
         debug!("Attempting bind as: {}", dn);
 
-        self.ldap
-            .simple_bind(dn, password)
-            .await
-            .map_err(|e| HeraclesError::LdapBind(e.to_string()))?
-            .success()
-            .map_err(|e| HeraclesError::LdapBind(format!("Invalid credentials: {}", e)))?;
+        let ldap = &mut self.ldap;
+        with_timeout(self.config.bind_timeout(), "bind", async move {
+            ldap.simple_bind(dn, password)
+                .await
+                .map_err(|e| HeraclesError::LdapBind(e.to_string()))?
+                .success()
+                .map_err(|e| {
+                    map_result_error(e, |e| {
+                        HeraclesError::LdapBind(format!("Invalid credentials: {}", e))
+                    })
+                })?;
+            Ok(())
+        })
+        .await
+        .inspect_err(|_| self.set_bound_dn(None))?;
 
+        self.set_bound_dn(Some(dn));
         debug!("Bind successful for: {}", dn);
         Ok(())
     }
 
+    /// Binds with user credentials, requesting password policy information.
+    ///
+    /// Rejected credentials yield `authenticated: false` rather than an
+    /// error, so callers can still read why (e.g. a locked account) and
+    /// warn about upcoming expiry or remaining grace logins.
+    #[instrument(skip(self, password), fields(dn = %dn))]
+    pub async fn authenticate_detailed(&mut self, dn: &str, password: &str) -> Result<AuthOutcome> {
+        debug!("Attempting bind with password policy control as: {}", dn);
+
+        let outcome = self
+            .policy_bind(dn, password)
+            .await
+            .and_then(auth_outcome)
+            .inspect_err(|_| self.set_bound_dn(None))?;
+        self.set_bound_dn(outcome.authenticated.then_some(dn));
+        Ok(outcome)
+    }
+
+    /// Sends a simple bind with the password policy request control and
+    /// returns the raw result, whatever its code.
+    pub(crate) async fn policy_bind(&mut self, dn: &str, password: &str) -> Result<LdapResult> {
+        let ldap = &mut self.ldap;
+        with_timeout(self.config.bind_timeout(), "bind", async move {
+            ldap.with_controls(ppolicy::request_control())
+                .simple_bind(dn, password)
+                .await
+                .map_err(|e| HeraclesError::LdapBind(e.to_string()))
+        })
+        .await
+    }
+
     /// Searches for LDAP entries.
     ///
+    /// Continuation references returned by the server are not entries and
+    /// are skipped; use [`search_with_referrals`](Self::search_with_referrals)
+    /// to receive their URIs. With `chase_referrals` set in [`LdapConfig`],
+    /// referrals are instead followed and the entries found there appended.
+    /// A referral that can't be followed fails the whole search, so results
+    /// are never silently incomplete.
+    ///
     /// # Arguments
     ///
-    /// * `base` - The base DN to search from (relative to config base_dn if not absolute)
+    /// * `base` - The base DN to search from (empty for the configured base_dn)
     /// * `scope` - Search scope (Base, OneLevel, Subtree)
     /// * `filter` - LDAP search filter
     /// * `attrs` - Attributes to retrieve (empty = all)
+    ///
+    /// If the server connection has dropped, the search is retried up to
+    /// `retry_count` times (see [`LdapConfig`]) on a freshly opened and
+    /// bound connection; a connection that still fails yields
+    /// `LdapConnection`. Writes are never retried, since a lost response
+    /// doesn't tell whether they were applied.
+    ///
+    /// The configured `size_limit` and `time_limit` are sent with the
+    /// request. Exceeding them fails with `SizeLimitExceeded` or `Timeout`
+    /// rather than returning a truncated result.
     #[instrument(skip(self, attrs), fields(base = %base, filter = %filter))]
     pub async fn search(
         &mut self,
@@ -94,16 +901,45 @@ impl LdapConnection {
         filter: &str,
         attrs: Vec<&str>,
     ) -> Result<Vec<LdapEntry>> {
-        self.ensure_bound().await?;
+        let options = search_options(&self.config, None);
+        self.search_limited(base, scope, filter, attrs, options)
+            .await
+    }
 
-        // Build absolute base DN
-        let search_base = if base.contains('=') {
-            base.to_string()
-        } else if base.is_empty() {
-            self.config.base_dn.clone()
+    /// Runs the search described by `builder`.
+    ///
+    /// Like [`search`](Self::search), with the builder's base, scope,
+    /// filter and attributes; its size limit, when set, replaces the
+    /// configured one. Sort keys make it a
+    /// [`search_sorted`](Self::search_sorted).
+    #[instrument(skip(self, builder), fields(base = %builder.get_base(), filter = %builder.get_filter()))]
+    pub async fn search_with(&mut self, builder: &SearchBuilder) -> Result<Vec<LdapEntry>> {
+        let options = search_options(&self.config, builder.get_size_limit());
+        let (base, scope, filter) = (
+            builder.get_base(),
+            builder.get_scope().into(),
+            builder.get_filter(),
+        );
+        let attrs = builder.get_attributes();
+        if builder.get_sort_keys().is_empty() {
+            self.search_limited(base, scope, filter, attrs, options)
+                .await
         } else {
-            format!("{},{}", base, self.config.base_dn)
-        };
+            self.sorted_search(base, scope, filter, attrs, builder.get_sort_keys(), options)
+                .await
+        }
+    }
+
+    /// Resolves the base and runs the search with retries under `options`.
+    async fn search_limited(
+        &mut self,
+        base: &str,
+        scope: Scope,
+        filter: &str,
+        attrs: Vec<&str>,
+        options: SearchOptions,
+    ) -> Result<Vec<LdapEntry>> {
+        let search_base = self.resolve_search_base(base)?;
 
         trace!(
             "Searching: base={}, scope={:?}, filter={}",
@@ -112,38 +948,545 @@ impl LdapConnection {
             filter
         );
 
-        let (results, _res) = self
-            .ldap
-            .search(&search_base, scope, filter, attrs)
-            .await
-            .map_err(|e| HeraclesError::LdapSearch(e.to_string()))?
-            .success()
-            .map_err(|e| HeraclesError::LdapSearch(e.to_string()))?;
-
-        let entries: Vec<LdapEntry> = results
-            .into_iter()
-            .map(|entry| {
-                let search_entry = SearchEntry::construct(entry);
-                LdapEntry {
-                    dn: search_entry.dn,
-                    attributes: search_entry.attrs.into_iter().collect(),
-                }
+        let filter = filter.to_string();
+        let attrs: Vec<String> = attrs.into_iter().map(str::to_string).collect();
+        let retries = self.config.retry_count;
+        if !self.config.chase_referrals {
+            return retry_transient(self, retries, |conn| {
+                let (base, filter, attrs) = (search_base.clone(), filter.clone(), attrs.clone());
+                let options = options.clone();
+                Box::pin(async move {
+                    conn.search_once(&base, scope, &filter, attrs, options)
+                        .await
+                })
             })
-            .collect();
+            .await;
+        }
 
-        debug!("Search returned {} entries", entries.len());
+        let (mut entries, referrals) = retry_transient(self, retries, |conn| {
+            let (base, filter, attrs) = (search_base.clone(), filter.clone(), attrs.clone());
+            let options = options.clone();
+            Box::pin(async move {
+                conn.search_with_referrals_once(&base, scope, &filter, attrs, options)
+                    .await
+            })
+        })
+        .await?;
+        if !referrals.is_empty() {
+            let chased = self
+                .chase_referrals(referrals, &search_base, scope, &filter, &attrs, options)
+                .await?;
+            debug!("Referrals returned {} more entries", chased.len());
+            entries.extend(chased);
+        }
         Ok(entries)
     }
 
-    /// Adds a new LDAP entry.
-    #[instrument(skip(self, attributes), fields(dn = %dn))]
-    pub async fn add(&mut self, dn: &str, attributes: HashMap<String, Vec<String>>) -> Result<()> {
-        use std::collections::HashSet;
-        self.ensure_bound().await?;
-
-        let attrs: Vec<(String, HashSet<String>)> = attributes
-            .into_iter()
-            .map(|(k, v)| (k, v.into_iter().collect::<HashSet<_>>()))
+    /// Follows referrals from a search, each on a short-lived connection to
+    /// the server it names.
+    ///
+    /// Only servers passing [`referral::is_trusted`] are contacted; any
+    /// other referral fails the search. The service credentials are only
+    /// sent over LDAPS or StartTLS, and plain connections bind anonymously.
+    /// The referral URL's base, scope and filter replace the original ones
+    /// when present. See [`referral::follow`] for the depth limit.
+    async fn chase_referrals(
+        &self,
+        referrals: Vec<String>,
+        base: &str,
+        scope: Scope,
+        filter: &str,
+        attrs: &[String],
+        options: SearchOptions,
+    ) -> Result<Vec<LdapEntry>> {
+        let config = self.config.clone();
+        let (base, filter, attrs) = (base.to_string(), filter.to_string(), attrs.to_vec());
+        referral::follow(referrals, config.max_referral_depth, move |url| {
+            let target = referral_target(&config, &url.server);
+            let base = url.base.unwrap_or_else(|| base.clone());
+            let scope = url.scope.map(Scope::from).unwrap_or(scope);
+            let filter = url.filter.unwrap_or_else(|| filter.clone());
+            let (attrs, options) = (attrs.clone(), options.clone());
+            Box::pin(async move {
+                let (config, service_bind) = target?;
+                let mut conn = LdapConnection::new(config).await?;
+                if !service_bind {
+                    if let Err(e) = conn.bind_anonymous().await {
+                        conn.close().await;
+                        return Err(e);
+                    }
+                }
+                let result = conn
+                    .search_with_referrals_once(&base, scope, &filter, attrs, options)
+                    .await;
+                conn.close().await;
+                result
+            })
+        })
+        .await
+    }
+
+    /// Runs one attempt of [`search`](Self::search) on a resolved base.
+    async fn search_once(
+        &mut self,
+        base: &str,
+        scope: Scope,
+        filter: &str,
+        attrs: Vec<String>,
+        options: SearchOptions,
+    ) -> Result<Vec<LdapEntry>> {
+        self.ensure_bound().await?;
+
+        let (results, _res) = self
+            .ldap
+            .with_search_options(options)
+            .search(base, scope, filter, attrs)
+            .await
+            .map_err(|e| map_transport_error(e, |e| HeraclesError::LdapSearch(e.to_string())))?
+            .success()
+            .map_err(|e| map_result_error(e, |e| HeraclesError::LdapSearch(e.to_string())))?;
+
+        let (entries, referrals) = partition_results(results);
+        if !referrals.is_empty() {
+            debug!("Search skipped {} continuation references", referrals.len());
+        }
+
+        debug!("Search returned {} entries", entries.len());
+        Ok(entries)
+    }
+
+    /// Searches with results ordered by the server (RFC 2891).
+    ///
+    /// `sort_keys` are `(attribute, descending)` pairs in priority order,
+    /// e.g. `sn` then `givenName`. The sort control is sent as critical, so
+    /// a server without sorting support fails with a `LdapSearch` error
+    /// rather than returning unsorted entries; a server that can't sort on
+    /// a given attribute is reported the same way. With no keys this is a
+    /// plain [`search`](Self::search).
+    #[instrument(skip(self, attrs, sort_keys), fields(base = %base, filter = %filter))]
+    pub async fn search_sorted(
+        &mut self,
+        base: &str,
+        scope: Scope,
+        filter: &str,
+        attrs: Vec<&str>,
+        sort_keys: &[(String, bool)],
+    ) -> Result<Vec<LdapEntry>> {
+        if sort_keys.is_empty() {
+            return self.search(base, scope, filter, attrs).await;
+        }
+        let options = search_options(&self.config, None);
+        self.sorted_search(base, scope, filter, attrs, sort_keys, options)
+            .await
+    }
+
+    /// Runs a sorted search under `options`.
+    async fn sorted_search(
+        &mut self,
+        base: &str,
+        scope: Scope,
+        filter: &str,
+        attrs: Vec<&str>,
+        sort_keys: &[(String, bool)],
+        options: SearchOptions,
+    ) -> Result<Vec<LdapEntry>> {
+        self.ensure_bound().await?;
+
+        let search_base = self.resolve_search_base(base)?;
+        trace!("Sorted search on {:?}", sort_keys);
+
+        let (results, result) = self
+            .ldap
+            .with_controls(sort::request_control(sort_keys))
+            .with_search_options(options)
+            .search(&search_base, scope, filter, attrs)
+            .await
+            .map_err(|e| HeraclesError::LdapSearch(e.to_string()))?
+            .success()
+            .map_err(map_sort_error)?;
+        sort::check_response(&result.ctrls)?;
+
+        let (entries, _) = partition_results(results);
+        debug!("Sorted search returned {} entries", entries.len());
+        Ok(entries)
+    }
+
+    /// Searches, requesting only the attributes `acl` lets the user read.
+    ///
+    /// The requested list is narrowed against the attribute read rules for
+    /// `object_type` on the resolved search base, so unreadable attributes
+    /// never leave the server. Rules scoped below the base aren't seen
+    /// here; use [`UserAcl::redact_entry`] on the results when they matter,
+    /// and for wildcard requests, which aren't narrowed.
+    #[instrument(skip(self, attrs, acl), fields(base = %base, filter = %filter))]
+    pub async fn search_readable(
+        &mut self,
+        base: &str,
+        scope: Scope,
+        filter: &str,
+        attrs: Vec<&str>,
+        acl: &UserAcl,
+        object_type: &str,
+    ) -> Result<Vec<LdapEntry>> {
+        let search_base = self.resolve_search_base(base)?;
+        let narrowed = readable_search_attrs(acl, &search_base, object_type, &attrs);
+        trace!("Narrowed search attributes {:?} -> {:?}", attrs, narrowed);
+
+        self.search(
+            &search_base,
+            scope,
+            filter,
+            narrowed.iter().map(String::as_str).collect(),
+        )
+        .await
+    }
+
+    /// Returns true if `dn` has at least one child entry.
+    ///
+    /// Runs a one-level search for any object with a size limit of 1,
+    /// requesting no attributes. See [`LdapEntry::is_container_by_class`] for
+    /// a check that needs no round trip.
+    #[instrument(skip(self), fields(dn = %dn))]
+    pub async fn is_container(&mut self, dn: &str) -> Result<bool> {
+        self.ensure_bound().await?;
+
+        let base = self.resolve_search_base(dn)?;
+        let ldap3::SearchResult(results, result) = self
+            .ldap
+            .with_search_options(SearchOptions::new().sizelimit(1))
+            .search(&base, Scope::OneLevel, "(objectClass=*)", vec!["1.1"])
+            .await
+            .map_err(|e| HeraclesError::LdapSearch(e.to_string()))?;
+
+        has_children(&results, result)
+    }
+
+    /// Finds an entry by its `entryUUID`, which survives renames and moves.
+    ///
+    /// Searches the subtree under `base` (empty for the base DN). Returns
+    /// `None` if nothing matches and an `LdapSearch` error if several
+    /// entries do.
+    #[instrument(skip(self))]
+    pub async fn find_by_uuid(&mut self, uuid: &str, base: &str) -> Result<Option<LdapEntry>> {
+        let entries = self
+            .search(base, Scope::Subtree, &uuid_filter(uuid), vec!["*"])
+            .await?;
+        single_uuid_match(entries, uuid)
+    }
+
+    /// Fetches the entries at `dns`, in order, with `None` for missing ones.
+    ///
+    /// DNs are absolute. Each batch of [`GET_MANY_BATCH`] DNs is looked up
+    /// with one subtree search under the base DN, matching `entryDN` or
+    /// `distinguishedName`. A batch that matches nothing is retried with a
+    /// base search per DN, which covers servers with neither attribute and
+    /// DNs outside the base DN, so a list of mostly missing DNs costs a
+    /// round trip each. Repeated DNs get the same entry.
+    #[instrument(skip(self, dns, attrs), fields(count = dns.len()))]
+    pub async fn get_many(
+        &mut self,
+        dns: &[&str],
+        attrs: Vec<&str>,
+    ) -> Result<Vec<Option<LdapEntry>>> {
+        let dns = dns
+            .iter()
+            .map(|dn| DistinguishedName::parse(dn))
+            .collect::<Result<Vec<_>>>()?;
+        let base = self.config.base_dn.clone();
+
+        let mut ordered = Vec::with_capacity(dns.len());
+        for batch in dns.chunks(GET_MANY_BATCH) {
+            let entries = self
+                .search(&base, Scope::Subtree, &dn_filter(batch), attrs.clone())
+                .await?;
+            if !entries.is_empty() {
+                ordered.extend(order_by_dn(batch, entries));
+                continue;
+            }
+
+            debug!("Batched DN lookup matched nothing, trying base searches");
+            self.ensure_bound().await?;
+            for dn in batch {
+                let ldap3::SearchResult(results, result) = self
+                    .ldap
+                    .with_search_options(search_options(&self.config, None))
+                    .search(
+                        &dn.to_string(),
+                        Scope::Base,
+                        "(objectClass=*)",
+                        attrs.clone(),
+                    )
+                    .await
+                    .map_err(|e| {
+                        map_transport_error(e, |e| HeraclesError::LdapSearch(e.to_string()))
+                    })?;
+                ordered.push(base_entry_outcome(results, result)?);
+            }
+        }
+        Ok(ordered)
+    }
+
+    /// Performs a search, returning matched entries and referral URIs separately.
+    ///
+    /// Referrals are neither followed nor treated as errors, leaving the
+    /// caller to decide what to do with them, whatever `chase_referrals`
+    /// says. Dropped connections are retried as in [`search`](Self::search).
+    #[instrument(skip(self, attrs), fields(base = %base, filter = %filter))]
+    pub async fn search_with_referrals(
+        &mut self,
+        base: &str,
+        scope: Scope,
+        filter: &str,
+        attrs: Vec<&str>,
+    ) -> Result<(Vec<LdapEntry>, Vec<String>)> {
+        let search_base = self.resolve_search_base(base)?;
+
+        let filter = filter.to_string();
+        let attrs: Vec<String> = attrs.into_iter().map(str::to_string).collect();
+        let retries = self.config.retry_count;
+        retry_transient(self, retries, |conn| {
+            let (base, filter, attrs) = (search_base.clone(), filter.clone(), attrs.clone());
+            let options = search_options(&conn.config, None);
+            Box::pin(async move {
+                conn.search_with_referrals_once(&base, scope, &filter, attrs, options)
+                    .await
+            })
+        })
+        .await
+    }
+
+    /// Runs one attempt of [`search_with_referrals`](Self::search_with_referrals).
+    async fn search_with_referrals_once(
+        &mut self,
+        base: &str,
+        scope: Scope,
+        filter: &str,
+        attrs: Vec<String>,
+        options: SearchOptions,
+    ) -> Result<(Vec<LdapEntry>, Vec<String>)> {
+        self.ensure_bound().await?;
+
+        let ldap3::SearchResult(results, result) = self
+            .ldap
+            .with_search_options(options)
+            .search(base, scope, filter, attrs)
+            .await
+            .map_err(|e| map_transport_error(e, |e| HeraclesError::LdapSearch(e.to_string())))?;
+
+        let (entries, referrals) = split_search_results(results, result)?;
+        debug!(
+            "Search returned {} entries and {} referrals",
+            entries.len(),
+            referrals.len()
+        );
+        Ok((entries, referrals))
+    }
+
+    /// Searches with the Simple Paged Results control (RFC 2696), yielding
+    /// one `Vec` of entries per page.
+    ///
+    /// Each page is requested only when the stream is polled, so at most
+    /// `page_size` entries are held at once and server size limits apply
+    /// per page rather than to the whole result. The paging cookie is
+    /// carried internally until the server reports the last page; at least
+    /// one (possibly empty) page is yielded. Servers that ignore the
+    /// control return everything as a single page. The first error ends
+    /// the stream.
+    #[instrument(skip(self, attrs), fields(base = %base, filter = %filter))]
+    pub async fn search_paged(
+        &mut self,
+        base: &str,
+        scope: Scope,
+        filter: &str,
+        attrs: Vec<&str>,
+        page_size: i32,
+    ) -> Result<impl Stream<Item = Result<Vec<LdapEntry>>> + Send + 'static> {
+        if page_size <= 0 {
+            return Err(HeraclesError::LdapSearch(format!(
+                "page size must be positive, got {}",
+                page_size
+            )));
+        }
+        self.ensure_bound().await?;
+
+        let search = PagedSearch {
+            ldap: self.ldap.clone(),
+            base: self.resolve_search_base(base)?,
+            scope,
+            filter: filter.to_string(),
+            attrs: attrs.into_iter().map(str::to_string).collect(),
+            page_size,
+            cookie: Vec::new(),
+        };
+
+        Ok(futures::stream::unfold(Some(search), |state| async move {
+            let mut search = state?;
+            match search.next_page().await {
+                Ok((entries, more)) => {
+                    trace!("Paged search returned {} entries", entries.len());
+                    Some((Ok(entries), more.then_some(search)))
+                }
+                Err(e) => Some((Err(e), None)),
+            }
+        }))
+    }
+
+    /// Runs [`search_paged`](Self::search_paged) to completion and returns
+    /// every entry.
+    ///
+    /// Avoids server size limits on large subtrees, but still holds the
+    /// whole result in memory.
+    pub async fn search_all_paged(
+        &mut self,
+        base: &str,
+        scope: Scope,
+        filter: &str,
+        attrs: Vec<&str>,
+        page_size: i32,
+    ) -> Result<Vec<LdapEntry>> {
+        let entries: Vec<LdapEntry> = self
+            .search_paged(base, scope, filter, attrs, page_size)
+            .await?
+            .try_concat()
+            .await?;
+        debug!("Paged search returned {} entries", entries.len());
+        Ok(entries)
+    }
+
+    /// Searches, yielding entries one at a time as they arrive.
+    ///
+    /// Only the entry being converted is held in memory, so exports of
+    /// millions of entries run in constant space. Results are fetched in
+    /// pages of [`STREAM_PAGE_SIZE`] with the Simple Paged Results control,
+    /// so server size limits apply per page; references and intermediate
+    /// responses are skipped. The connection stays borrowed for the
+    /// lifetime of the stream, so it can't be rebound or returned to the
+    /// pool mid-search. The first error ends the stream.
+    #[instrument(skip(self, attrs), fields(base = %base, filter = %filter))]
+    pub async fn search_stream(
+        &mut self,
+        base: &str,
+        scope: Scope,
+        filter: &str,
+        attrs: Vec<&str>,
+    ) -> Result<impl Stream<Item = Result<LdapEntry>> + Send + '_> {
+        self.ensure_bound().await?;
+        let search_base = self.resolve_search_base(base)?;
+        let attrs: Vec<String> = attrs.into_iter().map(str::to_string).collect();
+
+        let adapters: Vec<Box<dyn Adapter<'static, String, Vec<String>>>> = vec![
+            Box::new(EntriesOnly::new()),
+            Box::new(PagedAdapter::new(STREAM_PAGE_SIZE)),
+        ];
+        let stream = self
+            .ldap
+            .streaming_search_with(adapters, &search_base, scope, filter, attrs)
+            .await
+            .map_err(|e| HeraclesError::LdapSearch(e.to_string()))?;
+
+        Ok(futures::stream::unfold(Some(stream), |state| async move {
+            let mut stream = state?;
+            match stream.next().await {
+                Ok(Some(entry)) => Some((
+                    Ok(LdapEntry::from(SearchEntry::construct(entry))),
+                    Some(stream),
+                )),
+                Ok(None) => match stream.finish().await.success() {
+                    Ok(_) => None,
+                    Err(e) => Some((
+                        Err(map_result_error(e, |e| {
+                            HeraclesError::LdapSearch(e.to_string())
+                        })),
+                        None,
+                    )),
+                },
+                Err(e) => Some((Err(HeraclesError::LdapSearch(e.to_string())), None)),
+            }
+        }))
+    }
+
+    /// Exports a subtree as LDIF, yielding one record per entry as it arrives.
+    ///
+    /// The first item is the `version: 1` header, so concatenating all items
+    /// produces a complete LDIF file. Entries are never buffered, which keeps
+    /// memory use constant for large exports. Binary values are base64-encoded.
+    #[instrument(skip(self), fields(base = %base, filter = %filter))]
+    pub async fn export_ldif_stream(
+        &mut self,
+        base: &str,
+        scope: Scope,
+        filter: &str,
+    ) -> Result<impl Stream<Item = Result<String>> + Send + 'static> {
+        self.ensure_bound().await?;
+        let search_base = self.resolve_search_base(base)?;
+
+        let stream = self
+            .ldap
+            .streaming_search(&search_base, scope, filter, vec!["*".to_string()])
+            .await
+            .map_err(|e| HeraclesError::LdapSearch(e.to_string()))?;
+
+        let entries = futures::stream::unfold(Some(stream), |state| async move {
+            let mut stream = state?;
+            loop {
+                match stream.next().await {
+                    Ok(Some(entry)) if entry.is_ref() || entry.is_intermediate() => continue,
+                    Ok(Some(entry)) => {
+                        return Some((Ok(SearchEntry::construct(entry)), Some(stream)))
+                    }
+                    Ok(None) => {
+                        return match stream.finish().await.success() {
+                            Ok(_) => None,
+                            Err(e) => Some((
+                                Err(map_result_error(e, |e| {
+                                    HeraclesError::LdapSearch(e.to_string())
+                                })),
+                                None,
+                            )),
+                        };
+                    }
+                    Err(e) => return Some((Err(HeraclesError::LdapSearch(e.to_string())), None)),
+                }
+            }
+        });
+
+        Ok(ldif_records(entries))
+    }
+
+    /// Resolves a search base against the configured base DN.
+    ///
+    /// An empty base resolves to `base_dn`; any other base is an absolute
+    /// DN and returned as-is, even outside `base_dn` (e.g. `cn=schema`).
+    /// Malformed bases yield `HeraclesError::InvalidDN`.
+    pub fn resolve_search_base(&self, base: &str) -> Result<String> {
+        resolve_base(base, &self.config.base_dn)
+    }
+
+    /// Resolves a base given relative to the configured base DN.
+    ///
+    /// `ou=users` becomes `ou=users,<base_dn>` and an empty base resolves
+    /// to `base_dn`. The result can be passed to [`search`](Self::search).
+    /// Malformed bases yield `HeraclesError::InvalidDN`.
+    pub fn resolve_relative_base(&self, relative: &str) -> Result<String> {
+        resolve_relative(relative, &self.config.base_dn)
+    }
+
+    /// Adds a new LDAP entry.
+    ///
+    /// Attribute names are checked with [`validate_attribute_name`] before
+    /// anything is sent.
+    #[instrument(skip(self, attributes), fields(dn = %dn))]
+    pub async fn add(&mut self, dn: &str, attributes: HashMap<String, Vec<String>>) -> Result<()> {
+        use std::collections::HashSet;
+        for name in attributes.keys() {
+            validate_attribute_name(name)?;
+        }
+        self.ensure_writable(HeraclesError::LdapAdd)?;
+        self.ensure_bound().await?;
+
+        let attrs: Vec<(String, HashSet<String>)> = attributes
+            .into_iter()
+            .map(|(k, v)| (k, v.into_iter().collect::<HashSet<_>>()))
             .collect();
         let attrs_ref: Vec<(&str, HashSet<&str>)> = attrs
             .iter()
@@ -157,24 +1500,66 @@ impl LdapConnection {
             .await
             .map_err(|e| HeraclesError::LdapAdd(e.to_string()))?
             .success()
-            .map_err(|e| {
-                if e.to_string().contains("68") || e.to_string().contains("Already exists") {
-                    HeraclesError::LdapAlreadyExists(dn.to_string())
-                } else {
-                    HeraclesError::LdapAdd(e.to_string())
-                }
-            })?;
+            .map_err(|e| map_add_error(dn, e))?;
 
         debug!("Entry added successfully: {}", dn);
         Ok(())
     }
 
+    /// Adds an entry unless one already exists at `dn`.
+    ///
+    /// Returns `true` when the entry was created and `false` when the
+    /// server answered `entryAlreadyExists`; the existing entry is left
+    /// untouched. Other failures are returned as for [`add`](Self::add).
+    pub async fn add_if_absent(
+        &mut self,
+        dn: &str,
+        attributes: HashMap<String, Vec<String>>,
+    ) -> Result<bool> {
+        absent_outcome(self.add(dn, attributes).await)
+    }
+
+    /// Adds an entry, or applies `modifications` if it already exists.
+    ///
+    /// Returns `true` when the entry was created from `attributes` and
+    /// `false` when `modifications` were applied to the existing one. The
+    /// two steps are separate operations, so an entry deleted in between
+    /// fails the modify with `LdapNotFound`.
+    #[instrument(skip(self, attributes, modifications), fields(dn = %dn))]
+    pub async fn upsert(
+        &mut self,
+        dn: &str,
+        attributes: HashMap<String, Vec<String>>,
+        modifications: Vec<LdapModification>,
+    ) -> Result<bool> {
+        if self.add_if_absent(dn, attributes).await? {
+            return Ok(true);
+        }
+        debug!("Entry exists, modifying instead: {}", dn);
+        self.modify(dn, modifications).await?;
+        Ok(false)
+    }
+
     /// Modifies an existing LDAP entry.
+    ///
+    /// Attribute names are checked with [`validate_attribute_name`] before
+    /// anything is sent. When `modifications` include an
+    /// [`Increment`](LdapModification::Increment), the server must advertise
+    /// [`INCREMENT_FEATURE_OID`] in its root DSE; otherwise the modify fails
+    /// with `LdapModify` without being sent.
     #[instrument(skip(self, modifications), fields(dn = %dn))]
     pub async fn modify(&mut self, dn: &str, modifications: Vec<LdapModification>) -> Result<()> {
+        for modification in &modifications {
+            validate_attribute_name(modification.attr())?;
+        }
+        self.ensure_writable(HeraclesError::LdapModify)?;
         self.ensure_bound().await?;
+        if modifications.iter().any(|m| m.is_increment()) {
+            require_increment(&self.supported_features().await?)?;
+        }
 
-        let mods: Vec<ldap3::Mod<&str>> = modifications.iter().map(|m| m.to_ldap3_mod()).collect();
+        let mods: Vec<ldap3::Mod<String>> =
+            modifications.iter().map(|m| m.to_ldap3_mod()).collect();
 
         debug!("Modifying entry: {} with {} changes", dn, mods.len());
 
@@ -183,21 +1568,103 @@ impl LdapConnection {
             .await
             .map_err(|e| HeraclesError::LdapModify(e.to_string()))?
             .success()
-            .map_err(|e| {
-                if e.to_string().contains("32") || e.to_string().contains("No such object") {
-                    HeraclesError::LdapNotFound(dn.to_string())
-                } else {
-                    HeraclesError::LdapModify(e.to_string())
-                }
-            })?;
+            .map_err(|e| map_modify_error(dn, e))?;
 
         debug!("Entry modified successfully: {}", dn);
         Ok(())
     }
 
+    /// Sets all values of an attribute with a single `Replace`.
+    ///
+    /// Existing values are cleared and `values` stored in one operation; an
+    /// empty `values` removes the attribute.
+    #[instrument(skip(self, values), fields(dn = %dn, attr = %attr))]
+    pub async fn set_attribute(&mut self, dn: &str, attr: &str, values: Vec<String>) -> Result<()> {
+        self.modify(dn, vec![set_attribute_modification(attr, values)])
+            .await
+    }
+
+    /// Removes every value of an attribute.
+    ///
+    /// Clearing an attribute the entry does not have succeeds, so the call
+    /// is idempotent.
+    #[instrument(skip(self), fields(dn = %dn, attr = %attr))]
+    pub async fn clear_attribute(&mut self, dn: &str, attr: &str) -> Result<()> {
+        validate_attribute_name(attr)?;
+        self.ensure_writable(HeraclesError::LdapModify)?;
+        self.ensure_bound().await?;
+
+        let modification = clear_attribute_modification(attr);
+        let result = self
+            .ldap
+            .modify(dn, vec![modification.to_ldap3_mod()])
+            .await
+            .map_err(|e| HeraclesError::LdapModify(e.to_string()))?;
+
+        clear_outcome(dn, result)
+    }
+
+    /// Renames an entry's RDN value and keeps the RDN attribute in sync.
+    ///
+    /// The new RDN reuses the current attribute type, so renaming
+    /// `uid=jdoe,ou=users,...` to `jsmith` yields `uid=jsmith,ou=users,...`.
+    /// Other pairs of a multi-valued RDN are kept as they are. The modify DN
+    /// runs with `deleteoldrdn` set, so the server swaps the old RDN value
+    /// for the new one and leaves any other values of the attribute alone.
+    /// Returns the new DN.
+    ///
+    /// This is a single modify DN operation; callers that also need to
+    /// update attributes derived from the old value (e.g. `homeDirectory`)
+    /// must do so separately, and that update is not atomic with the rename.
+    #[instrument(skip(self), fields(dn = %dn))]
+    pub async fn rename_rdn_and_attr(&mut self, dn: &str, new_rdn_value: &str) -> Result<String> {
+        self.ensure_bound().await?;
+
+        let (rdn, new_dn) = rename_target(dn, new_rdn_value)?;
+        debug!("Renaming entry: {} -> {}", dn, new_dn);
+
+        self.modify_dn(dn, &rdn.to_string(), true, None).await?;
+
+        debug!("Entry renamed successfully: {}", new_dn);
+        Ok(new_dn)
+    }
+
+    /// Renames an entry and/or moves it under a new parent.
+    ///
+    /// `new_rdn` is the full new RDN (e.g. `uid=jsmith`); `delete_old_rdn`
+    /// drops the old RDN value from the entry. With `new_superior` set the
+    /// entry is moved there, which requires the target parent to already
+    /// exist. A missing entry yields `LdapNotFound` and an existing target
+    /// `LdapAlreadyExists`, as with [`add`](Self::add).
+    #[instrument(skip(self), fields(dn = %dn))]
+    pub async fn modify_dn(
+        &mut self,
+        dn: &str,
+        new_rdn: &str,
+        delete_old_rdn: bool,
+        new_superior: Option<&str>,
+    ) -> Result<()> {
+        self.ensure_writable(HeraclesError::LdapModify)?;
+        self.ensure_bound().await?;
+
+        let target = modify_dn_target(dn, new_rdn, new_superior);
+        debug!("Modifying DN: {} -> {}", dn, target);
+
+        self.ldap
+            .modifydn(dn, new_rdn, delete_old_rdn, new_superior)
+            .await
+            .map_err(|e| HeraclesError::LdapModify(e.to_string()))?
+            .success()
+            .map_err(|e| map_modify_dn_error(dn, &target, e))?;
+
+        debug!("Entry DN modified successfully: {}", target);
+        Ok(())
+    }
+
     /// Deletes an LDAP entry.
     #[instrument(skip(self), fields(dn = %dn))]
     pub async fn delete(&mut self, dn: &str) -> Result<()> {
+        self.ensure_writable(HeraclesError::LdapDelete)?;
         self.ensure_bound().await?;
 
         debug!("Deleting entry: {}", dn);
@@ -208,20 +1675,86 @@ impl LdapConnection {
             .map_err(|e| HeraclesError::LdapDelete(e.to_string()))?
             .success()
             .map_err(|e| {
-                if e.to_string().contains("32") || e.to_string().contains("No such object") {
-                    HeraclesError::LdapNotFound(dn.to_string())
-                } else {
-                    HeraclesError::LdapDelete(e.to_string())
-                }
+                map_result_error(e, |e| {
+                    if LdapResultCode::from_error(&e) == Some(LdapResultCode::NoSuchObject) {
+                        HeraclesError::LdapNotFound(dn.to_string())
+                    } else {
+                        HeraclesError::LdapDelete(e.to_string())
+                    }
+                })
             })?;
 
         debug!("Entry deleted successfully: {}", dn);
         Ok(())
     }
 
-    /// Checks if the connection is bound.
-    pub fn is_bound(&self) -> bool {
-        self.bound
+    /// Issues a cheap Root DSE read to check (and keep alive) the connection.
+    #[instrument(skip(self))]
+    pub async fn ping(&mut self) -> Result<()> {
+        self.ldap
+            .search("", Scope::Base, "(objectClass=*)", vec!["1.1"])
+            .await
+            .map_err(|e| HeraclesError::LdapConnection(e.to_string()))?
+            .success()
+            .map_err(|e| HeraclesError::LdapConnection(format!("Ping failed: {}", e)))?;
+        trace!("Ping succeeded");
+        Ok(())
+    }
+
+    /// Returns the authorization identity the server associates with this
+    /// connection, using the "Who am I?" extended operation (RFC 4532).
+    ///
+    /// The result is an authzId such as `dn:uid=jdoe,ou=users,dc=example,dc=com`
+    /// or `u:jdoe`, and empty for an anonymous connection. Unlike the DN
+    /// passed to a bind, it reflects SASL mappings and proxied
+    /// authorization. No bind is attempted first.
+    #[instrument(skip(self))]
+    pub async fn whoami(&mut self) -> Result<String> {
+        let (exop, _) = self
+            .ldap
+            .extended(WhoAmI)
+            .await
+            .map_err(|e| HeraclesError::LdapConnection(e.to_string()))?
+            .success()
+            .map_err(|e| {
+                map_result_error(e, |e| {
+                    HeraclesError::LdapConnection(format!("Who am I? failed: {}", e))
+                })
+            })?;
+
+        let authzid = whoami_authzid(exop)?;
+        trace!("Who am I? returned {:?}", authzid);
+        Ok(authzid)
+    }
+
+    /// Returns the `supportedFeatures` OIDs advertised in the root DSE.
+    #[instrument(skip(self))]
+    pub async fn supported_features(&mut self) -> Result<Vec<String>> {
+        let mut entries = self
+            .search_once(
+                "",
+                Scope::Base,
+                "(objectClass=*)",
+                vec!["supportedFeatures".to_string()],
+                SearchOptions::new(),
+            )
+            .await?;
+        Ok(entries
+            .pop()
+            .and_then(|mut e| e.attributes.remove("supportedFeatures"))
+            .unwrap_or_default())
+    }
+
+    /// Binds anonymously, so later operations don't bind as the service.
+    async fn bind_anonymous(&mut self) -> Result<()> {
+        self.bind_as("", "").await?;
+        self.bound = true;
+        Ok(())
+    }
+
+    /// Checks if the connection is bound.
+    pub fn is_bound(&self) -> bool {
+        self.bound
     }
 
     /// Returns the base DN from the configuration.
@@ -245,13 +1778,1080 @@ impl LdapConnection {
                 .await
                 .map_err(|e| HeraclesError::Internal(e.to_string()))?;
             self.bound = false;
+            self.set_bound_dn(None);
         }
         Ok(())
     }
 }
 
+impl Reconnect for LdapConnection {
+    fn can_reconnect(&self) -> bool {
+        match &self.state.lock().unwrap().bound_dn {
+            Some(dn) => *dn == self.config.bind_dn,
+            // Not bound yet: the operation would bind as the service anyway.
+            None => !self.bound,
+        }
+    }
+
+    async fn reconnect(&mut self) -> Result<()> {
+        let (ldap, uri) = open_ldap(&self.config).await?;
+        self.ldap = ldap;
+        self.state
+            .lock()
+            .unwrap()
+            .set_uri(&uri, is_encrypted(&self.config, &uri));
+        self.bound = false;
+        self.set_bound_dn(None);
+        self.bind().await
+    }
+}
+
+impl LdapConnection {
+    /// Unbinds and closes the connection, whatever its bind state.
+    ///
+    /// Errors are only logged: the connection is dropped either way.
+    pub(crate) async fn close(mut self) {
+        if let Err(e) = self.ldap.unbind().await {
+            debug!("Error while closing LDAP connection: {}", e);
+        }
+    }
+}
+
 impl Drop for LdapConnection {
     fn drop(&mut self) {
         self.bound = false;
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::acl::{AttributeFilter, ObjectAttributeAcl, PermissionBitmap};
+    use crate::ldap::operations::SearchScope;
+    use crate::ldap::test_server;
+    use ldap3::asn1::{ASNTag, OctetString, Sequence, Set, Tag, TagClass, Types};
+
+    fn ldap_result(rc: u32, refs: Vec<String>) -> LdapResult {
+        LdapResult {
+            rc,
+            matched: String::new(),
+            text: "server says no".into(),
+            refs,
+            ctrls: vec![],
+        }
+    }
+
+    fn result_error(rc: u32) -> LdapError {
+        LdapError::LdapResult {
+            result: ldap_result(rc, vec![]),
+        }
+    }
+
+    fn octets(value: &str) -> Tag {
+        Tag::OctetString(OctetString {
+            id: Types::OctetString as u64,
+            class: TagClass::Universal,
+            inner: value.as_bytes().to_vec(),
+        })
+    }
+
+    /// Builds a SearchResultEntry (APPLICATION 4) message.
+    fn raw_entry(dn: &str, attrs: &[(&str, &[&str])]) -> ResultEntry {
+        let attrs = attrs
+            .iter()
+            .map(|(name, values)| {
+                Tag::Sequence(Sequence {
+                    id: Types::Sequence as u64,
+                    class: TagClass::Universal,
+                    inner: vec![
+                        octets(name),
+                        Tag::Set(Set {
+                            id: Types::Set as u64,
+                            class: TagClass::Universal,
+                            inner: values.iter().map(|v| octets(v)).collect(),
+                        }),
+                    ],
+                })
+            })
+            .collect();
+        let tag = Tag::Sequence(Sequence {
+            id: 4,
+            class: TagClass::Application,
+            inner: vec![
+                octets(dn),
+                Tag::Sequence(Sequence {
+                    id: Types::Sequence as u64,
+                    class: TagClass::Universal,
+                    inner: attrs,
+                }),
+            ],
+        });
+        ResultEntry::new(tag.into_structure())
+    }
+
+    /// Builds a SearchResultReference (APPLICATION 19) message.
+    fn raw_reference(uris: &[&str]) -> ResultEntry {
+        let tag = Tag::Sequence(Sequence {
+            id: 19,
+            class: TagClass::Application,
+            inner: uris.iter().map(|u| octets(u)).collect(),
+        });
+        ResultEntry::new(tag.into_structure())
+    }
+
+    fn read_acl(read: AttributeFilter) -> UserAcl {
+        let mut attr_acls = HashMap::new();
+        attr_acls.insert(
+            "user".to_string(),
+            ObjectAttributeAcl::new(read, AttributeFilter::allow_all()),
+        );
+        UserAcl::new(
+            "uid=alice,ou=users,dc=example,dc=com".to_string(),
+            PermissionBitmap::from_bit(0),
+            PermissionBitmap::EMPTY,
+            attr_acls,
+            vec![],
+        )
+    }
+
+    #[test]
+    fn test_readable_search_attrs_narrowed() {
+        let acl = read_acl(AttributeFilter::with_denied(["userPassword", "mail"]));
+        let base = "ou=users,dc=example,dc=com";
+
+        assert_eq!(
+            readable_search_attrs(&acl, base, "user", &["cn", "mail", "userPassword"]),
+            vec!["cn"]
+        );
+        assert_eq!(
+            readable_search_attrs(&acl, base, "user", &["userPassword"]),
+            vec!["1.1"]
+        );
+    }
+
+    #[test]
+    fn test_readable_search_attrs_wildcards_unchanged() {
+        let acl = read_acl(AttributeFilter::with_allowed(["cn"]));
+        let base = "ou=users,dc=example,dc=com";
+
+        assert!(readable_search_attrs(&acl, base, "user", &[]).is_empty());
+        assert_eq!(
+            readable_search_attrs(&acl, base, "user", &["*", "mail"]),
+            vec!["*", "mail"]
+        );
+    }
+
+    #[test]
+    fn test_has_children() {
+        let child = raw_entry("ou=a,dc=example,dc=com", &[]);
+        assert!(has_children(&[child], ldap_result(0, vec![])).unwrap());
+        assert!(!has_children(&[], ldap_result(0, vec![])).unwrap());
+
+        // More children than the size limit of 1.
+        let child = raw_entry("ou=a,dc=example,dc=com", &[]);
+        assert!(has_children(&[child], ldap_result(4, vec![])).unwrap());
+
+        let reference = raw_reference(&["ldap://other/ou=b,dc=example,dc=com"]);
+        assert!(has_children(&[reference], ldap_result(0, vec![])).unwrap());
+
+        assert!(matches!(
+            has_children(&[], ldap_result(32, vec![])),
+            Err(HeraclesError::LdapSearch(_))
+        ));
+    }
+
+    #[test]
+    fn test_rename_target() {
+        let (rdn, new_dn) = rename_target("uid=jdoe,ou=users,dc=example,dc=com", "jsmith").unwrap();
+        assert_eq!(rdn, RdnComponent::new("uid", "jsmith"));
+        assert_eq!(new_dn, "uid=jsmith,ou=users,dc=example,dc=com");
+
+        let (rdn, new_dn) = rename_target("cn=Old Name,dc=example,dc=com", "Doe, John").unwrap();
+        assert_eq!(rdn.to_string(), "cn=Doe\\, John");
+        assert_eq!(new_dn, "cn=Doe\\, John,dc=example,dc=com");
+
+        let (rdn, new_dn) =
+            rename_target("cn=John+uid=jdoe,ou=users,dc=example,dc=com", "Jane").unwrap();
+        assert_eq!(rdn.to_string(), "cn=Jane+uid=jdoe");
+        assert_eq!(new_dn, "cn=Jane+uid=jdoe,ou=users,dc=example,dc=com");
+
+        assert!(matches!(
+            rename_target("", "x"),
+            Err(HeraclesError::InvalidDN(_))
+        ));
+    }
+
+    #[test]
+    fn test_modify_dn_target() {
+        let dn = "uid=jdoe,ou=users,dc=example,dc=com";
+        assert_eq!(
+            modify_dn_target(dn, "uid=jsmith", None),
+            "uid=jsmith,ou=users,dc=example,dc=com"
+        );
+        assert_eq!(
+            modify_dn_target(dn, "uid=jdoe", Some("ou=former,dc=example,dc=com")),
+            "uid=jdoe,ou=former,dc=example,dc=com"
+        );
+        assert_eq!(modify_dn_target("dc=com", "dc=org", None), "dc=org");
+    }
+
+    fn paged_control(cookie: &[u8]) -> Control {
+        let raw = RawControl::from(PagedResults {
+            size: 0,
+            cookie: cookie.to_vec(),
+        });
+        Control(None, raw)
+    }
+
+    #[test]
+    fn test_paged_cookie() {
+        assert_eq!(paged_cookie(&[paged_control(b"next")]).unwrap(), b"next");
+        assert!(paged_cookie(&[paged_control(b"")]).unwrap().is_empty());
+        assert!(paged_cookie(&[]).unwrap().is_empty());
+
+        let garbage = Control(
+            None,
+            RawControl {
+                ctype: PAGED_RESULTS_OID.to_string(),
+                crit: false,
+                val: Some(vec![0x30, 0x03, 0x02]),
+            },
+        );
+        assert!(matches!(
+            paged_cookie(&[garbage]),
+            Err(HeraclesError::LdapSearch(_))
+        ));
+    }
+
+    /// Pages over the whole base DN with a page size of 10: a subtree of
+    /// `n` entries must come back as `ceil(n / 10)` pages (at least one),
+    /// every page but the last full.
+    #[tokio::test]
+    #[ignore = "requires a live LDAP server configured through LDAP_* variables"]
+    async fn test_search_paged_page_counts() {
+        let config = LdapConfig::from_env().unwrap();
+        let mut conn = LdapConnection::new(config).await.unwrap();
+        conn.bind().await.unwrap();
+
+        let pages: Vec<Vec<LdapEntry>> = conn
+            .search_paged("", Scope::Subtree, "(objectClass=*)", vec!["1.1"], 10)
+            .await
+            .unwrap()
+            .try_collect()
+            .await
+            .unwrap();
+        let total: usize = pages.iter().map(Vec::len).sum();
+
+        assert_eq!(pages.len(), total.div_ceil(10).max(1));
+        assert!(pages[..pages.len() - 1].iter().all(|p| p.len() == 10));
+
+        let all = conn
+            .search_all_paged("", Scope::Subtree, "(objectClass=*)", vec!["1.1"], 10)
+            .await
+            .unwrap();
+        assert_eq!(all.len(), total);
+    }
+
+    /// Streams the same entries, in the same order, as a buffered search.
+    #[tokio::test]
+    #[ignore = "requires a live LDAP server configured through LDAP_* variables"]
+    async fn test_search_stream_matches_search() {
+        let config = LdapConfig::from_env().unwrap();
+        let mut conn = LdapConnection::new(config).await.unwrap();
+        conn.bind().await.unwrap();
+
+        let buffered = conn
+            .search("", Scope::Subtree, "(objectClass=*)", vec!["*"])
+            .await
+            .unwrap();
+        let streamed: Vec<LdapEntry> = conn
+            .search_stream("", Scope::Subtree, "(objectClass=*)", vec!["*"])
+            .await
+            .unwrap()
+            .try_collect()
+            .await
+            .unwrap();
+
+        assert_eq!(streamed.len(), buffered.len());
+        for (s, b) in streamed.iter().zip(&buffered) {
+            assert_eq!(s.dn, b.dn);
+            assert_eq!(s.attributes, b.attributes);
+        }
+    }
+
+    /// Stand-in connection whose "socket" is `bound`: operations fail with
+    /// a dropped-connection error until a reconnect binds it again.
+    struct FlakyConn {
+        bound: bool,
+        as_user: bool,
+        reconnects: u32,
+        calls: u32,
+        reconnect_error: Option<HeraclesError>,
+    }
+
+    impl FlakyConn {
+        fn dropped() -> Self {
+            Self {
+                bound: false,
+                as_user: false,
+                reconnects: 0,
+                calls: 0,
+                reconnect_error: None,
+            }
+        }
+
+        async fn op(&mut self) -> Result<u32> {
+            self.calls += 1;
+            if self.bound {
+                Ok(42)
+            } else {
+                Err(HeraclesError::LdapConnection("connection lost".into()))
+            }
+        }
+    }
+
+    impl Reconnect for FlakyConn {
+        fn can_reconnect(&self) -> bool {
+            !self.as_user
+        }
+
+        async fn reconnect(&mut self) -> Result<()> {
+            self.reconnects += 1;
+            if let Some(e) = self.reconnect_error.take() {
+                return Err(e);
+            }
+            self.bound = true;
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_retry_transient_rebinds_dropped_connection() {
+        let mut conn = FlakyConn::dropped();
+        let result = retry_transient(&mut conn, 1, |c| Box::pin(c.op())).await;
+        assert_eq!(result.unwrap(), 42);
+        assert_eq!(conn.reconnects, 1);
+        assert_eq!(conn.calls, 2);
+        assert!(conn.bound);
+    }
+
+    #[tokio::test]
+    async fn test_retry_transient_respects_retry_count() {
+        let mut conn = FlakyConn::dropped();
+        let result = retry_transient(&mut conn, 0, |c| Box::pin(c.op())).await;
+        assert!(matches!(result, Err(HeraclesError::LdapConnection(_))));
+        assert_eq!((conn.calls, conn.reconnects), (1, 0));
+
+        // A connection that keeps dropping is given up on after the retries
+        let mut conn = FlakyConn::dropped();
+        let result = retry_transient(&mut conn, 2, |c| {
+            c.bound = false;
+            Box::pin(c.op())
+        })
+        .await;
+        assert!(matches!(result, Err(HeraclesError::LdapConnection(_))));
+        assert_eq!((conn.calls, conn.reconnects), (3, 2));
+    }
+
+    #[tokio::test]
+    async fn test_retry_transient_keeps_user_binds() {
+        // A connection bound as an end user is not rebound as the service
+        let mut conn = FlakyConn {
+            as_user: true,
+            ..FlakyConn::dropped()
+        };
+        let result = retry_transient(&mut conn, 3, |c| Box::pin(c.op())).await;
+        assert!(matches!(result, Err(HeraclesError::LdapConnection(_))));
+        assert_eq!((conn.calls, conn.reconnects), (1, 0));
+        assert!(!conn.bound);
+    }
+
+    #[tokio::test]
+    async fn test_can_reconnect_follows_bound_identity() {
+        let (uri, _) = test_server::spawn(0).await;
+        let mut conn = LdapConnection::new(LdapConfig::default().with_uris([uri]))
+            .await
+            .unwrap();
+        assert!(conn.can_reconnect());
+
+        conn.bind().await.unwrap();
+        assert!(conn.can_reconnect());
+
+        conn.bind_as("uid=jdoe,ou=users,dc=example,dc=com", "secret")
+            .await
+            .unwrap();
+        assert!(!conn.can_reconnect());
+    }
+
+    /// Returns an `ldap://` URI on a local port with nothing listening.
+    fn unreachable_uri() -> String {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        format!("ldap://{}", listener.local_addr().unwrap())
+    }
+
+    #[tokio::test]
+    async fn test_new_fails_over_in_order() {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let live = format!("ldap://{}", listener.local_addr().unwrap());
+        let config = LdapConfig::default().with_uris([unreachable_uri(), live.clone()]);
+
+        let conn = LdapConnection::new(config).await.unwrap();
+        assert_eq!(conn.info().uri, live);
+        assert!(!conn.info().encrypted);
+    }
+
+    #[tokio::test]
+    async fn test_new_returns_last_error_when_all_fail() {
+        let last = unreachable_uri();
+        let config = LdapConfig::default().with_uris([unreachable_uri(), last.clone()]);
+
+        let err = LdapConnection::new(config).await.err().unwrap();
+        assert!(matches!(err, HeraclesError::LdapConnection(ref m) if m.starts_with(&last)));
+    }
+
+    #[tokio::test]
+    async fn test_new_rejects_bad_ca_file_before_connecting() {
+        let mut config = LdapConfig::default().with_uris([unreachable_uri()]);
+        config.tls.ca_file = Some("/nonexistent/heracles/ca.pem".into());
+
+        let err = LdapConnection::new(config).await.err().unwrap();
+        assert!(matches!(err, HeraclesError::Configuration(ref m) if m.contains("ca.pem")));
+    }
+
+    #[tokio::test]
+    async fn test_retry_transient_skips_other_errors() {
+        let mut conn = FlakyConn::dropped();
+        let result: Result<u32> = retry_transient(&mut conn, 3, |c| {
+            c.calls += 1;
+            Box::pin(async { Err(HeraclesError::LdapSearch("rc=32".into())) })
+        })
+        .await;
+        assert!(matches!(result, Err(HeraclesError::LdapSearch(_))));
+        assert_eq!((conn.calls, conn.reconnects), (1, 0));
+
+        // Invalid credentials while rebinding end the retries
+        let mut conn = FlakyConn::dropped();
+        conn.reconnect_error = Some(HeraclesError::LdapBind("rc=49".into()));
+        let result = retry_transient(&mut conn, 3, |c| Box::pin(c.op())).await;
+        assert!(matches!(result, Err(HeraclesError::LdapBind(_))));
+        assert_eq!((conn.calls, conn.reconnects), (1, 1));
+    }
+
+    #[test]
+    fn test_map_transport_error() {
+        let lost = map_transport_error(LdapError::EndOfStream, |_| unreachable!());
+        assert!(matches!(lost, HeraclesError::LdapConnection(m) if m.contains("connection lost")));
+
+        let io = LdapError::Io {
+            source: std::io::Error::from(std::io::ErrorKind::ConnectionReset),
+        };
+        assert!(is_transport_error(&io));
+
+        let rc = map_transport_error(result_error(49), |e| HeraclesError::LdapBind(e.to_string()));
+        assert!(matches!(rc, HeraclesError::LdapBind(_)));
+    }
+
+    #[test]
+    fn test_whoami_authzid() {
+        let exop = |val: Option<&[u8]>| Exop {
+            name: None,
+            val: val.map(<[u8]>::to_vec),
+        };
+        assert_eq!(
+            whoami_authzid(exop(Some(b"dn:cn=admin,dc=example,dc=com"))).unwrap(),
+            "dn:cn=admin,dc=example,dc=com"
+        );
+        assert_eq!(whoami_authzid(exop(Some(b"u:jdoe"))).unwrap(), "u:jdoe");
+        assert_eq!(whoami_authzid(exop(None)).unwrap(), "");
+        assert!(matches!(
+            whoami_authzid(exop(Some(&[0xff, 0xfe]))),
+            Err(HeraclesError::LdapConnection(_))
+        ));
+    }
+
+    /// After binding with the configured service account, the server must
+    /// report that DN as the authorization identity.
+    #[tokio::test]
+    #[ignore = "requires a live LDAP server configured through LDAP_* variables"]
+    async fn test_whoami_matches_bind_dn() {
+        let config = LdapConfig::from_env().unwrap();
+        let bind_dn = config.bind_dn.clone();
+        let mut conn = LdapConnection::new(config).await.unwrap();
+        conn.bind().await.unwrap();
+
+        let authzid = conn.whoami().await.unwrap();
+        let dn = authzid.strip_prefix("dn:").expect("dn: authzId");
+        assert!(DistinguishedName::parse(dn)
+            .unwrap()
+            .eq_normalized(&DistinguishedName::parse(&bind_dn).unwrap()));
+    }
+
+    #[test]
+    fn test_map_sort_error() {
+        assert!(matches!(
+            map_sort_error(result_error(12)),
+            HeraclesError::LdapSearch(m) if m.contains("not supported")
+        ));
+        assert!(matches!(
+            map_sort_error(result_error(32)),
+            HeraclesError::LdapSearch(m) if !m.contains("not supported")
+        ));
+    }
+
+    #[test]
+    fn test_require_increment() {
+        assert!(require_increment(&[
+            "1.3.6.1.4.1.4203.1.5.1".to_string(),
+            INCREMENT_FEATURE_OID.to_string()
+        ])
+        .is_ok());
+        assert!(matches!(
+            require_increment(&[]),
+            Err(HeraclesError::LdapModify(m)) if m.contains(INCREMENT_FEATURE_OID)
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_increment_requires_server_feature() {
+        // The test server's root DSE advertises no features.
+        let (uri, _) = test_server::spawn(0).await;
+        let mut conn = LdapConnection::new(LdapConfig::default().with_uris([uri]))
+            .await
+            .unwrap();
+
+        let err = conn
+            .modify(
+                "cn=uidNext,dc=example,dc=com",
+                vec![LdapModification::increment("uidNumber", 1)],
+            )
+            .await
+            .unwrap_err();
+        assert!(matches!(err, HeraclesError::LdapModify(m) if m.contains(INCREMENT_FEATURE_OID)));
+    }
+
+    #[test]
+    fn test_absent_outcome() {
+        let dn = "uid=jdoe,ou=users,dc=example,dc=com";
+        assert!(absent_outcome(Ok(())).unwrap());
+        assert!(!absent_outcome(Err(map_add_error(dn, result_error(68)))).unwrap());
+        assert!(matches!(
+            absent_outcome(Err(map_add_error(dn, result_error(65)))),
+            Err(HeraclesError::LdapAdd(_))
+        ));
+        assert!(matches!(
+            absent_outcome(Err(map_add_error(dn, result_error(13)))),
+            Err(HeraclesError::SecurityRequired(_))
+        ));
+    }
+
+    #[test]
+    fn test_map_modify_dn_error() {
+        let dn = "uid=jdoe,ou=users,dc=example,dc=com";
+        let target = "uid=jsmith,ou=users,dc=example,dc=com";
+        assert!(matches!(
+            map_modify_dn_error(dn, target, result_error(32)),
+            HeraclesError::LdapNotFound(d) if d == dn
+        ));
+        assert!(matches!(
+            map_modify_dn_error(dn, target, result_error(68)),
+            HeraclesError::LdapAlreadyExists(d) if d == target
+        ));
+        assert!(matches!(
+            map_modify_dn_error(dn, target, result_error(66)),
+            HeraclesError::LdapModify(_)
+        ));
+        assert!(matches!(
+            map_modify_dn_error(dn, target, result_error(13)),
+            HeraclesError::SecurityRequired(_)
+        ));
+    }
+
+    #[test]
+    fn test_set_attribute_modification() {
+        let modification = set_attribute_modification(
+            "mail",
+            vec!["a@example.com".into(), "b@example.com".into()],
+        );
+        match modification {
+            LdapModification::Replace { attr, values } => {
+                assert_eq!(attr, "mail");
+                assert_eq!(values, vec!["a@example.com", "b@example.com"]);
+            }
+            other => panic!("expected Replace, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_clear_attribute_modification() {
+        match clear_attribute_modification("mail") {
+            LdapModification::Delete { attr, values } => {
+                assert_eq!(attr, "mail");
+                assert!(values.is_empty());
+            }
+            other => panic!("expected Delete, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_clear_outcome_idempotent() {
+        let dn = "uid=john,dc=example,dc=com";
+        assert!(clear_outcome(dn, ldap_result(0, vec![])).is_ok());
+        assert!(clear_outcome(dn, ldap_result(16, vec![])).is_ok());
+        assert!(matches!(
+            clear_outcome(dn, ldap_result(32, vec![])),
+            Err(HeraclesError::LdapNotFound(_))
+        ));
+        assert!(matches!(
+            clear_outcome(dn, ldap_result(50, vec![])),
+            Err(HeraclesError::LdapModify(_))
+        ));
+    }
+
+    fn with_ppolicy(mut result: LdapResult, value: &[u8]) -> LdapResult {
+        result.ctrls.push(ldap3::controls::Control(
+            None,
+            ldap3::controls::RawControl {
+                ctype: ppolicy::PPOLICY_OID.into(),
+                crit: false,
+                val: Some(value.to_vec()),
+            },
+        ));
+        result
+    }
+
+    #[test]
+    fn test_auth_outcome_expiry_warning() {
+        // SEQUENCE { [0] { [0] 172800 } }
+        let value = [0x30, 0x07, 0xa0, 0x05, 0x80, 0x03, 0x02, 0xa3, 0x00];
+        let outcome = auth_outcome(with_ppolicy(ldap_result(0, vec![]), &value)).unwrap();
+
+        assert!(outcome.authenticated);
+        assert_eq!(outcome.time_before_expiration(), Some(172_800));
+        assert_eq!(outcome.grace_auths_remaining(), None);
+    }
+
+    #[test]
+    fn test_auth_outcome_grace_logins() {
+        // SEQUENCE { [0] { [1] 2 }, [1] passwordExpired }
+        let value = [0x30, 0x08, 0xa0, 0x03, 0x81, 0x01, 0x02, 0x81, 0x01, 0x00];
+        let outcome = auth_outcome(with_ppolicy(ldap_result(0, vec![]), &value)).unwrap();
+
+        assert!(outcome.authenticated);
+        assert_eq!(outcome.grace_auths_remaining(), Some(2));
+        assert_eq!(
+            outcome.policy.unwrap().error,
+            Some(ppolicy::PasswordPolicyError::PasswordExpired)
+        );
+    }
+
+    #[test]
+    fn test_auth_outcome_rejected() {
+        // SEQUENCE { [1] accountLocked }
+        let value = [0x30, 0x03, 0x81, 0x01, 0x01];
+        let outcome = auth_outcome(with_ppolicy(ldap_result(49, vec![]), &value)).unwrap();
+
+        assert!(!outcome.authenticated);
+        assert_eq!(
+            outcome.policy.unwrap().error,
+            Some(ppolicy::PasswordPolicyError::AccountLocked)
+        );
+
+        let plain = auth_outcome(ldap_result(49, vec![])).unwrap();
+        assert!(!plain.authenticated);
+        assert!(plain.policy.is_none());
+
+        assert!(matches!(
+            auth_outcome(ldap_result(52, vec![])),
+            Err(HeraclesError::LdapBind(_))
+        ));
+    }
+
+    #[test]
+    fn test_auth_outcome_ignores_malformed_control() {
+        let garbage = [0x04, 0x02];
+        let outcome = auth_outcome(with_ppolicy(ldap_result(0, vec![]), &garbage)).unwrap();
+        assert!(outcome.authenticated);
+        assert!(outcome.policy.is_none());
+
+        let outcome = auth_outcome(with_ppolicy(ldap_result(49, vec![]), &garbage)).unwrap();
+        assert!(!outcome.authenticated);
+        assert!(outcome.policy.is_none());
+
+        // The result code wins over a malformed control
+        assert!(matches!(
+            auth_outcome(with_ppolicy(ldap_result(52, vec![]), &garbage)),
+            Err(HeraclesError::LdapBind(_))
+        ));
+    }
+
+    #[test]
+    fn test_uuid_filter() {
+        assert_eq!(
+            uuid_filter("5ff7a1a4-2c7e-103b-8f3c-6d0e2f1b9a10"),
+            "(entryUUID=5ff7a1a4-2c7e-103b-8f3c-6d0e2f1b9a10)"
+        );
+        assert_eq!(
+            uuid_filter("x*)(uid=*"),
+            "(entryUUID=x\\2a\\29\\28uid=\\2a)"
+        );
+    }
+
+    #[test]
+    fn test_dn_filter() {
+        let dns = [DistinguishedName::parse("uid=a\\,b,dc=example,dc=com").unwrap()];
+        assert_eq!(
+            dn_filter(&dns),
+            "(|(entryDN=uid=a\\5c,b,dc=example,dc=com)(distinguishedName=uid=a\\5c,b,dc=example,dc=com))"
+        );
+    }
+
+    #[test]
+    fn test_order_by_dn() {
+        let dns: Vec<DistinguishedName> = [
+            "uid=carol,ou=users,dc=example,dc=com",
+            "uid=ghost,ou=users,dc=example,dc=com",
+            "UID=Alice,ou=users,dc=example,dc=com",
+            "uid=carol,ou=users,dc=example,dc=com",
+        ]
+        .iter()
+        .map(|dn| DistinguishedName::parse(dn).unwrap())
+        .collect();
+        let entries = vec![
+            LdapEntry::new("uid=alice,ou=users,dc=example,dc=com"),
+            LdapEntry::new("uid=carol,ou=users,dc=example,dc=com"),
+        ];
+
+        let dns_found: Vec<Option<String>> = order_by_dn(&dns, entries)
+            .into_iter()
+            .map(|e| e.map(|e| e.dn))
+            .collect();
+        assert_eq!(
+            dns_found,
+            vec![
+                Some("uid=carol,ou=users,dc=example,dc=com".to_string()),
+                None,
+                Some("uid=alice,ou=users,dc=example,dc=com".to_string()),
+                Some("uid=carol,ou=users,dc=example,dc=com".to_string()),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_get_many_falls_back_to_base_searches() {
+        // The test server matches nothing, like one without entryDN.
+        let (uri, searches) = test_server::spawn(0).await;
+        let mut conn = LdapConnection::new(LdapConfig::default().with_uris([uri]))
+            .await
+            .unwrap();
+
+        let dns = ["uid=b,dc=example,dc=com", "uid=a,dc=example,dc=com"];
+        let entries = conn.get_many(&dns, vec!["cn"]).await.unwrap();
+        assert_eq!(entries.len(), 2);
+        assert!(entries.iter().all(Option::is_none));
+
+        let seen: Vec<(String, u64)> = searches
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|s| (s.base.clone(), s.scope))
+            .collect();
+        assert_eq!(
+            seen,
+            vec![
+                ("dc=example,dc=com".to_string(), 2),
+                (dns[0].to_string(), 0),
+                (dns[1].to_string(), 0),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_base_entry_outcome() {
+        assert!(base_entry_outcome(vec![], ldap_result(32, vec![]))
+            .unwrap()
+            .is_none());
+        assert!(base_entry_outcome(vec![], ldap_result(0, vec![]))
+            .unwrap()
+            .is_none());
+        assert!(matches!(
+            base_entry_outcome(vec![], ldap_result(50, vec![])),
+            Err(HeraclesError::LdapSearch(_))
+        ));
+    }
+
+    #[test]
+    fn test_single_uuid_match() {
+        let uuid = "5ff7a1a4-2c7e-103b-8f3c-6d0e2f1b9a10";
+        assert!(single_uuid_match(vec![], uuid).unwrap().is_none());
+
+        let found = single_uuid_match(vec![LdapEntry::new("uid=a,dc=example,dc=com")], uuid)
+            .unwrap()
+            .unwrap();
+        assert_eq!(found.dn, "uid=a,dc=example,dc=com");
+
+        let duplicates = vec![
+            LdapEntry::new("uid=a,dc=example,dc=com"),
+            LdapEntry::new("uid=b,dc=example,dc=com"),
+        ];
+        match single_uuid_match(duplicates, uuid) {
+            Err(HeraclesError::LdapSearch(msg)) => assert!(msg.contains("2 entries")),
+            other => panic!("expected LdapSearch error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_referral_target() {
+        let config = LdapConfig {
+            referral_hosts: vec!["dc2.example.com".into()],
+            ..LdapConfig::default().with_uris(["ldap://dc1.example.com"])
+        };
+
+        let err = referral_target(&config, "ldap://evil.example.net").unwrap_err();
+        assert!(
+            matches!(err, HeraclesError::LdapSearch(ref m) if m.contains("untrusted") && m.contains("evil.example.net"))
+        );
+
+        let (target, service_bind) = referral_target(&config, "ldaps://dc2.example.com").unwrap();
+        assert_eq!(target.uri, "ldaps://dc2.example.com");
+        assert!(service_bind);
+
+        let (_, service_bind) = referral_target(&config, "ldap://dc1.example.com").unwrap();
+        assert!(!service_bind);
+        let starttls = LdapConfig {
+            use_tls: true,
+            ..config
+        };
+        let (_, service_bind) = referral_target(&starttls, "ldap://dc1.example.com").unwrap();
+        assert!(service_bind);
+    }
+
+    #[test]
+    fn test_split_entries_and_referrals() {
+        let results = vec![
+            raw_entry("uid=john,ou=users,dc=example,dc=com", &[("uid", &["john"])]),
+            raw_reference(&["ldap://replica.example.com/ou=people,dc=example,dc=com"]),
+        ];
+
+        let (entries, referrals) = split_search_results(results, ldap_result(0, vec![])).unwrap();
+
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].dn, "uid=john,ou=users,dc=example,dc=com");
+        assert_eq!(entries[0].get_first("uid"), Some("john"));
+        assert_eq!(
+            referrals,
+            vec!["ldap://replica.example.com/ou=people,dc=example,dc=com"]
+        );
+    }
+
+    #[test]
+    fn test_partition_skips_references() {
+        let results = vec![
+            raw_reference(&["ldap://replica.example.com/ou=people,dc=example,dc=com"]),
+            raw_entry("uid=john,ou=users,dc=example,dc=com", &[("uid", &["john"])]),
+            raw_reference(&["ldap://other.example.com/ou=groups,dc=example,dc=com"]),
+        ];
+
+        let (entries, referrals) = partition_results(results);
+
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].dn, "uid=john,ou=users,dc=example,dc=com");
+        assert_eq!(referrals.len(), 2);
+    }
+
+    #[test]
+    fn test_split_referral_result() {
+        let refs = vec!["ldap://other.example.com/dc=example,dc=com".to_string()];
+        let (entries, referrals) =
+            split_search_results(vec![], ldap_result(10, refs.clone())).unwrap();
+
+        assert!(entries.is_empty());
+        assert_eq!(referrals, refs);
+    }
+
+    #[test]
+    fn test_split_search_error() {
+        let err = split_search_results(vec![], ldap_result(32, vec![])).unwrap_err();
+        assert!(matches!(err, HeraclesError::LdapSearch(_)));
+    }
+
+    #[test]
+    fn test_map_stronger_auth_required() {
+        let err = map_result_error(result_error(8), |e| HeraclesError::LdapBind(e.to_string()));
+        match err {
+            HeraclesError::SecurityRequired(msg) => assert!(msg.contains("TLS")),
+            other => panic!("unexpected error: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_map_confidentiality_required() {
+        let err = map_result_error(result_error(13), |e| {
+            HeraclesError::LdapModify(e.to_string())
+        });
+        match err {
+            HeraclesError::SecurityRequired(msg) => assert!(msg.contains("TLS")),
+            other => panic!("unexpected error: {:?}", other),
+        }
+    }
+
+    const BASE_DN: &str = "dc=example,dc=com";
+
+    #[test]
+    fn test_resolve_base_relative() {
+        assert_eq!(
+            resolve_relative("ou=users", BASE_DN).unwrap(),
+            "ou=users,dc=example,dc=com"
+        );
+        assert_eq!(resolve_relative("", BASE_DN).unwrap(), BASE_DN);
+        assert_eq!(resolve_base("", BASE_DN).unwrap(), BASE_DN);
+        assert!(matches!(
+            resolve_relative("users", BASE_DN),
+            Err(HeraclesError::InvalidDN(_))
+        ));
+    }
+
+    #[test]
+    fn test_resolve_base_absolute() {
+        assert_eq!(
+            resolve_base("ou=users,dc=example,dc=com", BASE_DN).unwrap(),
+            "ou=users,dc=example,dc=com"
+        );
+        assert_eq!(
+            resolve_base("OU=Users,DC=Example,DC=com", BASE_DN).unwrap(),
+            "OU=Users,DC=Example,DC=com"
+        );
+        assert_eq!(resolve_base(BASE_DN, BASE_DN).unwrap(), BASE_DN);
+    }
+
+    #[test]
+    fn test_resolve_base_outside_base_dn() {
+        assert_eq!(resolve_base("cn=schema", BASE_DN).unwrap(), "cn=schema");
+        assert_eq!(resolve_base("cn=Monitor", BASE_DN).unwrap(), "cn=Monitor");
+        assert_eq!(
+            resolve_base("ou=x,dc=other,dc=org", BASE_DN).unwrap(),
+            "ou=x,dc=other,dc=org"
+        );
+    }
+
+    #[test]
+    fn test_resolve_base_malformed() {
+        assert!(matches!(
+            resolve_base("users", BASE_DN),
+            Err(HeraclesError::InvalidDN(_))
+        ));
+        assert!(resolve_base("ou=,dc=example,dc=com", BASE_DN).is_err());
+        assert!(resolve_base("=users", BASE_DN).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_with_timeout_expires() {
+        let result: Result<()> = with_timeout(
+            Duration::from_millis(20),
+            "bind",
+            std::future::pending::<Result<()>>(),
+        )
+        .await;
+        assert!(matches!(result, Err(HeraclesError::Timeout(_))));
+    }
+
+    #[tokio::test]
+    async fn test_with_timeout_passes_result() {
+        let result = with_timeout(Duration::from_secs(1), "bind", async { Ok(7) }).await;
+        assert_eq!(result.unwrap(), 7);
+    }
+
+    #[tokio::test]
+    async fn test_search_sends_configured_limits() {
+        let (uri, searches) = test_server::spawn(0).await;
+        let config = LdapConfig {
+            size_limit: 50,
+            time_limit: 5,
+            ..LdapConfig::default().with_uris([uri])
+        };
+        let mut conn = LdapConnection::new(config).await.unwrap();
+
+        conn.search("", Scope::Subtree, "(objectClass=*)", vec!["cn"])
+            .await
+            .unwrap();
+        let builder = SearchBuilder::new("").size_limit(3);
+        conn.search_with(&builder).await.unwrap();
+
+        let limits: Vec<(u64, u64)> = searches
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|s| (s.size_limit, s.time_limit))
+            .collect();
+        assert_eq!(limits, vec![(50, 5), (3, 5)]);
+    }
+
+    #[tokio::test]
+    async fn test_search_with_forwards_builder() {
+        let (uri, searches) = test_server::spawn(0).await;
+        let mut conn = LdapConnection::new(LdapConfig::default().with_uris([uri]))
+            .await
+            .unwrap();
+
+        let builder = SearchBuilder::new("ou=users,dc=example,dc=com")
+            .scope(SearchScope::OneLevel)
+            .filter("(uid=jdoe)")
+            .attributes(vec!["cn", "mail"])
+            .size_limit(7);
+        conn.search_with(&builder).await.unwrap();
+        conn.search_with(&builder.sort_by("sn", false))
+            .await
+            .unwrap();
+
+        let seen = searches.lock().unwrap();
+        let expected = test_server::SeenSearch {
+            base: "ou=users,dc=example,dc=com".into(),
+            scope: 1,
+            size_limit: 7,
+            time_limit: 0,
+            filter: "(uid=jdoe)".into(),
+            attrs: vec!["cn".into(), "mail".into()],
+            controls: vec![],
+        };
+        assert_eq!(seen[0], expected);
+        assert_eq!(
+            seen[1],
+            test_server::SeenSearch {
+                controls: vec![sort::SORT_REQUEST_OID.into()],
+                ..expected
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn test_search_size_limit_exceeded() {
+        let (uri, _) = test_server::spawn(4).await;
+        let mut conn = LdapConnection::new(LdapConfig::default().with_uris([uri]))
+            .await
+            .unwrap();
+
+        let err = conn
+            .search_with(&SearchBuilder::new("").size_limit(1))
+            .await
+            .unwrap_err();
+        assert!(matches!(err, HeraclesError::SizeLimitExceeded(_)));
+    }
+
+    #[test]
+    fn test_search_options_prefers_builder_size_limit() {
+        let config = LdapConfig {
+            size_limit: 100,
+            time_limit: 30,
+            ..Default::default()
+        };
+        let options = search_options(&config, None);
+        assert_eq!((options.sizelimit, options.timelimit), (100, 30));
+        let options = search_options(&config, Some(usize::MAX));
+        assert_eq!((options.sizelimit, options.timelimit), (i32::MAX, 30));
+    }
+
+    #[test]
+    fn test_map_other_codes_use_fallback() {
+        let err = map_result_error(result_error(49), |e| HeraclesError::LdapBind(e.to_string()));
+        assert!(matches!(err, HeraclesError::LdapBind(_)));
+    }
+}
@@ -1,15 +1,27 @@
 //! Distinguished Name (DN) utilities.
 
 use crate::errors::{HeraclesError, Result};
+use crate::ldap::operations::SearchScope;
+use caseless::Caseless;
 use std::fmt;
+use std::hash::{Hash, Hasher};
+use unicode_normalization::UnicodeNormalization;
 
 /// Represents a parsed Distinguished Name component.
+///
+/// A multi-valued RDN such as `cn=John+uid=jdoe` keeps its first pair in
+/// `attr_type`/`attr_value` and the rest in `additional`. [`parse`](Self::parse)
+/// sorts the pairs by attribute type, so equal RDNs compare equal whatever
+/// order they were written in; build multi-valued RDNs with
+/// [`with_pair`](Self::with_pair) to keep that ordering.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct RdnComponent {
     /// Attribute type (e.g., "uid", "cn", "ou")
     pub attr_type: String,
     /// Attribute value
     pub attr_value: String,
+    /// Further `(type, value)` pairs of a multi-valued RDN.
+    pub additional: Vec<(String, String)>,
 }
 
 impl RdnComponent {
@@ -18,34 +30,126 @@ impl RdnComponent {
         Self {
             attr_type: attr_type.into(),
             attr_value: attr_value.into(),
+            additional: Vec::new(),
         }
     }
 
-    /// Parses an RDN component from string (e.g., "uid=test").
+    /// Parses an RDN component from string (e.g., "uid=test" or
+    /// "cn=John+uid=jdoe").
     pub fn parse(s: &str) -> Result<Self> {
-        let parts: Vec<&str> = s.splitn(2, '=').collect();
-        if parts.len() != 2 {
-            return Err(HeraclesError::Schema(format!("Invalid RDN: {}", s)));
+        let pairs = split_unescaped(s, '+')
+            .into_iter()
+            .map(|ava| {
+                let parts: Vec<&str> = ava.splitn(2, '=').collect();
+                if parts.len() != 2 {
+                    return Err(HeraclesError::Schema(format!("Invalid RDN: {}", s)));
+                }
+                Ok((
+                    parts[0].trim().to_string(),
+                    unescape_dn_value(parts[1].trim()),
+                ))
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(Self::from_sorted(pairs))
+    }
+
+    /// Adds a `(type, value)` pair, making this a multi-valued RDN.
+    ///
+    /// Pairs are re-sorted by attribute type, as [`parse`](Self::parse) does.
+    pub fn with_pair(self, attr_type: impl Into<String>, attr_value: impl Into<String>) -> Self {
+        let mut pairs: Vec<(String, String)> = self
+            .pairs()
+            .map(|(t, v)| (t.to_string(), v.to_string()))
+            .collect();
+        pairs.push((attr_type.into(), attr_value.into()));
+        Self::from_sorted(pairs)
+    }
+
+    /// Returns the value paired with `attr_type` (case-insensitive), looking
+    /// at every pair of a multi-valued RDN.
+    pub fn value_of(&self, attr_type: &str) -> Option<&str> {
+        self.pairs()
+            .find(|(t, _)| t.eq_ignore_ascii_case(attr_type))
+            .map(|(_, value)| value)
+    }
+
+    /// Sorts `pairs` by attribute type and builds a component from them.
+    fn from_sorted(mut pairs: Vec<(String, String)>) -> Self {
+        pairs.sort_by_key(|(attr_type, _)| attr_type.to_ascii_lowercase());
+        Self::from_pairs(pairs)
+    }
+
+    /// Builds a component from at least one `(type, value)` pair.
+    fn from_pairs(pairs: Vec<(String, String)>) -> Self {
+        let mut pairs = pairs.into_iter();
+        let (attr_type, attr_value) = pairs.next().unwrap_or_default();
+        Self {
+            attr_type,
+            attr_value,
+            additional: pairs.collect(),
         }
-        Ok(Self {
-            attr_type: parts[0].trim().to_string(),
-            attr_value: unescape_dn_value(parts[1].trim()),
-        })
+    }
+
+    /// All `(type, value)` pairs, starting with `attr_type`/`attr_value`.
+    pub fn pairs(&self) -> impl Iterator<Item = (&str, &str)> {
+        std::iter::once((self.attr_type.as_str(), self.attr_value.as_str())).chain(
+            self.additional
+                .iter()
+                .map(|(attr_type, value)| (attr_type.as_str(), value.as_str())),
+        )
+    }
+
+    /// Returns true if the RDN holds more than one `type=value` pair.
+    pub fn is_multi_valued(&self) -> bool {
+        !self.additional.is_empty()
+    }
+
+    /// Pairs with lowercase types and normalized values, sorted by type.
+    fn normalized_pairs(&self) -> Vec<(String, String)> {
+        let mut pairs: Vec<(String, String)> = self
+            .pairs()
+            .map(|(attr_type, value)| (attr_type.to_ascii_lowercase(), normalize_value(value)))
+            .collect();
+        pairs.sort();
+        pairs
     }
 }
 
+impl RdnComponent {
+    /// Returns the value normalized for caseIgnoreMatch-style comparison.
+    ///
+    /// Leading/trailing whitespace is trimmed and the value is Unicode
+    /// case-folded and NFC-normalized, so `José`, `JOSÉ` and a decomposed
+    /// `Jose\u{301}` all compare equal.
+    pub fn normalized_value(&self) -> String {
+        normalize_value(&self.attr_value)
+    }
+}
+
+/// caseIgnoreMatch-style normalization; see [`RdnComponent::normalized_value`].
+fn normalize_value(value: &str) -> String {
+    value.trim().nfd().default_case_fold().nfc().collect()
+}
+
 impl fmt::Display for RdnComponent {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(
-            f,
-            "{}={}",
-            self.attr_type,
-            escape_dn_value(&self.attr_value)
-        )
+        for (i, (attr_type, value)) in self.pairs().enumerate() {
+            if i > 0 {
+                write!(f, "+")?;
+            }
+            write!(f, "{}={}", attr_type, escape_dn_value(value))?;
+        }
+        Ok(())
     }
 }
 
 /// Represents a full Distinguished Name.
+///
+/// `==` compares components exactly; use [`eq_normalized`](Self::eq_normalized)
+/// for DN-aware equality. `Hash` is computed on the normalized form, so it
+/// agrees with both, and [`normalized`](Self::normalized) DNs can key a
+/// `HashMap` by entry identity.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct DistinguishedName {
     /// RDN components from left to right (most specific to least specific).
@@ -64,6 +168,10 @@ impl DistinguishedName {
     }
 
     /// Parses a DN from string.
+    ///
+    /// Parsing is lenient: empty components are skipped and malformed
+    /// escapes are kept literally. Use [`try_parse`](Self::try_parse) to
+    /// reject such input.
     pub fn parse(dn: &str) -> Result<Self> {
         if dn.is_empty() {
             return Ok(Self::empty());
@@ -78,6 +186,48 @@ impl DistinguishedName {
         })
     }
 
+    /// Parses a DN, rejecting malformed input with `HeraclesError::InvalidDN`.
+    ///
+    /// Unlike [`parse`](Self::parse), empty components (`uid=a,,dc=x`, a
+    /// trailing comma), components without `=`, empty or invalid attribute
+    /// types and unbalanced escapes (a lone trailing `\`, `\4` without a
+    /// second hex digit) are errors naming the offending part. The empty
+    /// string is the valid root DN.
+    pub fn try_parse(dn: &str) -> Result<Self> {
+        if dn.is_empty() {
+            return Ok(Self::empty());
+        }
+
+        let invalid = |what: &str, part: &str| {
+            HeraclesError::InvalidDN(format!("{} '{}' in DN '{}'", what, part, dn))
+        };
+
+        let mut components = Vec::new();
+        for rdn in split_unescaped(dn, ',') {
+            if rdn.trim().is_empty() {
+                return Err(invalid("empty RDN", rdn));
+            }
+            for ava in split_unescaped(rdn, '+') {
+                let Some((attr_type, value)) = ava.split_once('=') else {
+                    return Err(invalid("missing '=' in", ava.trim()));
+                };
+                let attr_type = attr_type.trim();
+                if attr_type.is_empty() {
+                    return Err(invalid("empty attribute type in", ava.trim()));
+                }
+                if !is_valid_attr_type(attr_type) {
+                    return Err(invalid("invalid attribute type", attr_type));
+                }
+                if !has_balanced_escapes(value) {
+                    return Err(invalid("unbalanced escape in", ava.trim()));
+                }
+            }
+            components.push(RdnComponent::parse(rdn.trim())?);
+        }
+
+        Ok(Self { components })
+    }
+
     /// Returns the RDN (first component).
     pub fn rdn(&self) -> Option<&RdnComponent> {
         self.components.first()
@@ -94,6 +244,16 @@ impl DistinguishedName {
         }
     }
 
+    /// Splits into the leaf RDN and the parent (superior) DN.
+    ///
+    /// The two halves map onto the `new_rdn`/`new_superior` arguments of a
+    /// modify DN operation. Returns `None` for DNs with fewer than two
+    /// components, which have no parent.
+    pub fn split_rdn(&self) -> Option<(RdnComponent, DistinguishedName)> {
+        let parent = self.parent()?;
+        Some((self.components[0].clone(), parent))
+    }
+
     /// Returns the RDN value (e.g., "test" for "uid=test,ou=users,dc=example,dc=com").
     pub fn rdn_value(&self) -> Option<&str> {
         self.rdn().map(|r| r.attr_value.as_str())
@@ -114,6 +274,62 @@ impl DistinguishedName {
         self.components[offset..] == base.components
     }
 
+    /// Checks if this DN is a strict ancestor of `other` (`other` is somewhere below it).
+    ///
+    /// Components are compared case-insensitively.
+    pub fn is_ancestor_of(&self, other: &DistinguishedName) -> bool {
+        other.components.len() > self.components.len() && other.ends_with_normalized(self)
+    }
+
+    /// Checks if this DN is an immediate child of `parent` (exactly one level below).
+    ///
+    /// Components are compared case-insensitively.
+    pub fn is_child_of(&self, parent: &DistinguishedName) -> bool {
+        self.components.len() == parent.components.len() + 1 && self.ends_with_normalized(parent)
+    }
+
+    /// Returns the leading components of this DN below `base`.
+    ///
+    /// `uid=test,ou=users,dc=example,dc=com` relative to `dc=example,dc=com`
+    /// is `uid=test,ou=users`. The base itself yields an empty DN, and a DN
+    /// outside `base` yields `None`. Components are compared
+    /// case-insensitively.
+    pub fn relative_to(&self, base: &DistinguishedName) -> Option<DistinguishedName> {
+        if !self.ends_with_normalized(base) {
+            return None;
+        }
+        let depth = self.components.len() - base.components.len();
+        Some(Self::from_components(self.components[..depth].to_vec()))
+    }
+
+    /// Checks if this DN and `other` are distinct entries sharing the same parent.
+    ///
+    /// Components are compared case-insensitively.
+    pub fn is_sibling_of(&self, other: &DistinguishedName) -> bool {
+        if self.components.is_empty() || self.components.len() != other.components.len() {
+            return false;
+        }
+
+        !rdn_eq_normalized(&self.components[0], &other.components[0])
+            && self.components[1..]
+                .iter()
+                .zip(&other.components[1..])
+                .all(|(a, b)| rdn_eq_normalized(a, b))
+    }
+
+    /// Checks if the trailing components of this DN match `base` case-insensitively.
+    pub(crate) fn ends_with_normalized(&self, base: &DistinguishedName) -> bool {
+        if base.components.len() > self.components.len() {
+            return false;
+        }
+
+        let offset = self.components.len() - base.components.len();
+        self.components[offset..]
+            .iter()
+            .zip(&base.components)
+            .all(|(a, b)| rdn_eq_normalized(a, b))
+    }
+
     /// Appends another DN (base) to this DN.
     pub fn append(&self, base: &DistinguishedName) -> Self {
         let mut components = self.components.clone();
@@ -131,6 +347,91 @@ impl DistinguishedName {
         self.components.is_empty()
     }
 
+    /// Checks whether both DNs name the same entry.
+    ///
+    /// Attribute types compare case-insensitively and values use
+    /// [`RdnComponent::normalized_value`] (trimmed, case-folded, NFC), so
+    /// `UID=Test, DC=Example` equals `uid=test,dc=example`.
+    pub fn eq_normalized(&self, other: &DistinguishedName) -> bool {
+        self.components.len() == other.components.len()
+            && self
+                .components
+                .iter()
+                .zip(&other.components)
+                .all(|(a, b)| rdn_eq_normalized(a, b))
+    }
+
+    /// Returns a copy with lowercase attribute types and normalized values.
+    ///
+    /// Two DNs are [`eq_normalized`](Self::eq_normalized) exactly when their
+    /// normalized forms are `==`.
+    pub fn normalized(&self) -> Self {
+        Self {
+            components: self
+                .components
+                .iter()
+                .map(|c| RdnComponent::from_pairs(c.normalized_pairs()))
+                .collect(),
+        }
+    }
+
+    /// Formats the DN as an RFC 4514 string that re-parses to an equal DN.
+    ///
+    /// Attribute types are lowercased and the pairs of a multi-valued RDN
+    /// are sorted by type. Values keep their case; only the characters
+    /// RFC 4514 requires are escaped (`"+,;<>\` as `\c`, a leading `#` as
+    /// `\#`, leading/trailing spaces, NUL and other control characters as
+    /// `\XX`), so `=` is left as-is. Unlike [`Display`](fmt::Display), the
+    /// output survives [`parse`](Self::parse) for any value.
+    ///
+    /// Round-tripping is semantic, not byte-identical: `cn=a\2cb` becomes
+    /// `cn=a\,b`, and `UID=x` becomes `uid=x`.
+    pub fn to_rfc4514(&self) -> String {
+        let rdns: Vec<String> = self
+            .components
+            .iter()
+            .map(|c| {
+                let mut pairs: Vec<(String, &str)> = c
+                    .pairs()
+                    .map(|(attr_type, value)| (attr_type.to_ascii_lowercase(), value))
+                    .collect();
+                pairs.sort_by(|a, b| a.0.cmp(&b.0));
+                pairs
+                    .iter()
+                    .map(|(attr_type, value)| format!("{}={}", attr_type, escape_rfc4514(value)))
+                    .collect::<Vec<_>>()
+                    .join("+")
+            })
+            .collect();
+        rdns.join(",")
+    }
+
+    /// Builds an RFC 4516 LDAP URL searching from this DN.
+    ///
+    /// Produces `ldap://host/base??scope?filter`, with the DN (in
+    /// [`to_rfc4514`](Self::to_rfc4514) form) and filter percent-encoded.
+    /// `host` is `host[:port]`, or a full `ldap://`/`ldaps://` URI whose
+    /// scheme is then kept. The filter part is omitted when `filter` is
+    /// `None`.
+    pub fn to_ldap_url(&self, host: &str, scope: SearchScope, filter: Option<&str>) -> String {
+        let server = host.trim_end_matches('/');
+        let mut url = if server.starts_with("ldap://") || server.starts_with("ldaps://") {
+            server.to_string()
+        } else {
+            format!("ldap://{}", server)
+        };
+
+        url.push('/');
+        url.push_str(&percent_encode_url(&self.to_rfc4514()));
+        url.push_str("??");
+        url.push_str(scope.url_token());
+        if let Some(filter) = filter {
+            url.push('?');
+            url.push_str(&percent_encode_url(filter));
+        }
+        url
+    }
+
     /// Converts to canonical lowercase form.
     pub fn to_canonical(&self) -> Self {
         Self {
@@ -140,12 +441,26 @@ impl DistinguishedName {
                 .map(|c| RdnComponent {
                     attr_type: c.attr_type.to_lowercase(),
                     attr_value: c.attr_value.clone(),
+                    additional: c
+                        .additional
+                        .iter()
+                        .map(|(attr_type, value)| (attr_type.to_lowercase(), value.clone()))
+                        .collect(),
                 })
                 .collect(),
         }
     }
 }
 
+impl Hash for DistinguishedName {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.components.len().hash(state);
+        for component in &self.components {
+            component.normalized_pairs().hash(state);
+        }
+    }
+}
+
 impl fmt::Display for DistinguishedName {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         let parts: Vec<String> = self.components.iter().map(|c| c.to_string()).collect();
@@ -153,6 +468,8 @@ impl fmt::Display for DistinguishedName {
     }
 }
 
+/// Lossy conversion: input that [`DistinguishedName::parse`] rejects becomes
+/// the empty DN. Use [`DistinguishedName::try_parse`] to surface errors.
 impl From<&str> for DistinguishedName {
     fn from(s: &str) -> Self {
         Self::parse(s).unwrap_or_else(|_| Self::empty())
@@ -218,11 +535,76 @@ impl DnBuilder {
     }
 }
 
+/// Compares two RDN components ignoring case in the type and using
+/// [`RdnComponent::normalized_value`] for the value.
+fn rdn_eq_normalized(a: &RdnComponent, b: &RdnComponent) -> bool {
+    if !a.is_multi_valued() && !b.is_multi_valued() {
+        return a.attr_type.eq_ignore_ascii_case(&b.attr_type)
+            && a.normalized_value() == b.normalized_value();
+    }
+    a.normalized_pairs() == b.normalized_pairs()
+}
+
+/// Splits on `sep` where it is not escaped with a backslash.
+fn split_unescaped(s: &str, sep: char) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut start = 0;
+    let mut escaped = false;
+
+    for (i, c) in s.char_indices() {
+        if escaped {
+            escaped = false;
+        } else if c == '\\' {
+            escaped = true;
+        } else if c == sep {
+            parts.push(&s[start..i]);
+            start = i + c.len_utf8();
+        }
+    }
+    parts.push(&s[start..]);
+    parts
+}
+
+/// Checks an attribute type is a descriptor (`cn`, `x-attr`) or numeric OID.
+fn is_valid_attr_type(attr_type: &str) -> bool {
+    let mut chars = attr_type.chars();
+    match chars.next() {
+        Some(c) if c.is_ascii_alphabetic() => chars.all(|c| c.is_ascii_alphanumeric() || c == '-'),
+        Some(c) if c.is_ascii_digit() => attr_type
+            .split('.')
+            .all(|arc| !arc.is_empty() && arc.bytes().all(|b| b.is_ascii_digit())),
+        _ => false,
+    }
+}
+
+/// Checks every `\` in a DN value escapes a special character or starts a
+/// two-digit hex pair.
+fn has_balanced_escapes(value: &str) -> bool {
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            continue;
+        }
+        match chars.next() {
+            Some(' ' | '"' | '#' | '+' | ',' | ';' | '<' | '=' | '>' | '\\') => {}
+            Some(h) if h.is_ascii_hexdigit() => {
+                if !chars.next().is_some_and(|l| l.is_ascii_hexdigit()) {
+                    return false;
+                }
+            }
+            _ => return false,
+        }
+    }
+    true
+}
+
 /// Escapes special characters in a DN value according to RFC 4514.
 pub fn escape_dn_value(value: &str) -> String {
     let mut result = String::with_capacity(value.len() * 2);
 
-    for (i, c) in value.chars().enumerate() {
+    for (i, c) in value.char_indices() {
+        // `i` is a byte offset, so the last char ends exactly at `len()`
+        let last = i + c.len_utf8() == value.len();
         match c {
             // Characters that must be escaped
             '"' | '+' | ',' | ';' | '<' | '>' | '\\' => {
@@ -230,7 +612,7 @@ pub fn escape_dn_value(value: &str) -> String {
                 result.push(c);
             }
             // Space at beginning or end
-            ' ' if i == 0 || i == value.len() - 1 => {
+            ' ' if i == 0 || last => {
                 result.push('\\');
                 result.push(c);
             }
@@ -252,6 +634,48 @@ pub fn escape_dn_value(value: &str) -> String {
     result
 }
 
+/// Escapes a value for [`DistinguishedName::to_rfc4514`].
+///
+/// Leading and trailing spaces are hex-escaped rather than `\ `, since
+/// parsing trims unescaped whitespace around values.
+fn escape_rfc4514(value: &str) -> String {
+    let mut result = String::with_capacity(value.len() + 8);
+
+    for (i, c) in value.char_indices() {
+        let last = i + c.len_utf8() == value.len();
+        match c {
+            '"' | '+' | ',' | ';' | '<' | '>' | '\\' => {
+                result.push('\\');
+                result.push(c);
+            }
+            '#' if i == 0 => result.push_str("\\#"),
+            ' ' if i == 0 || last => result.push_str("\\20"),
+            c if c.is_ascii_control() => result.push_str(&format!("\\{:02x}", c as u8)),
+            _ => result.push(c),
+        }
+    }
+
+    result
+}
+
+/// Percent-encodes a DN or filter for use in an LDAP URL.
+///
+/// Unreserved characters and the sub-delimiters that DNs and filters rely
+/// on (`,`, `=`, `(`, `)`, `*`, ...) are kept; everything else, including
+/// `?`, `%`, `\`, spaces and non-ASCII bytes, is encoded.
+fn percent_encode_url(value: &str) -> String {
+    let mut result = String::with_capacity(value.len());
+    for b in value.bytes() {
+        match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' => result.push(b as char),
+            b'-' | b'.' | b'_' | b'~' | b'!' | b'$' | b'&' | b'\'' | b'(' | b')' | b'*' | b'+'
+            | b',' | b';' | b'=' | b':' | b'@' => result.push(b as char),
+            _ => result.push_str(&format!("%{:02X}", b)),
+        }
+    }
+    result
+}
+
 /// Unescapes a DN value.
 pub fn unescape_dn_value(value: &str) -> String {
     let mut result = String::with_capacity(value.len());
@@ -324,17 +748,22 @@ fn split_dn(dn: &str) -> Vec<String> {
 }
 
 /// Escapes special characters in an LDAP filter value according to RFC 4515.
+///
+/// Besides `*`, `(`, `)` and `\\`, every byte outside printable ASCII
+/// (control characters and each byte of a multibyte UTF-8 sequence) is
+/// written as `\\xx`, so `José` becomes `Jos\\c3\\a9`.
 pub fn escape_filter_value(value: &str) -> String {
-    let mut result = String::with_capacity(value.len() * 3);
+    let needs_escape = |b: u8| !(0x20..0x7f).contains(&b) || b"*()\\".contains(&b);
+    if !value.bytes().any(needs_escape) {
+        return value.to_string();
+    }
 
-    for c in value.chars() {
-        match c {
-            '*' => result.push_str("\\2a"),
-            '(' => result.push_str("\\28"),
-            ')' => result.push_str("\\29"),
-            '\\' => result.push_str("\\5c"),
-            '\0' => result.push_str("\\00"),
-            _ => result.push(c),
+    let mut result = String::with_capacity(value.len() * 3);
+    for b in value.bytes() {
+        if needs_escape(b) {
+            result.push_str(&format!("\\{:02x}", b));
+        } else {
+            result.push(b as char);
         }
     }
 
@@ -352,6 +781,54 @@ mod tests {
         assert_eq!(rdn.attr_value, "testuser");
     }
 
+    #[test]
+    fn test_multi_valued_rdn_round_trip() {
+        let input = "cn=John+uid=jdoe,ou=users,dc=x,dc=y";
+        let dn = DistinguishedName::parse(input).unwrap();
+
+        assert_eq!(dn.len(), 4);
+        let rdn = dn.rdn().unwrap();
+        assert!(rdn.is_multi_valued());
+        assert_eq!(
+            rdn.pairs().collect::<Vec<_>>(),
+            vec![("cn", "John"), ("uid", "jdoe")]
+        );
+        assert_eq!(dn.to_string(), input);
+        assert!(!dn.components[1].is_multi_valued());
+    }
+
+    #[test]
+    fn test_rdn_with_pair_and_value_of() {
+        let rdn = RdnComponent::new("uid", "jdoe").with_pair("cn", "John");
+        assert_eq!(rdn, RdnComponent::parse("cn=John+uid=jdoe").unwrap());
+        assert_eq!(rdn.value_of("UID"), Some("jdoe"));
+        assert_eq!(rdn.value_of("cn"), Some("John"));
+        assert_eq!(rdn.value_of("ou"), None);
+    }
+
+    #[test]
+    fn test_multi_valued_rdn_order_normalized() {
+        let a = DistinguishedName::parse("uid=jdoe+cn=John,dc=x").unwrap();
+        let b = DistinguishedName::parse("cn=John+uid=jdoe,dc=x").unwrap();
+        assert_eq!(a, b);
+        assert_eq!(a.to_string(), "cn=John+uid=jdoe,dc=x");
+
+        let c = DistinguishedName::parse("UID=JDOE+cn=john,DC=x").unwrap();
+        assert!(a.eq_normalized(&c));
+        assert_eq!(hash_of(&a), hash_of(&c));
+        assert!(!a.eq_normalized(&DistinguishedName::parse("cn=John,dc=x").unwrap()));
+    }
+
+    #[test]
+    fn test_escaped_plus_is_not_separator() {
+        let rdn = RdnComponent::parse("cn=A\\+B").unwrap();
+        assert!(!rdn.is_multi_valued());
+        assert_eq!(rdn.attr_value, "A+B");
+        assert_eq!(rdn.to_string(), "cn=A\\+B");
+
+        assert!(RdnComponent::parse("cn=John+jdoe").is_err());
+    }
+
     #[test]
     fn test_rdn_component_display() {
         let rdn = RdnComponent::new("cn", "Test, User");
@@ -366,6 +843,19 @@ mod tests {
         assert_eq!(dn.rdn_value(), Some("test"));
     }
 
+    #[test]
+    fn test_dn_split_rdn() {
+        let dn = DistinguishedName::parse("uid=test,ou=users,dc=example,dc=com").unwrap();
+        let (rdn, parent) = dn.split_rdn().unwrap();
+
+        assert_eq!(rdn, RdnComponent::new("uid", "test"));
+        assert_eq!(parent.to_string(), "ou=users,dc=example,dc=com");
+
+        let single = DistinguishedName::parse("dc=com").unwrap();
+        assert!(single.split_rdn().is_none());
+        assert!(DistinguishedName::empty().split_rdn().is_none());
+    }
+
     #[test]
     fn test_dn_display() {
         let dn = DistinguishedName::parse("uid=test,ou=users,dc=example,dc=com").unwrap();
@@ -389,6 +879,63 @@ mod tests {
         assert!(!dn.is_under(&other));
     }
 
+    #[test]
+    fn test_dn_is_child_of() {
+        let parent = DistinguishedName::parse("ou=users,dc=example,dc=com").unwrap();
+        let child = DistinguishedName::parse("uid=test,OU=Users,dc=example,dc=com").unwrap();
+        let grandchild =
+            DistinguishedName::parse("cn=device,uid=test,ou=users,dc=example,dc=com").unwrap();
+
+        assert!(child.is_child_of(&parent));
+        assert!(!grandchild.is_child_of(&parent));
+        assert!(!parent.is_child_of(&parent));
+    }
+
+    #[test]
+    fn test_dn_relative_to() {
+        let base = DistinguishedName::parse("dc=example,dc=com").unwrap();
+        let child = DistinguishedName::parse("ou=users,dc=example,dc=com").unwrap();
+        let grandchild = DistinguishedName::parse("uid=test,ou=users,DC=Example,dc=com").unwrap();
+
+        assert_eq!(child.relative_to(&base).unwrap().to_string(), "ou=users");
+        assert_eq!(
+            grandchild.relative_to(&base).unwrap().to_string(),
+            "uid=test,ou=users"
+        );
+        assert_eq!(
+            grandchild.relative_to(&child).unwrap().to_string(),
+            "uid=test"
+        );
+        assert!(base.relative_to(&base).unwrap().is_empty());
+        assert_eq!(base.relative_to(&child), None);
+
+        let other = DistinguishedName::parse("ou=users,dc=other,dc=com").unwrap();
+        assert_eq!(other.relative_to(&base), None);
+    }
+
+    #[test]
+    fn test_dn_is_ancestor_of() {
+        let base = DistinguishedName::parse("dc=example,dc=com").unwrap();
+        let child = DistinguishedName::parse("ou=users,dc=example,dc=com").unwrap();
+        let grandchild = DistinguishedName::parse("uid=test,ou=users,DC=Example,dc=com").unwrap();
+
+        assert!(base.is_ancestor_of(&child));
+        assert!(base.is_ancestor_of(&grandchild));
+        assert!(!base.is_ancestor_of(&base));
+        assert!(!grandchild.is_ancestor_of(&base));
+    }
+
+    #[test]
+    fn test_dn_is_sibling_of() {
+        let alice = DistinguishedName::parse("uid=alice,ou=users,dc=example,dc=com").unwrap();
+        let bob = DistinguishedName::parse("uid=bob,ou=Users,dc=example,dc=com").unwrap();
+        let carol = DistinguishedName::parse("uid=carol,ou=admins,dc=example,dc=com").unwrap();
+
+        assert!(alice.is_sibling_of(&bob));
+        assert!(!alice.is_sibling_of(&alice));
+        assert!(!alice.is_sibling_of(&carol));
+    }
+
     #[test]
     fn test_dn_builder() {
         let dn = DnBuilder::new()
@@ -421,6 +968,145 @@ mod tests {
         assert_eq!(escape_dn_value("#hash"), "\\#hash");
     }
 
+    #[test]
+    fn test_escape_dn_value_multibyte_boundaries() {
+        assert_eq!(escape_dn_value("é "), "é\\ ");
+        assert_eq!(escape_dn_value("Zoë Müller "), "Zoë Müller\\ ");
+        assert_eq!(escape_dn_value(" é"), "\\ é");
+        assert_eq!(escape_dn_value("é é"), "é é");
+        assert_eq!(escape_dn_value(" "), "\\ ");
+        assert_eq!(escape_dn_value("  "), "\\ \\ ");
+    }
+
+    #[test]
+    fn test_to_rfc4514_round_trip() {
+        let corpus = [
+            "uid=test,ou=users,dc=example,dc=com",
+            "cn=Smith\\2c John,ou=people,dc=example,dc=com",
+            "cn=Smith\\, John,dc=example,dc=com",
+            "cn=John+uid=jdoe,ou=people,dc=example,dc=com",
+            "cn=a\\+b,dc=example",
+            "cn=\\#hash,dc=example",
+            "cn=mid#hash,dc=example",
+            "cn=x\\=y,dc=example",
+            "cn=\\ padded\\20,dc=example",
+            "cn=\\20,dc=example",
+            "cn=back\\\\slash,dc=example",
+            "cn=\\\"quoted\\\"\\;\\<\\>,dc=example",
+            "cn=nul\\00byte,dc=example",
+            "CN=José Müller,DC=Example",
+        ];
+
+        for dn in corpus {
+            let parsed = DistinguishedName::parse(dn).unwrap();
+            let formatted = parsed.to_rfc4514();
+            let reparsed = DistinguishedName::parse(&formatted).unwrap();
+            assert!(
+                reparsed.eq_normalized(&parsed),
+                "{} -> {} -> {:?}",
+                dn,
+                formatted,
+                reparsed
+            );
+            assert_eq!(reparsed.to_rfc4514(), formatted);
+        }
+    }
+
+    #[test]
+    fn test_to_rfc4514_output() {
+        let dn = DistinguishedName::parse("CN=Smith\\2c John+UID=js,DC=Example").unwrap();
+        assert_eq!(dn.to_rfc4514(), "cn=Smith\\, John+uid=js,dc=Example");
+
+        let dn = DistinguishedName::parse("cn=x\\=y,dc=example").unwrap();
+        assert_eq!(dn.to_rfc4514(), "cn=x=y,dc=example");
+
+        let dn = DistinguishedName::from_components(vec![RdnComponent::new("cn", " trailing ")]);
+        assert_eq!(dn.to_rfc4514(), "cn=\\20trailing\\20");
+    }
+
+    #[test]
+    fn test_try_parse_valid() {
+        let dn = DistinguishedName::try_parse("uid=test, ou=users,dc=example,dc=com").unwrap();
+        assert_eq!(
+            dn,
+            DistinguishedName::parse("uid=test,ou=users,dc=example,dc=com").unwrap()
+        );
+
+        let dn = DistinguishedName::try_parse("cn=Smith\\2c John+uid=js,2.5.4.3=x").unwrap();
+        assert_eq!(dn.len(), 2);
+        assert!(dn.components[0].is_multi_valued());
+        assert_eq!(dn.components[0].attr_value, "Smith, John");
+
+        assert!(DistinguishedName::try_parse("").unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_try_parse_rejects_empty_rdn() {
+        let err = DistinguishedName::try_parse("uid=a,,dc=x").unwrap_err();
+        assert!(matches!(err, HeraclesError::InvalidDN(ref m) if m.contains("empty RDN")));
+
+        let err = DistinguishedName::try_parse("uid=a,dc=x,").unwrap_err();
+        assert!(matches!(err, HeraclesError::InvalidDN(ref m) if m.contains("empty RDN")));
+
+        assert!(DistinguishedName::try_parse("uid=a+,dc=x").is_err());
+    }
+
+    #[test]
+    fn test_try_parse_rejects_missing_equals() {
+        let err = DistinguishedName::try_parse("notadn").unwrap_err();
+        assert!(matches!(err, HeraclesError::InvalidDN(ref m) if m.contains("'notadn'")));
+
+        let err = DistinguishedName::try_parse("uid=a,users,dc=x").unwrap_err();
+        assert!(matches!(err, HeraclesError::InvalidDN(ref m) if m.contains("'users'")));
+    }
+
+    #[test]
+    fn test_try_parse_rejects_bad_types_and_escapes() {
+        let err = DistinguishedName::try_parse("=a,dc=x").unwrap_err();
+        assert!(
+            matches!(err, HeraclesError::InvalidDN(ref m) if m.contains("empty attribute type"))
+        );
+
+        assert!(DistinguishedName::try_parse("u id=a,dc=x").is_err());
+        assert!(DistinguishedName::try_parse("1.2..3=a").is_err());
+        assert!(DistinguishedName::try_parse("cn=a\\").is_err());
+        assert!(DistinguishedName::try_parse("cn=a\\4,dc=x").is_err());
+        assert!(DistinguishedName::try_parse("cn=a\\q,dc=x").is_err());
+
+        // The lenient paths still accept the same input
+        assert!(DistinguishedName::parse("uid=a,,dc=x").is_ok());
+        assert_eq!(
+            DistinguishedName::from("notadn"),
+            DistinguishedName::empty()
+        );
+    }
+
+    #[test]
+    fn test_to_ldap_url_subtree_with_filter() {
+        let dn = DistinguishedName::parse("ou=users,dc=example,dc=com").unwrap();
+        assert_eq!(
+            dn.to_ldap_url(
+                "ldap.example.com:389",
+                SearchScope::Subtree,
+                Some("(&(objectClass=person)(cn=John Doe?))")
+            ),
+            "ldap://ldap.example.com:389/ou=users,dc=example,dc=com??sub?(&(objectClass=person)(cn=John%20Doe%3F))"
+        );
+    }
+
+    #[test]
+    fn test_to_ldap_url_base_without_filter() {
+        let dn = DistinguishedName::parse("cn=Smith\\, José,dc=example").unwrap();
+        assert_eq!(
+            dn.to_ldap_url("ldaps://ldap.example.com/", SearchScope::Base, None),
+            "ldaps://ldap.example.com/cn=Smith%5C,%20Jos%C3%A9,dc=example??base"
+        );
+        assert_eq!(
+            DistinguishedName::empty().to_ldap_url("host", SearchScope::OneLevel, None),
+            "ldap://host/??one"
+        );
+    }
+
     #[test]
     fn test_unescape_dn_value() {
         assert_eq!(unescape_dn_value("simple"), "simple");
@@ -435,6 +1121,15 @@ mod tests {
         assert_eq!(escape_filter_value("with*wildcard"), "with\\2awildcard");
         assert_eq!(escape_filter_value("(parens)"), "\\28parens\\29");
         assert_eq!(escape_filter_value("back\\slash"), "back\\5cslash");
+        assert_eq!(escape_filter_value("nul\0"), "nul\\00");
+    }
+
+    #[test]
+    fn test_escape_filter_value_non_ascii() {
+        assert_eq!(escape_filter_value("Jos\u{e9}"), "Jos\\c3\\a9");
+        assert_eq!(escape_filter_value("tab\there"), "tab\\09here");
+        assert_eq!(escape_filter_value("\u{20ac}*"), "\\e2\\82\\ac\\2a");
+        assert_eq!(escape_filter_value("~ok{}"), "~ok{}");
     }
 
     #[test]
@@ -450,4 +1145,59 @@ mod tests {
         let canonical = dn.to_canonical();
         assert_eq!(canonical.rdn_type(), Some("uid"));
     }
+
+    fn hash_of(dn: &DistinguishedName) -> u64 {
+        use std::collections::hash_map::DefaultHasher;
+        let mut hasher = DefaultHasher::new();
+        dn.hash(&mut hasher);
+        hasher.finish()
+    }
+
+    #[test]
+    fn test_eq_normalized() {
+        let a = DistinguishedName::parse("UID=Test,OU=People,DC=Example,DC=com").unwrap();
+        let b = DistinguishedName::parse("uid=test, ou=people , dc=example,dc=COM").unwrap();
+        let c = DistinguishedName::parse("uid=other,ou=people,dc=example,dc=com").unwrap();
+
+        assert_ne!(a, b);
+        assert!(a.eq_normalized(&b));
+        assert!(!a.eq_normalized(&c));
+        assert!(!a.eq_normalized(&a.parent().unwrap()));
+        assert_eq!(a.normalized(), b.normalized());
+        assert_eq!(hash_of(&a), hash_of(&b));
+    }
+
+    #[test]
+    fn test_normalized_dn_as_map_key() {
+        let mut owners = std::collections::HashMap::new();
+        let dn = DistinguishedName::parse("cn=Jos\u{e9},DC=Example,DC=com").unwrap();
+        owners.insert(dn.normalized(), "alice");
+
+        let lookup = DistinguishedName::parse("CN=jose\u{301},dc=example,dc=com").unwrap();
+        assert_eq!(owners.get(&lookup.normalized()), Some(&"alice"));
+    }
+
+    #[test]
+    fn test_rdn_normalized_value_unicode_case() {
+        let upper = RdnComponent::new("cn", "JOSÉ");
+        let lower = RdnComponent::new("CN", "josé");
+        assert_eq!(upper.normalized_value(), lower.normalized_value());
+        assert!(rdn_eq_normalized(&upper, &lower));
+    }
+
+    #[test]
+    fn test_rdn_normalized_value_composition() {
+        let precomposed = RdnComponent::new("cn", "Jos\u{e9}");
+        let decomposed = RdnComponent::new("cn", "Jose\u{301}");
+        assert_ne!(precomposed.attr_value, decomposed.attr_value);
+        assert_eq!(precomposed.normalized_value(), "jos\u{e9}");
+        assert!(rdn_eq_normalized(&precomposed, &decomposed));
+
+        let a = DistinguishedName::parse("cn=Jos\u{e9},ou=users,dc=example,dc=com").unwrap();
+        let b = DistinguishedName::parse("cn=jose\u{301},ou=Users,dc=example,dc=com").unwrap();
+        let parent = DistinguishedName::parse("ou=users,dc=example,dc=com").unwrap();
+        assert!(a.is_child_of(&parent));
+        assert!(b.is_child_of(&parent));
+        assert!(!a.is_sibling_of(&b));
+    }
 }
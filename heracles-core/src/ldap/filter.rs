@@ -1,7 +1,13 @@
 //! LDAP filter building utilities.
 
-use crate::ldap::dn::escape_filter_value;
+use crate::errors::{HeraclesError, Result};
+use crate::ldap::dn::{escape_filter_value, DistinguishedName};
+use crate::ldap::operations::LdapEntry;
 use std::fmt;
+use time::{Date, Month, OffsetDateTime, Time, UtcOffset};
+
+/// Default nesting limit enforced by [`LdapFilter::parse`].
+pub const DEFAULT_MAX_FILTER_DEPTH: usize = 50;
 
 /// Represents an LDAP filter.
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -24,6 +30,17 @@ pub enum LdapFilter {
     And(Vec<LdapFilter>),
     /// Disjunction: (|(filter1)(filter2)...)
     Or(Vec<LdapFilter>),
+    /// Extensible match: (attr:dn:rule:=value)
+    ExtensibleMatch {
+        /// Attribute to match; may be omitted when a matching rule is given.
+        attr: Option<String>,
+        /// Matching rule name or OID.
+        matching_rule: Option<String>,
+        /// Assertion value.
+        value: String,
+        /// Also match against the attributes of the entry's DN.
+        dn_attrs: bool,
+    },
     /// Raw filter string (use with caution - should be pre-escaped)
     Raw(String),
 }
@@ -85,6 +102,23 @@ impl LdapFilter {
         Self::Or(filters)
     }
 
+    /// Creates an extensible match: (attr:dn:rule:=value)
+    ///
+    /// RFC 4515 requires at least one of `attr` and `matching_rule`.
+    pub fn extensible(
+        attr: Option<&str>,
+        matching_rule: Option<&str>,
+        value: impl Into<String>,
+        dn_attrs: bool,
+    ) -> Self {
+        Self::ExtensibleMatch {
+            attr: attr.map(str::to_string),
+            matching_rule: matching_rule.map(str::to_string),
+            value: value.into(),
+            dn_attrs,
+        }
+    }
+
     /// Creates a raw filter (should be already escaped).
     pub fn raw(filter: impl Into<String>) -> Self {
         Self::Raw(filter.into())
@@ -94,6 +128,507 @@ impl LdapFilter {
     pub fn to_string_escaped(&self) -> String {
         self.to_string()
     }
+
+    /// Attribute names referenced anywhere in the filter.
+    ///
+    /// Names are deduplicated case-insensitively and keep the spelling and
+    /// order of their first occurrence. Extensible matches without an
+    /// attribute contribute nothing; `Raw` filters are parsed on a
+    /// best-effort basis and skipped if malformed.
+    pub fn attributes(&self) -> Vec<String> {
+        let mut attrs = Vec::new();
+        self.collect_attributes(&mut attrs);
+        attrs
+    }
+
+    fn collect_attributes(&self, attrs: &mut Vec<String>) {
+        let attr = match self {
+            LdapFilter::Equals(attr, _)
+            | LdapFilter::Present(attr)
+            | LdapFilter::Substring(attr, ..)
+            | LdapFilter::GreaterOrEqual(attr, _)
+            | LdapFilter::LessOrEqual(attr, _)
+            | LdapFilter::Approx(attr, _) => attr,
+            LdapFilter::ExtensibleMatch {
+                attr: Some(attr), ..
+            } => attr,
+            LdapFilter::ExtensibleMatch { attr: None, .. } => return,
+            LdapFilter::Not(inner) => return inner.collect_attributes(attrs),
+            LdapFilter::And(filters) | LdapFilter::Or(filters) => {
+                for filter in filters {
+                    filter.collect_attributes(attrs);
+                }
+                return;
+            }
+            LdapFilter::Raw(raw) => {
+                if let Ok(parsed) = LdapFilter::parse(raw) {
+                    parsed.collect_attributes(attrs);
+                }
+                return;
+            }
+        };
+        if !attrs.iter().any(|a| a.eq_ignore_ascii_case(attr)) {
+            attrs.push(attr.clone());
+        }
+    }
+
+    /// Returns an equivalent, tidier filter.
+    ///
+    /// Nested `And`-in-`And` and `Or`-in-`Or` are flattened, single-child
+    /// `And`/`Or` collapse to the child, double negations cancel, and
+    /// repeated sibling clauses are dropped (first occurrence wins, so the
+    /// order of distinct clauses is kept). Empty `(&)` and `(|)` are left
+    /// as they are.
+    pub fn simplify(self) -> LdapFilter {
+        match self {
+            LdapFilter::Not(inner) => match inner.simplify() {
+                LdapFilter::Not(double) => *double,
+                other => LdapFilter::not(other),
+            },
+            LdapFilter::And(filters) => Self::simplify_list(filters, true),
+            LdapFilter::Or(filters) => Self::simplify_list(filters, false),
+            other => other,
+        }
+    }
+
+    fn simplify_list(filters: Vec<LdapFilter>, is_and: bool) -> LdapFilter {
+        let mut flat: Vec<LdapFilter> = Vec::with_capacity(filters.len());
+        for filter in filters {
+            let children = match filter.simplify() {
+                LdapFilter::And(children) if is_and => children,
+                LdapFilter::Or(children) if !is_and => children,
+                other => vec![other],
+            };
+            for child in children {
+                if !flat.contains(&child) {
+                    flat.push(child);
+                }
+            }
+        }
+
+        match (flat.len(), is_and) {
+            (1, _) => flat.pop().unwrap(),
+            (_, true) => LdapFilter::And(flat),
+            (_, false) => LdapFilter::Or(flat),
+        }
+    }
+
+    /// Parses an RFC 4515 filter string.
+    ///
+    /// `\xx` escapes in assertion values are decoded, so the resulting
+    /// filter re-escapes correctly when formatted. Empty `(&)` and `(|)`
+    /// (RFC 4526 absolute true/false) are accepted.
+    ///
+    /// # Errors
+    ///
+    /// Returns `InvalidFilter` with the byte offset of the first problem,
+    /// including nesting deeper than [`DEFAULT_MAX_FILTER_DEPTH`].
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use heracles_core::ldap::LdapFilter;
+    ///
+    /// let filter = LdapFilter::parse("(&(objectClass=person)(uid=a*))").unwrap();
+    /// assert_eq!(
+    ///     filter,
+    ///     LdapFilter::and(vec![
+    ///         LdapFilter::eq("objectClass", "person"),
+    ///         LdapFilter::starts_with("uid", "a"),
+    ///     ])
+    /// );
+    /// ```
+    pub fn parse(s: &str) -> Result<Self> {
+        Self::parse_with_max_depth(s, DEFAULT_MAX_FILTER_DEPTH)
+    }
+
+    /// Same as [`parse`](Self::parse) with a custom nesting limit; a plain
+    /// `(attr=value)` has depth 1.
+    pub fn parse_with_max_depth(s: &str, max_depth: usize) -> Result<Self> {
+        let mut parser = FilterParser {
+            input: s,
+            pos: 0,
+            depth: 0,
+            max_depth,
+        };
+        let filter = parser.filter()?;
+        if parser.pos != s.len() {
+            return Err(parser.error("unexpected trailing input"));
+        }
+        Ok(filter)
+    }
+
+    /// Checks the filter's nesting depth and node count against limits.
+    ///
+    /// Formatting, matching and simplifying recurse over the tree, so
+    /// filters built from untrusted input should be validated first. A
+    /// plain `(attr=value)` has depth 1 and counts as one node.
+    pub fn validate(&self, max_depth: usize, max_nodes: usize) -> Result<()> {
+        let mut nodes = 0;
+        let mut stack = vec![(self, 1)];
+
+        while let Some((filter, depth)) = stack.pop() {
+            nodes += 1;
+            if depth > max_depth {
+                return Err(HeraclesError::InvalidFilter(format!(
+                    "nesting exceeds {} levels",
+                    max_depth
+                )));
+            }
+            if nodes > max_nodes {
+                return Err(HeraclesError::InvalidFilter(format!(
+                    "more than {} filter nodes",
+                    max_nodes
+                )));
+            }
+            match filter {
+                LdapFilter::Not(inner) => stack.push((inner, depth + 1)),
+                LdapFilter::And(filters) | LdapFilter::Or(filters) => {
+                    stack.extend(filters.iter().map(|f| (f, depth + 1)));
+                }
+                _ => {}
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Evaluates the filter against an entry without contacting a server.
+    ///
+    /// Attribute names and values compare case-insensitively, as with the
+    /// common `caseIgnoreMatch` rules; a multi-valued attribute matches if
+    /// any of its values does. Since attribute syntaxes are unknown here,
+    /// `>=` and `<=` use lexical byte comparison of the lowercased values
+    /// (so `"9" >= "10"`), and `~=` behaves like equality. An extensible
+    /// match without a rule, or with `caseIgnoreMatch` or `caseExactMatch`,
+    /// is evaluated (checking every attribute when `attr` is omitted, and the
+    /// DN's RDN values with `:dn`); any other matching rule never matches. A
+    /// `Raw` filter is parsed first and never matches if it is malformed.
+    pub fn matches(&self, entry: &LdapEntry) -> bool {
+        match self {
+            LdapFilter::Equals(attr, value) | LdapFilter::Approx(attr, value) => {
+                let value = value.to_lowercase();
+                entry_values(entry, attr).any(|v| v.to_lowercase() == value)
+            }
+            LdapFilter::Present(attr) => entry_values(entry, attr).next().is_some(),
+            LdapFilter::Substring(attr, initial, any, final_) => entry_values(entry, attr)
+                .any(|v| substring_matches(&v.to_lowercase(), initial, any, final_)),
+            LdapFilter::GreaterOrEqual(attr, value) => {
+                let value = value.to_lowercase();
+                entry_values(entry, attr).any(|v| v.to_lowercase() >= value)
+            }
+            LdapFilter::LessOrEqual(attr, value) => {
+                let value = value.to_lowercase();
+                entry_values(entry, attr).any(|v| v.to_lowercase() <= value)
+            }
+            LdapFilter::Not(inner) => !inner.matches(entry),
+            LdapFilter::And(filters) => filters.iter().all(|f| f.matches(entry)),
+            LdapFilter::Or(filters) => filters.iter().any(|f| f.matches(entry)),
+            LdapFilter::ExtensibleMatch {
+                attr,
+                matching_rule,
+                value,
+                dn_attrs,
+            } => extensible_matches(
+                entry,
+                attr.as_deref(),
+                matching_rule.as_deref(),
+                value,
+                *dn_attrs,
+            ),
+            LdapFilter::Raw(raw) => LdapFilter::parse(raw)
+                .map(|f| f.matches(entry))
+                .unwrap_or(false),
+        }
+    }
+}
+
+/// Values of `attr` on `entry`, looking the name up case-insensitively.
+fn entry_values<'a>(entry: &'a LdapEntry, attr: &'a str) -> impl Iterator<Item = &'a String> {
+    entry
+        .attributes
+        .iter()
+        .filter(move |(name, _)| name.eq_ignore_ascii_case(attr))
+        .flat_map(|(_, values)| values)
+}
+
+/// Evaluates an extensible match; see [`LdapFilter::matches`].
+fn extensible_matches(
+    entry: &LdapEntry,
+    attr: Option<&str>,
+    matching_rule: Option<&str>,
+    value: &str,
+    dn_attrs: bool,
+) -> bool {
+    let case_exact = match matching_rule {
+        None => false,
+        Some(rule) if rule.eq_ignore_ascii_case("caseIgnoreMatch") || rule == "2.5.13.2" => false,
+        Some(rule) if rule.eq_ignore_ascii_case("caseExactMatch") || rule == "2.5.13.5" => true,
+        Some(_) => return false,
+    };
+    let value_matches = |candidate: &str| {
+        if case_exact {
+            candidate == value
+        } else {
+            candidate.to_lowercase() == value.to_lowercase()
+        }
+    };
+    let attr_matches = |name: &str| attr.is_none_or(|a| a.eq_ignore_ascii_case(name));
+
+    let in_entry = entry
+        .attributes
+        .iter()
+        .filter(|(name, _)| attr_matches(name))
+        .flat_map(|(_, values)| values)
+        .any(|v| value_matches(v));
+
+    in_entry
+        || dn_attrs
+            && DistinguishedName::parse(&entry.dn)
+                .map(|dn| {
+                    dn.components
+                        .iter()
+                        .flat_map(|c| c.pairs())
+                        .any(|(name, value)| attr_matches(name) && value_matches(value))
+                })
+                .unwrap_or(false)
+}
+
+/// Checks `value` (already lowercased) against substring segments in order.
+fn substring_matches(
+    value: &str,
+    initial: &Option<String>,
+    any: &[String],
+    final_: &Option<String>,
+) -> bool {
+    let mut rest = value;
+
+    if let Some(init) = initial {
+        match rest.strip_prefix(init.to_lowercase().as_str()) {
+            Some(tail) => rest = tail,
+            None => return false,
+        }
+    }
+
+    for part in any {
+        let part = part.to_lowercase();
+        match rest.find(&part) {
+            Some(idx) => rest = &rest[idx + part.len()..],
+            None => return false,
+        }
+    }
+
+    match final_ {
+        Some(fin) => rest.ends_with(&fin.to_lowercase()),
+        None => true,
+    }
+}
+
+/// Recursive-descent parser for RFC 4515 filter strings.
+struct FilterParser<'a> {
+    input: &'a str,
+    pos: usize,
+    depth: usize,
+    max_depth: usize,
+}
+
+impl FilterParser<'_> {
+    fn peek(&self) -> Option<u8> {
+        self.input.as_bytes().get(self.pos).copied()
+    }
+
+    fn expect(&mut self, byte: u8) -> Result<()> {
+        if self.peek() == Some(byte) {
+            self.pos += 1;
+            Ok(())
+        } else {
+            Err(self.error(&format!("expected '{}'", byte as char)))
+        }
+    }
+
+    fn error(&self, reason: &str) -> HeraclesError {
+        HeraclesError::InvalidFilter(format!("{} at byte {}", reason, self.pos))
+    }
+
+    /// filter = "(" filtercomp ")"
+    fn filter(&mut self) -> Result<LdapFilter> {
+        if self.depth == self.max_depth {
+            return Err(self.error(&format!("nesting exceeds {} levels", self.max_depth)));
+        }
+        self.depth += 1;
+        let filter = self.filter_inner();
+        self.depth -= 1;
+        filter
+    }
+
+    fn filter_inner(&mut self) -> Result<LdapFilter> {
+        self.expect(b'(')?;
+        let filter = match self.peek() {
+            Some(b'&') => {
+                self.pos += 1;
+                LdapFilter::And(self.filter_list()?)
+            }
+            Some(b'|') => {
+                self.pos += 1;
+                LdapFilter::Or(self.filter_list()?)
+            }
+            Some(b'!') => {
+                self.pos += 1;
+                LdapFilter::not(self.filter()?)
+            }
+            _ => self.item()?,
+        };
+        self.expect(b')')?;
+        Ok(filter)
+    }
+
+    fn filter_list(&mut self) -> Result<Vec<LdapFilter>> {
+        let mut filters = Vec::new();
+        while self.peek() == Some(b'(') {
+            filters.push(self.filter()?);
+        }
+        Ok(filters)
+    }
+
+    /// item = attr ( "=" / "~=" / ">=" / "<=" ) value
+    fn item(&mut self) -> Result<LdapFilter> {
+        if self.peek() == Some(b':') {
+            return self.extensible(None);
+        }
+        let attr = self.attribute()?;
+        let op = match self.peek() {
+            Some(b'=') => None,
+            Some(op @ (b'~' | b'>' | b'<')) => {
+                self.pos += 1;
+                Some(op)
+            }
+            Some(b':') => return self.extensible(Some(attr)),
+            _ => return Err(self.error("expected filter type")),
+        };
+        self.expect(b'=')?;
+
+        let parts = self.value_parts()?;
+        if op.is_some() && parts.len() > 1 {
+            return Err(self.error("unexpected '*' in value"));
+        }
+        let mut parts = parts.into_iter();
+        let first = parts.next().unwrap_or_default();
+
+        Ok(match op {
+            Some(b'~') => LdapFilter::Approx(attr, first),
+            Some(b'>') => LdapFilter::GreaterOrEqual(attr, first),
+            Some(_) => LdapFilter::LessOrEqual(attr, first),
+            None if parts.len() == 0 => LdapFilter::Equals(attr, first),
+            None => {
+                let mut rest: Vec<String> = parts.collect();
+                let last = rest.pop().unwrap_or_default();
+                if rest.is_empty() && first.is_empty() && last.is_empty() {
+                    return Ok(LdapFilter::Present(attr));
+                }
+                if rest.iter().any(String::is_empty) {
+                    return Err(self.error("empty substring between '*'"));
+                }
+                let initial = Some(first).filter(|v| !v.is_empty());
+                let final_ = Some(last).filter(|v| !v.is_empty());
+                LdapFilter::Substring(attr, initial, rest, final_)
+            }
+        })
+    }
+
+    /// extensible = [attr] [":dn"] [":" matchingrule] ":=" value
+    fn extensible(&mut self, attr: Option<String>) -> Result<LdapFilter> {
+        self.expect(b':')?;
+        let dn_attrs = self
+            .input
+            .get(self.pos..self.pos + 3)
+            .is_some_and(|s| s.eq_ignore_ascii_case("dn:"));
+        if dn_attrs {
+            self.pos += 3;
+        }
+
+        let matching_rule = if self.peek() == Some(b'=') {
+            None
+        } else {
+            let rule = self.descriptor("expected matching rule")?;
+            self.expect(b':')?;
+            Some(rule)
+        };
+        if attr.is_none() && matching_rule.is_none() {
+            return Err(self.error("extensible match needs an attribute or matching rule"));
+        }
+        self.expect(b'=')?;
+
+        let mut parts = self.value_parts()?;
+        if parts.len() > 1 {
+            return Err(self.error("unexpected '*' in value"));
+        }
+        Ok(LdapFilter::ExtensibleMatch {
+            attr,
+            matching_rule,
+            value: parts.pop().unwrap_or_default(),
+            dn_attrs,
+        })
+    }
+
+    /// Attribute description: descriptor or OID, with optional `;options`.
+    fn attribute(&mut self) -> Result<String> {
+        self.descriptor("expected attribute description")
+    }
+
+    fn descriptor(&mut self, expected: &str) -> Result<String> {
+        let start = self.pos;
+        while matches!(self.peek(), Some(b) if b.is_ascii_alphanumeric() || b"-.;".contains(&b)) {
+            self.pos += 1;
+        }
+        if self.pos == start {
+            return Err(self.error(expected));
+        }
+        Ok(self.input[start..self.pos].to_string())
+    }
+
+    /// Reads an assertion value up to the closing ')', split on unescaped
+    /// '*' and with `\xx` escapes decoded.
+    fn value_parts(&mut self) -> Result<Vec<String>> {
+        let mut parts = Vec::new();
+        let mut current = Vec::new();
+        let mut part_start = self.pos;
+
+        loop {
+            match self.peek() {
+                None | Some(b')') => break,
+                Some(b'(') => return Err(self.error("unescaped '(' in value")),
+                Some(b'*') => {
+                    parts.push(self.decode(current, part_start)?);
+                    current = Vec::new();
+                    self.pos += 1;
+                    part_start = self.pos;
+                }
+                Some(b'\\') => {
+                    let hex = self
+                        .input
+                        .get(self.pos + 1..self.pos + 3)
+                        .filter(|h| h.bytes().all(|b| b.is_ascii_hexdigit()))
+                        .and_then(|h| u8::from_str_radix(h, 16).ok())
+                        .ok_or_else(|| self.error("invalid escape sequence"))?;
+                    current.push(hex);
+                    self.pos += 3;
+                }
+                Some(b) => {
+                    current.push(b);
+                    self.pos += 1;
+                }
+            }
+        }
+
+        parts.push(self.decode(current, part_start)?);
+        Ok(parts)
+    }
+
+    fn decode(&self, bytes: Vec<u8>, start: usize) -> Result<String> {
+        String::from_utf8(bytes).map_err(|_| {
+            HeraclesError::InvalidFilter(format!("value is not valid UTF-8 at byte {}", start))
+        })
+    }
 }
 
 impl fmt::Display for LdapFilter {
@@ -141,11 +676,100 @@ impl fmt::Display for LdapFilter {
                 }
                 write!(f, ")")
             }
+            LdapFilter::ExtensibleMatch {
+                attr,
+                matching_rule,
+                value,
+                dn_attrs,
+            } => {
+                write!(f, "(")?;
+                if let Some(attr) = attr {
+                    write!(f, "{}", attr)?;
+                }
+                if *dn_attrs {
+                    write!(f, ":dn")?;
+                }
+                if let Some(rule) = matching_rule {
+                    write!(f, ":{}", rule)?;
+                }
+                write!(f, ":={})", escape_filter_value(value))
+            }
             LdapFilter::Raw(s) => write!(f, "{}", s),
         }
     }
 }
 
+/// Formats `dt` as an LDAP GeneralizedTime value in UTC (`YYYYMMDDHHMMSSZ`).
+///
+/// Sub-second precision is dropped, so the value compares correctly against
+/// operational attributes such as `createTimestamp`.
+pub fn generalized_time(dt: OffsetDateTime) -> String {
+    let utc = dt.to_offset(UtcOffset::UTC);
+    format!(
+        "{:04}{:02}{:02}{:02}{:02}{:02}Z",
+        utc.year(),
+        u8::from(utc.month()),
+        utc.day(),
+        utc.hour(),
+        utc.minute(),
+        utc.second()
+    )
+}
+
+/// Parses an LDAP GeneralizedTime value such as `20240101000000Z`.
+///
+/// Accepts an optional fraction of a second (`20240101000000.5Z`) and a
+/// `+HHMM`/`-HHMM` offset in place of `Z`. Returns `None` for anything
+/// else, including values without seconds.
+pub fn parse_generalized_time(value: &str) -> Option<OffsetDateTime> {
+    let digits = |s: &str| -> Option<u32> {
+        s.bytes()
+            .all(|b| b.is_ascii_digit())
+            .then(|| s.parse().ok())
+            .flatten()
+    };
+    let (stamp, rest) = value.split_at_checked(14)?;
+    let field = |range: std::ops::Range<usize>| digits(stamp.get(range)?);
+
+    let (nanos, zone) = match rest.strip_prefix(['.', ',']) {
+        Some(rest) => {
+            let end = rest.find(|c: char| !c.is_ascii_digit())?;
+            let fraction = rest.get(..end.min(9)).filter(|f| !f.is_empty())?;
+            (digits(&format!("{:0<9}", fraction))?, &rest[end..])
+        }
+        None => (0, rest),
+    };
+
+    let offset = match zone {
+        "Z" => UtcOffset::UTC,
+        _ if zone.len() == 5 => {
+            let sign = match zone.get(..1)? {
+                "+" => 1,
+                "-" => -1,
+                _ => return None,
+            };
+            let (hours, minutes) = (digits(zone.get(1..3)?)?, digits(zone.get(3..)?)?);
+            UtcOffset::from_hms(sign * hours as i8, sign * minutes as i8, 0).ok()?
+        }
+        _ => return None,
+    };
+
+    let date = Date::from_calendar_date(
+        field(0..4)? as i32,
+        Month::try_from(field(4..6)? as u8).ok()?,
+        field(6..8)? as u8,
+    )
+    .ok()?;
+    let time = Time::from_hms_nano(
+        field(8..10)? as u8,
+        field(10..12)? as u8,
+        field(12..14)? as u8,
+        nanos,
+    )
+    .ok()?;
+    Some(date.with_time(time).assume_offset(offset))
+}
+
 /// Builder for constructing LDAP filters.
 #[derive(Debug, Default)]
 pub struct FilterBuilder {
@@ -194,6 +818,28 @@ impl FilterBuilder {
         self
     }
 
+    /// Adds an OR of objectClass filters, matching any of `classes`.
+    pub fn any_object_class(mut self, classes: &[&str]) -> Self {
+        self.filters.push(patterns::any_object_class(classes));
+        self
+    }
+
+    /// Adds an inclusive time range on a GeneralizedTime attribute:
+    /// `(&(attr>=from)(attr<=to))`, with both bounds converted to UTC.
+    pub fn time_range(
+        mut self,
+        attr: impl Into<String>,
+        from: OffsetDateTime,
+        to: OffsetDateTime,
+    ) -> Self {
+        let attr = attr.into();
+        self.filters.push(LdapFilter::and(vec![
+            LdapFilter::gte(attr.clone(), generalized_time(from)),
+            LdapFilter::lte(attr, generalized_time(to)),
+        ]));
+        self
+    }
+
     /// Adds a nested filter.
     pub fn filter(mut self, filter: LdapFilter) -> Self {
         self.filters.push(filter);
@@ -266,13 +912,13 @@ pub mod patterns {
 
     /// Filter for systems/servers.
     pub fn system() -> LdapFilter {
-        LdapFilter::or(vec![
-            LdapFilter::eq("objectClass", "hrcServer"),
-            LdapFilter::eq("objectClass", "hrcWorkstation"),
-            LdapFilter::eq("objectClass", "hrcTerminal"),
-            LdapFilter::eq("objectClass", "hrcPrinter"),
-            LdapFilter::eq("objectClass", "hrcPhone"),
-            LdapFilter::eq("objectClass", "hrcMobilePhone"),
+        any_object_class(&[
+            "hrcServer",
+            "hrcWorkstation",
+            "hrcTerminal",
+            "hrcPrinter",
+            "hrcPhone",
+            "hrcMobilePhone",
         ])
     }
 
@@ -298,12 +944,415 @@ pub mod patterns {
             .present("sshPublicKey")
             .build_and()
     }
+
+    /// Filter matching a set of object classes.
+    ///
+    /// With `all` the classes are ANDed, otherwise ORed. A single class
+    /// yields a plain equality filter.
+    pub fn by_object_classes(classes: &[&str], all: bool) -> LdapFilter {
+        let builder = classes
+            .iter()
+            .fold(FilterBuilder::new(), |b, class| b.object_class(*class));
+        if all {
+            builder.build_and()
+        } else {
+            builder.build_or()
+        }
+    }
+
+    /// Filter matching entries with any of the given object classes.
+    ///
+    /// Shorthand for [`by_object_classes`] with `all` set to false.
+    pub fn any_object_class(classes: &[&str]) -> LdapFilter {
+        by_object_classes(classes, false)
+    }
+
+    /// Filter for a user's own entry in self-service flows.
+    ///
+    /// Matches `uid` on entries carrying all `object_classes`.
+    pub fn self_entry(uid: &str, object_classes: &[&str]) -> LdapFilter {
+        object_classes
+            .iter()
+            .fold(FilterBuilder::new(), |b, class| b.object_class(*class))
+            .eq("uid", uid)
+            .build_and()
+    }
+
+    /// Filter for FusionDirectory objects carrying ACLs.
+    pub fn fd_acl_object() -> LdapFilter {
+        by_object_classes(&["gosaAcl", "fdAclEntry"], false)
+    }
+
+    /// Filter for FusionDirectory ACL roles.
+    pub fn fd_role() -> LdapFilter {
+        by_object_classes(&["gosaRole"], true)
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    fn sample_entry() -> LdapEntry {
+        LdapEntry::new("uid=jdoe,ou=users,dc=example,dc=com")
+            .with_attribute("objectClass", vec!["top", "inetOrgPerson"])
+            .with_attribute("uid", vec!["jdoe"])
+            .with_attribute("cn", vec!["John Doe"])
+            .with_attribute(
+                "mail",
+                vec!["jdoe@example.com", "john.doe@corp.example.org"],
+            )
+            .with_attribute("uidNumber", vec!["1500"])
+    }
+
+    #[test]
+    fn test_matches_equality_and_presence() {
+        let entry = sample_entry();
+
+        assert!(LdapFilter::eq("uid", "JDOE").matches(&entry));
+        assert!(LdapFilter::eq("objectclass", "inetorgperson").matches(&entry));
+        assert!(!LdapFilter::eq("uid", "jane").matches(&entry));
+        assert!(LdapFilter::present("mail").matches(&entry));
+        assert!(!LdapFilter::present("telephoneNumber").matches(&entry));
+    }
+
+    #[test]
+    fn test_matches_multi_valued() {
+        let entry = sample_entry();
+
+        assert!(LdapFilter::eq("mail", "john.doe@corp.example.org").matches(&entry));
+        assert!(LdapFilter::ends_with("mail", "@CORP.example.org").matches(&entry));
+        assert!(LdapFilter::starts_with("mail", "jdoe@").matches(&entry));
+        assert!(!LdapFilter::ends_with("mail", "@other.org").matches(&entry));
+        assert!(LdapFilter::not(LdapFilter::eq("mail", "nobody@example.com")).matches(&entry));
+    }
+
+    #[test]
+    fn test_matches_substring_segments_in_order() {
+        let entry = sample_entry();
+        let segments = |init: Option<&str>, any: &[&str], fin: Option<&str>| {
+            LdapFilter::Substring(
+                "cn".into(),
+                init.map(Into::into),
+                any.iter().map(|s| s.to_string()).collect(),
+                fin.map(Into::into),
+            )
+        };
+
+        assert!(segments(Some("jo"), &["n", "d"], Some("oe")).matches(&entry));
+        assert!(!segments(None, &["doe", "john"], None).matches(&entry));
+        assert!(!segments(Some("john"), &[], Some("n doe")).matches(&entry));
+    }
+
+    #[test]
+    fn test_matches_combinators_and_ordering() {
+        let entry = sample_entry();
+        let filter =
+            LdapFilter::parse("(&(objectClass=person)(|(uid=a*)(mail=*@example.com)))").unwrap();
+        assert!(!filter.matches(&entry));
+
+        let filter =
+            LdapFilter::parse("(&(objectClass=inetOrgPerson)(|(uid=a*)(mail=*@example.com)))")
+                .unwrap();
+        assert!(filter.matches(&entry));
+
+        assert!(LdapFilter::gte("uidNumber", "1000").matches(&entry));
+        assert!(LdapFilter::lte("uidNumber", "2000").matches(&entry));
+        // Lexical, not numeric: "1500" < "900".
+        assert!(!LdapFilter::gte("uidNumber", "900").matches(&entry));
+
+        assert!(LdapFilter::raw("(uid=jdoe)").matches(&entry));
+        assert!(!LdapFilter::raw("uid=jdoe").matches(&entry));
+    }
+
+    #[test]
+    fn test_generalized_time() {
+        let dt = OffsetDateTime::from_unix_timestamp(1_704_067_200).unwrap();
+        assert_eq!(generalized_time(dt), "20240101000000Z");
+
+        // 2024-03-05 07:08:09.5 at +02:00 is 05:08:09 UTC.
+        let dt = OffsetDateTime::from_unix_timestamp_nanos(1_709_622_489_500_000_000)
+            .unwrap()
+            .to_offset(UtcOffset::from_hms(2, 0, 0).unwrap());
+        assert_eq!(dt.hour(), 9);
+        assert_eq!(generalized_time(dt), "20240305070809Z");
+    }
+
+    #[test]
+    fn test_parse_generalized_time() {
+        let dt = parse_generalized_time("20240101000000Z").unwrap();
+        assert_eq!(dt.unix_timestamp(), 1_704_067_200);
+
+        let dt = parse_generalized_time("20240305070809.5Z").unwrap();
+        assert_eq!(dt.unix_timestamp_nanos(), 1_709_622_489_500_000_000);
+        let dt = parse_generalized_time("20240305090809+0200").unwrap();
+        assert_eq!(dt.unix_timestamp(), 1_709_622_489);
+        assert_eq!(generalized_time(dt), "20240305070809Z");
+
+        for invalid in [
+            "",
+            "202401010000Z",
+            "20240101000000",
+            "20241301000000Z",
+            "20240101000000+02",
+            "2024010100000aZ",
+            "20240101000000.Z",
+        ] {
+            assert!(parse_generalized_time(invalid).is_none(), "{}", invalid);
+        }
+    }
+
+    #[test]
+    fn test_time_range() {
+        let to = OffsetDateTime::from_unix_timestamp(1_709_622_489).unwrap();
+        let from = to - time::Duration::days(30);
+
+        let filter = FilterBuilder::new()
+            .object_class("inetOrgPerson")
+            .time_range("createTimestamp", from, to)
+            .build_and();
+        assert_eq!(
+            filter.to_string(),
+            "(&(objectClass=inetOrgPerson)\
+             (&(createTimestamp>=20240204070809Z)(createTimestamp<=20240305070809Z)))"
+        );
+    }
+
+    #[test]
+    fn test_extensible_match_display() {
+        assert_eq!(
+            LdapFilter::extensible(Some("cn"), Some("caseExactMatch"), "John", false).to_string(),
+            "(cn:caseExactMatch:=John)"
+        );
+        assert_eq!(
+            LdapFilter::extensible(Some("uid"), None, "foo", true).to_string(),
+            "(uid:dn:=foo)"
+        );
+        assert_eq!(
+            LdapFilter::extensible(None, Some("2.4.6.8.10"), "Dino*", true).to_string(),
+            "(:dn:2.4.6.8.10:=Dino\\2a)"
+        );
+    }
+
+    #[test]
+    fn test_extensible_match_parse() {
+        for input in [
+            "(cn:caseExactMatch:=John)",
+            "(uid:dn:=foo)",
+            "(:dn:2.4.6.8.10:=Dino\\2a)",
+            "(o:dn:caseIgnoreMatch:=Example)",
+        ] {
+            let parsed = LdapFilter::parse(input).unwrap();
+            assert!(matches!(parsed, LdapFilter::ExtensibleMatch { .. }));
+            assert_eq!(parsed.to_string(), input);
+        }
+        assert_eq!(
+            LdapFilter::parse("(:dn:2.4.6.8.10:=x)").unwrap(),
+            LdapFilter::extensible(None, Some("2.4.6.8.10"), "x", true)
+        );
+
+        assert!(LdapFilter::parse("(:=x)").is_err());
+        assert!(LdapFilter::parse("(:dn:=x)").is_err());
+        assert!(LdapFilter::parse("(cn:rule=x)").is_err());
+        assert!(LdapFilter::parse("(cn:=a*)").is_err());
+    }
+
+    #[test]
+    fn test_extensible_match_matches() {
+        let entry = sample_entry();
+
+        assert!(
+            LdapFilter::extensible(Some("cn"), Some("caseExactMatch"), "John Doe", false)
+                .matches(&entry)
+        );
+        assert!(
+            !LdapFilter::extensible(Some("cn"), Some("caseExactMatch"), "john doe", false)
+                .matches(&entry)
+        );
+        assert!(
+            LdapFilter::extensible(None, Some("caseIgnoreMatch"), "JDOE", false).matches(&entry)
+        );
+        assert!(LdapFilter::extensible(Some("ou"), None, "users", true).matches(&entry));
+        assert!(!LdapFilter::extensible(Some("ou"), None, "users", false).matches(&entry));
+        assert!(
+            !LdapFilter::extensible(Some("cn"), Some("1.2.3.4"), "John Doe", false).matches(&entry)
+        );
+    }
+
+    #[test]
+    fn test_attributes() {
+        let filter =
+            LdapFilter::parse("(&(objectClass=person)(|(uid=a*)(mail=*@x.com))(!(UID=root)))")
+                .unwrap();
+        assert_eq!(filter.attributes(), vec!["objectClass", "uid", "mail"]);
+
+        let filter = LdapFilter::or(vec![
+            LdapFilter::extensible(Some("cn"), Some("caseExactMatch"), "x", false),
+            LdapFilter::extensible(None, Some("2.4.6.8.10"), "y", true),
+            LdapFilter::raw("(&(sn>=a)(givenName~=b))"),
+            LdapFilter::raw("not a filter"),
+        ]);
+        assert_eq!(filter.attributes(), vec!["cn", "sn", "givenName"]);
+    }
+
+    #[test]
+    fn test_parse_rejects_deep_nesting() {
+        let deep = format!("{}(a=1){}", "(&".repeat(1000), ")".repeat(1000));
+        let err = LdapFilter::parse(&deep).unwrap_err();
+        assert!(matches!(err, HeraclesError::InvalidFilter(_)));
+        assert!(err.to_string().contains("at byte 100"), "{}", err);
+
+        let at_limit = format!("{}(a=1){}", "(!".repeat(49), ")".repeat(49));
+        assert!(LdapFilter::parse(&at_limit).is_ok());
+        assert!(LdapFilter::parse_with_max_depth(&at_limit, 49).is_err());
+    }
+
+    #[test]
+    fn test_validate_limits() {
+        let deep = (0..1000).fold(LdapFilter::eq("a", "1"), |inner, _| {
+            LdapFilter::and(vec![inner])
+        });
+        assert!(matches!(
+            deep.validate(DEFAULT_MAX_FILTER_DEPTH, usize::MAX),
+            Err(HeraclesError::InvalidFilter(_))
+        ));
+        assert!(deep.validate(1001, 1001).is_ok());
+
+        let wide = LdapFilter::or(
+            (0..10)
+                .map(|i| LdapFilter::eq("uid", i.to_string()))
+                .collect(),
+        );
+        assert!(wide.validate(2, 11).is_ok());
+        assert!(wide.validate(2, 10).is_err());
+        assert!(wide.validate(1, 100).is_err());
+    }
+
+    #[test]
+    fn test_simplify_collapses_nesting() {
+        let simplified = LdapFilter::parse("(&(&(a=1)))").unwrap().simplify();
+        assert_eq!(simplified, LdapFilter::eq("a", "1"));
+        assert_eq!(simplified.to_string(), "(a=1)");
+
+        let simplified = LdapFilter::parse("(&(a=1)(&(b=2)(|(c=3)))(!(!(d=4))))")
+            .unwrap()
+            .simplify();
+        assert_eq!(simplified.to_string(), "(&(a=1)(b=2)(c=3)(d=4))");
+
+        // Different combinators don't merge.
+        let filter = LdapFilter::parse("(|(a=1)(&(b=2)(c=3)))").unwrap();
+        assert_eq!(filter.clone().simplify(), filter);
+    }
+
+    #[test]
+    fn test_simplify_dedup_keeps_order() {
+        let filter = FilterBuilder::new()
+            .eq("z", "1")
+            .filter(FilterBuilder::new().eq("a", "2").eq("z", "1").build_and())
+            .eq("m", "3")
+            .eq("a", "2")
+            .build_and();
+        assert_eq!(filter.simplify().to_string(), "(&(z=1)(a=2)(m=3))");
+
+        assert_eq!(
+            LdapFilter::parse("(|(a=1)(a=1))").unwrap().simplify(),
+            LdapFilter::eq("a", "1")
+        );
+        assert_eq!(LdapFilter::and(vec![]).simplify(), LdapFilter::and(vec![]));
+        assert_eq!(
+            LdapFilter::parse("(!(|(a=1)))")
+                .unwrap()
+                .simplify()
+                .to_string(),
+            "(!(a=1))"
+        );
+    }
+
+    #[test]
+    fn test_parse_round_trip() {
+        let input = "(&(objectClass=person)(|(uid=a*)(mail=*@x.com)))";
+        let filter = LdapFilter::parse(input).unwrap();
+
+        assert_eq!(
+            filter,
+            LdapFilter::and(vec![
+                LdapFilter::eq("objectClass", "person"),
+                LdapFilter::or(vec![
+                    LdapFilter::starts_with("uid", "a"),
+                    LdapFilter::ends_with("mail", "@x.com"),
+                ]),
+            ])
+        );
+        assert_eq!(filter.to_string(), input);
+    }
+
+    #[test]
+    fn test_parse_item_types() {
+        let cases = [
+            ("(mail=*)", LdapFilter::present("mail")),
+            ("(cn=*jo*)", LdapFilter::contains("cn", "jo")),
+            (
+                "(cn=a*b*c*d)",
+                LdapFilter::Substring(
+                    "cn".into(),
+                    Some("a".into()),
+                    vec!["b".into(), "c".into()],
+                    Some("d".into()),
+                ),
+            ),
+            ("(uidNumber>=1000)", LdapFilter::gte("uidNumber", "1000")),
+            ("(uidNumber<=2000)", LdapFilter::lte("uidNumber", "2000")),
+            ("(sn~=smith)", LdapFilter::approx("sn", "smith")),
+            (
+                "(!(uid=root))",
+                LdapFilter::not(LdapFilter::eq("uid", "root")),
+            ),
+            ("(cn;lang-en=x)", LdapFilter::eq("cn;lang-en", "x")),
+            ("(2.5.4.3=x)", LdapFilter::eq("2.5.4.3", "x")),
+            ("(&)", LdapFilter::and(vec![])),
+        ];
+        for (input, expected) in cases {
+            let parsed = LdapFilter::parse(input).unwrap();
+            assert_eq!(parsed, expected, "{}", input);
+            assert_eq!(parsed.to_string(), input);
+        }
+    }
+
+    #[test]
+    fn test_parse_unescapes_values() {
+        let filter = LdapFilter::parse("(cn=Test \\28User\\29 \\2a\\c3\\a9)").unwrap();
+        assert_eq!(filter, LdapFilter::eq("cn", "Test (User) *\u{e9}"));
+        assert_eq!(filter.to_string(), "(cn=Test \\28User\\29 \\2a\\c3\\a9)");
+    }
+
+    #[test]
+    fn test_parse_errors_report_offset() {
+        let cases = [
+            ("", 0),
+            ("uid=x", 0),
+            ("(uid=x", 6),
+            ("(uid=x))", 7),
+            ("(=x)", 1),
+            ("(uid=a(b)", 6),
+            ("(uid=\\zz)", 5),
+            ("(uid=\\+1)", 5),
+            ("(uid=\\-f)", 5),
+            ("(uid>=a*)", 8),
+            ("(cn=a**b)", 8),
+            ("(&(uid=x)y)", 9),
+        ];
+        for (input, offset) in cases {
+            let err = LdapFilter::parse(input).unwrap_err();
+            assert!(matches!(err, HeraclesError::InvalidFilter(_)), "{}", input);
+            assert!(
+                err.to_string().ends_with(&format!("at byte {}", offset)),
+                "{}: {}",
+                input,
+                err
+            );
+        }
+    }
+
     #[test]
     fn test_equality_filter() {
         let filter = LdapFilter::eq("uid", "testuser");
@@ -413,6 +1462,69 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_pattern_by_object_classes() {
+        assert_eq!(
+            patterns::by_object_classes(&["posixAccount", "shadowAccount"], true).to_string(),
+            "(&(objectClass=posixAccount)(objectClass=shadowAccount))"
+        );
+        assert_eq!(
+            patterns::by_object_classes(&["posixAccount", "shadowAccount"], false).to_string(),
+            "(|(objectClass=posixAccount)(objectClass=shadowAccount))"
+        );
+        assert_eq!(
+            patterns::by_object_classes(&["posixGroup"], false).to_string(),
+            "(objectClass=posixGroup)"
+        );
+    }
+
+    #[test]
+    fn test_pattern_any_object_class() {
+        assert_eq!(
+            patterns::any_object_class(&["posixGroup", "groupOfNames"]).to_string(),
+            "(|(objectClass=posixGroup)(objectClass=groupOfNames))"
+        );
+
+        let filter = FilterBuilder::new()
+            .any_object_class(&["posixGroup", "groupOfNames"])
+            .eq("cn", "admins")
+            .build_and();
+        assert_eq!(
+            filter.to_string(),
+            "(&(|(objectClass=posixGroup)(objectClass=groupOfNames))(cn=admins))"
+        );
+    }
+
+    #[test]
+    fn test_pattern_system() {
+        assert_eq!(
+            patterns::system().to_string(),
+            "(|(objectClass=hrcServer)(objectClass=hrcWorkstation)(objectClass=hrcTerminal)\
+             (objectClass=hrcPrinter)(objectClass=hrcPhone)(objectClass=hrcMobilePhone))"
+        );
+    }
+
+    #[test]
+    fn test_pattern_self_entry() {
+        assert_eq!(
+            patterns::self_entry("john.doe", &["inetOrgPerson", "hrcAcl"]).to_string(),
+            "(&(objectClass=inetOrgPerson)(objectClass=hrcAcl)(uid=john.doe))"
+        );
+        assert_eq!(
+            patterns::self_entry("john.doe", &[]).to_string(),
+            "(uid=john.doe)"
+        );
+    }
+
+    #[test]
+    fn test_pattern_fd_acl() {
+        assert_eq!(
+            patterns::fd_acl_object().to_string(),
+            "(|(objectClass=gosaAcl)(objectClass=fdAclEntry))"
+        );
+        assert_eq!(patterns::fd_role().to_string(), "(objectClass=gosaRole)");
+    }
+
     #[test]
     fn test_greater_or_equal() {
         let filter = LdapFilter::gte("uidNumber", "1000");
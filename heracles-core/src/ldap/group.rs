@@ -0,0 +1,74 @@
+//! Group membership helpers.
+//!
+//! POSIX groups list members as bare uids in `memberUid`, while
+//! `groupOfNames` lists full DNs in `member`. These helpers pick the right
+//! form for a given group entry.
+
+use crate::ldap::dn::DistinguishedName;
+use crate::ldap::operations::LdapEntry;
+
+/// Returns true if members of `group` are referenced by bare uid.
+///
+/// That is the case for a `posixGroup` that is not also a `groupOfNames`
+/// (RFC 2307bis groups carry both and use DNs).
+pub fn uses_bare_uid(group: &LdapEntry) -> bool {
+    group.has_object_class("posixGroup") && !group.has_object_class("groupOfNames")
+}
+
+/// Returns the value identifying `member_dn` as a member of `group`.
+///
+/// For a POSIX group this is the `uid` of the member DN's RDN, e.g. `jdoe`
+/// for `uid=jdoe,ou=users,dc=example,dc=com` or
+/// `cn=John+uid=jdoe,ou=users,dc=example,dc=com` (machine accounts keep
+/// their trailing `$`); an RDN without a `uid` falls back to its value.
+/// Otherwise the full DN is returned unchanged, as is a DN that can't be
+/// parsed.
+pub fn member_value_for(group: &LdapEntry, member_dn: &str) -> String {
+    if !uses_bare_uid(group) {
+        return member_dn.to_string();
+    }
+
+    DistinguishedName::parse(member_dn)
+        .ok()
+        .and_then(|dn| {
+            dn.rdn()
+                .map(|rdn| rdn.value_of("uid").unwrap_or(&rdn.attr_value).to_string())
+        })
+        .unwrap_or_else(|| member_dn.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_member_value_posix_group() {
+        let group = LdapEntry::new("cn=devs,ou=groups,dc=example,dc=com")
+            .with_attribute("objectClass", vec!["top", "posixGroup"]);
+
+        assert_eq!(
+            member_value_for(&group, "uid=jdoe,ou=users,dc=example,dc=com"),
+            "jdoe"
+        );
+        assert_eq!(
+            member_value_for(&group, "uid=ws01$,ou=computers,dc=example,dc=com"),
+            "ws01$"
+        );
+        assert_eq!(
+            member_value_for(&group, "cn=John Doe+uid=jdoe,ou=users,dc=example,dc=com"),
+            "jdoe"
+        );
+    }
+
+    #[test]
+    fn test_member_value_group_of_names() {
+        let dn = "uid=jdoe,ou=users,dc=example,dc=com";
+        let group = LdapEntry::new("cn=devs,ou=groups,dc=example,dc=com")
+            .with_attribute("objectClass", vec!["top", "groupOfNames"]);
+        assert_eq!(member_value_for(&group, dn), dn);
+
+        let rfc2307bis = LdapEntry::new("cn=devs,ou=groups,dc=example,dc=com")
+            .with_attribute("objectClass", vec!["posixGroup", "groupOfNames"]);
+        assert_eq!(member_value_for(&rfc2307bis, dn), dn);
+    }
+}
@@ -0,0 +1,220 @@
+//! LDIF (RFC 2849) encoding for LDAP entries.
+
+use crate::errors::Result;
+use crate::ldap::operations::LdapEntry;
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use futures::{Stream, StreamExt};
+use ldap3::SearchEntry;
+
+/// Maximum line length before folding.
+const LINE_WIDTH: usize = 76;
+
+/// Header preceding the records of an LDIF file.
+pub const LDIF_VERSION_HEADER: &str = "version: 1\n\n";
+
+/// Encodes an entry as an LDIF record, terminated by a blank line.
+///
+/// `objectClass` is written first, remaining attributes in name order.
+/// Binary attributes are included, base64-encoded.
+pub fn entry_to_ldif(entry: &LdapEntry) -> String {
+    let text = entry.attributes.iter().map(|(name, values)| {
+        (
+            name.as_str(),
+            values.iter().map(|v| v.as_bytes()).collect::<Vec<_>>(),
+        )
+    });
+    let binary = entry.bin_attributes.iter().map(|(name, values)| {
+        (
+            name.as_str(),
+            values.iter().map(|v| v.as_slice()).collect::<Vec<_>>(),
+        )
+    });
+    encode_record(&entry.dn, text.chain(binary).collect())
+}
+
+/// Encodes a search entry, including binary-valued attributes.
+pub(crate) fn search_entry_to_ldif(entry: &SearchEntry) -> String {
+    let text = entry.attrs.iter().map(|(name, values)| {
+        (
+            name.as_str(),
+            values.iter().map(|v| v.as_bytes()).collect::<Vec<_>>(),
+        )
+    });
+    let binary = entry.bin_attrs.iter().map(|(name, values)| {
+        (
+            name.as_str(),
+            values.iter().map(|v| v.as_slice()).collect::<Vec<_>>(),
+        )
+    });
+    encode_record(&entry.dn, text.chain(binary).collect())
+}
+
+/// Maps a stream of search entries to LDIF records, preceded by the
+/// version header.
+pub(crate) fn ldif_records<S>(entries: S) -> impl Stream<Item = Result<String>>
+where
+    S: Stream<Item = Result<SearchEntry>>,
+{
+    futures::stream::once(async { Ok(LDIF_VERSION_HEADER.to_string()) })
+        .chain(entries.map(|entry| entry.map(|e| search_entry_to_ldif(&e))))
+}
+
+fn encode_record(dn: &str, mut attrs: Vec<(&str, Vec<&[u8]>)>) -> String {
+    attrs.sort_by(|(a, _), (b, _)| {
+        let a_oc = a.eq_ignore_ascii_case("objectClass");
+        let b_oc = b.eq_ignore_ascii_case("objectClass");
+        b_oc.cmp(&a_oc).then_with(|| a.cmp(b))
+    });
+
+    let mut out = String::new();
+    push_line(&mut out, "dn", dn.as_bytes());
+    for (name, values) in attrs {
+        for value in values {
+            push_line(&mut out, name, value);
+        }
+    }
+    out.push('\n');
+    out
+}
+
+/// Appends `name: value` (or `name:: base64` when not a SAFE-STRING), folded.
+fn push_line(out: &mut String, name: &str, value: &[u8]) {
+    let line = match std::str::from_utf8(value) {
+        Ok(s) if is_safe_string(s) => format!("{}: {}", name, s),
+        _ => format!("{}:: {}", name, BASE64.encode(value)),
+    };
+
+    let mut chars = line.chars().peekable();
+    let mut width = 0;
+    let mut first = true;
+    while let Some(c) = chars.next() {
+        out.push(c);
+        width += 1;
+        let limit = if first { LINE_WIDTH } else { LINE_WIDTH - 1 };
+        if width == limit && chars.peek().is_some() {
+            out.push_str("\n ");
+            width = 0;
+            first = false;
+        }
+    }
+    out.push('\n');
+}
+
+/// RFC 2849 SAFE-STRING: ASCII without NUL/CR/LF, not starting with
+/// space, ':' or '<', and (by convention) not ending with a space.
+fn is_safe_string(s: &str) -> bool {
+    if s.is_empty() {
+        return true;
+    }
+    let bytes = s.as_bytes();
+    !matches!(bytes[0], b' ' | b':' | b'<')
+        && bytes[bytes.len() - 1] != b' '
+        && bytes
+            .iter()
+            .all(|&b| b.is_ascii() && b != 0 && b != b'\r' && b != b'\n')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn search_entry(dn: &str, attrs: &[(&str, &[&str])]) -> SearchEntry {
+        SearchEntry {
+            dn: dn.to_string(),
+            attrs: attrs
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.iter().map(|s| s.to_string()).collect()))
+                .collect(),
+            bin_attrs: HashMap::new(),
+        }
+    }
+
+    #[test]
+    fn test_entry_to_ldif() {
+        let entry = LdapEntry::new("uid=john,ou=users,dc=example,dc=com")
+            .with_single("uid", "john")
+            .with_attribute("objectClass", vec!["top", "inetOrgPerson"])
+            .with_single("cn", "John Doe");
+
+        assert_eq!(
+            entry_to_ldif(&entry),
+            "dn: uid=john,ou=users,dc=example,dc=com\n\
+             objectClass: top\n\
+             objectClass: inetOrgPerson\n\
+             cn: John Doe\n\
+             uid: john\n\n"
+        );
+    }
+
+    #[test]
+    fn test_entry_to_ldif_binary() {
+        let entry = LdapEntry::new("uid=john,dc=example,dc=com")
+            .with_single("uid", "john")
+            .with_binary("jpegPhoto", vec![vec![0xff, 0xd8, 0xff]]);
+
+        assert_eq!(
+            entry_to_ldif(&entry),
+            format!(
+                "dn: uid=john,dc=example,dc=com
+jpegPhoto:: {}
+uid: john
+
+",
+                BASE64.encode([0xff, 0xd8, 0xff])
+            )
+        );
+    }
+
+    #[test]
+    fn test_unsafe_values_base64() {
+        let entry = LdapEntry::new("cn=José,dc=example,dc=com").with_single("description", " lead");
+        let ldif = entry_to_ldif(&entry);
+
+        assert!(ldif.starts_with(&format!(
+            "dn:: {}\n",
+            BASE64.encode("cn=José,dc=example,dc=com")
+        )));
+        assert!(ldif.contains(&format!("description:: {}\n", BASE64.encode(" lead"))));
+    }
+
+    #[test]
+    fn test_binary_and_folding() {
+        let mut entry = search_entry("uid=john,dc=example,dc=com", &[]);
+        entry
+            .bin_attrs
+            .insert("jpegPhoto".to_string(), vec![vec![0xff; 100]]);
+        let ldif = search_entry_to_ldif(&entry);
+
+        let lines: Vec<&str> = ldif.lines().collect();
+        assert!(lines[1].starts_with("jpegPhoto:: "));
+        assert!(lines.iter().all(|l| l.len() <= LINE_WIDTH));
+        assert!(lines[2].starts_with(' '));
+
+        // Unfolding restores the original value.
+        let unfolded = ldif.replace("\n ", "");
+        let encoded = unfolded.lines().nth(1).unwrap();
+        assert_eq!(
+            encoded,
+            format!("jpegPhoto:: {}", BASE64.encode([0xff; 100]))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_ldif_records_stream() {
+        let entries = futures::stream::iter(vec![
+            Ok(search_entry("uid=a,dc=example,dc=com", &[("uid", &["a"])])),
+            Ok(search_entry("uid=b,dc=example,dc=com", &[("uid", &["b"])])),
+        ]);
+
+        let records: Vec<String> = ldif_records(entries).map(|r| r.unwrap()).collect().await;
+
+        assert_eq!(records.len(), 3);
+        assert_eq!(
+            records.concat(),
+            "version: 1\n\n\
+             dn: uid=a,dc=example,dc=com\nuid: a\n\n\
+             dn: uid=b,dc=example,dc=com\nuid: b\n\n"
+        );
+    }
+}
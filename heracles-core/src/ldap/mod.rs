@@ -2,9 +2,15 @@
 //!
 //! This module provides LDAP connection pooling and operations:
 //! - Connection management with deadpool-based pooling
+//! - Login flow (user bind followed by ACL compilation)
 //! - DN parsing, escaping, and manipulation
 //! - Filter building with proper escaping
+//! - Server-side sorting of search results
+//! - Referral chasing across servers
+//! - DNS SRV discovery of servers (`srv` feature)
+//! - Group membership value forms (memberUid vs member)
 //! - CRUD operations on LDAP entries
+//! - LDIF export
 //!
 //! # Example
 //!
@@ -33,23 +39,46 @@
 //! # }
 //! ```
 
+pub mod auth;
 pub mod config;
 pub mod connection;
 pub mod dn;
 pub mod filter;
+pub mod group;
+pub mod ldif;
 pub mod operations;
 pub mod pool;
+pub mod ppolicy;
+pub mod referral;
+pub mod result_code;
+pub mod sort;
+#[cfg(feature = "srv")]
+pub mod srv;
+#[cfg(test)]
+mod test_server;
+mod tls;
 
 // Re-export main types
-pub use config::LdapConfig;
-pub use connection::LdapConnection;
+pub use config::{LdapConfig, TlsConfig};
+pub use connection::{AuthOutcome, ConnInfo, LdapConnection};
 pub use dn::{
     escape_dn_value, escape_filter_value, unescape_dn_value, DistinguishedName, DnBuilder,
     RdnComponent,
 };
-pub use filter::{patterns, FilterBuilder, LdapFilter};
-pub use operations::{LdapEntry, LdapModification, SearchBuilder, SearchScope};
+pub use filter::{
+    generalized_time, parse_generalized_time, patterns, FilterBuilder, LdapFilter,
+    DEFAULT_MAX_FILTER_DEPTH,
+};
+pub use ldif::{entry_to_ldif, LDIF_VERSION_HEADER};
+pub use operations::{
+    validate_attribute_name, ChangeSet, LdapEntry, LdapModification, SearchBuilder, SearchScope,
+    CONTAINER_CLASSES,
+};
 pub use pool::{
-    create_pool, create_pool_from_env, LdapPool, LdapPoolBuilder, LdapPoolExt, PoolStatus,
-    PooledConnection,
+    create_pool, create_pool_from_env, spawn_keepalive, warm_up, CircuitBreaker,
+    CircuitBreakerConfig, CircuitState, KeepaliveConfig, LdapPool, LdapPoolBuilder, LdapPoolExt,
+    Ping, PoolMetrics, PoolStatus, PooledConnection, ReadWriteRouter, ReplicatedPool,
 };
+pub use ppolicy::{PasswordPolicyError, PasswordPolicyInfo};
+pub use referral::LdapUrl;
+pub use result_code::LdapResultCode;
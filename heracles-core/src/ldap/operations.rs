@@ -1,6 +1,13 @@
 //! LDAP operations data structures.
 
-use std::collections::HashMap;
+use crate::errors::{HeraclesError, Result};
+use crate::ldap::filter::parse_generalized_time;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use time::OffsetDateTime;
+
+/// Object classes treated as containers by [`LdapEntry::is_container_by_class`].
+pub const CONTAINER_CLASSES: &[&str] = &["organizationalUnit", "container", "domain"];
 
 /// Represents an LDAP entry with DN and attributes.
 #[derive(Debug, Clone, PartialEq)]
@@ -9,6 +16,10 @@ pub struct LdapEntry {
     pub dn: String,
     /// Attributes and their values.
     pub attributes: HashMap<String, Vec<String>>,
+    /// Attributes whose values aren't valid UTF-8 (e.g. `jpegPhoto`,
+    /// `objectSid`) or were requested with the `;binary` option, keyed by
+    /// the name the server returned.
+    pub bin_attributes: HashMap<String, Vec<Vec<u8>>>,
 }
 
 impl LdapEntry {
@@ -17,9 +28,16 @@ impl LdapEntry {
         Self {
             dn: dn.into(),
             attributes: HashMap::new(),
+            bin_attributes: HashMap::new(),
         }
     }
 
+    /// Adds a binary attribute with multiple values.
+    pub fn with_binary(mut self, name: impl Into<String>, values: Vec<Vec<u8>>) -> Self {
+        self.bin_attributes.insert(name.into(), values);
+        self
+    }
+
     /// Adds an attribute with multiple values.
     pub fn with_attribute(
         mut self,
@@ -45,11 +63,56 @@ impl LdapEntry {
             .map(|s| s.as_str())
     }
 
+    /// Gets the first value of an attribute as an integer.
+    ///
+    /// Returns `None` if the attribute is missing or not an integer.
+    pub fn get_i64(&self, attr: &str) -> Option<i64> {
+        self.get_first(attr)?.parse().ok()
+    }
+
+    /// Gets the first value of a Boolean attribute (`TRUE` or `FALSE`).
+    ///
+    /// Returns `None` if the attribute is missing or holds anything else.
+    pub fn get_bool(&self, attr: &str) -> Option<bool> {
+        match self.get_first(attr)? {
+            "TRUE" => Some(true),
+            "FALSE" => Some(false),
+            _ => None,
+        }
+    }
+
+    /// Gets the first value of a GeneralizedTime attribute.
+    ///
+    /// Returns `None` if the attribute is missing or not a valid time; see
+    /// [`parse_generalized_time`] for the accepted forms.
+    pub fn get_time(&self, attr: &str) -> Option<OffsetDateTime> {
+        parse_generalized_time(self.get_first(attr)?)
+    }
+
     /// Gets all values of an attribute.
     pub fn get_all(&self, attr: &str) -> Option<&Vec<String>> {
         self.attributes.get(attr)
     }
 
+    /// Gets the first value of a binary attribute.
+    ///
+    /// See [`get_all_binary`](Self::get_all_binary) for how `attr` is looked up.
+    pub fn get_binary(&self, attr: &str) -> Option<&[u8]> {
+        self.get_all_binary(attr)
+            .and_then(|v| v.first())
+            .map(|v| v.as_slice())
+    }
+
+    /// Gets all values of a binary attribute.
+    ///
+    /// `attr` is looked up as given, then with the `;binary` option, so
+    /// `userCertificate` also finds `userCertificate;binary`.
+    pub fn get_all_binary(&self, attr: &str) -> Option<&Vec<Vec<u8>>> {
+        self.bin_attributes
+            .get(attr)
+            .or_else(|| self.bin_attributes.get(&format!("{};binary", attr)))
+    }
+
     /// Checks if the entry has a specific objectClass.
     pub fn has_object_class(&self, object_class: &str) -> bool {
         self.attributes
@@ -58,14 +121,115 @@ impl LdapEntry {
             .unwrap_or(false)
     }
 
+    /// Returns true if the entry has one of [`CONTAINER_CLASSES`], i.e. is
+    /// expected to hold children.
+    ///
+    /// This is a cheap guess from the objectClass alone; use
+    /// [`LdapConnection::is_container`](crate::ldap::LdapConnection::is_container)
+    /// to check for actual children.
+    pub fn is_container_by_class(&self) -> bool {
+        self.is_container_by_classes(CONTAINER_CLASSES)
+    }
+
+    /// Same as [`is_container_by_class`](Self::is_container_by_class) with
+    /// a custom set of container object classes.
+    pub fn is_container_by_classes(&self, classes: &[&str]) -> bool {
+        classes.iter().any(|class| self.has_object_class(class))
+    }
+
     /// Returns the RDN (first component of the DN).
     pub fn rdn(&self) -> Option<&str> {
         self.dn.split(',').next()
     }
+
+    /// Checks whether both entries hold the same attributes and values.
+    ///
+    /// Attribute names match case-insensitively, and the order of attributes
+    /// and values is ignored, as are duplicate values and attributes without
+    /// values. Values of attributes named in `case_ignore_attrs` (matched
+    /// case-insensitively) compare ignoring case; all others compare exactly.
+    /// DNs are not compared.
+    pub fn semantically_equals(
+        &self,
+        other: &LdapEntry,
+        case_ignore_attrs: &HashSet<String>,
+    ) -> bool {
+        let case_ignore: HashSet<String> =
+            case_ignore_attrs.iter().map(|a| a.to_lowercase()).collect();
+        self.normalized_attributes(&case_ignore) == other.normalized_attributes(&case_ignore)
+    }
+
+    fn normalized_attributes(
+        &self,
+        case_ignore: &HashSet<String>,
+    ) -> BTreeMap<String, BTreeSet<String>> {
+        let mut normalized: BTreeMap<String, BTreeSet<String>> = BTreeMap::new();
+        for (name, values) in &self.attributes {
+            if values.is_empty() {
+                continue;
+            }
+            let name = name.to_lowercase();
+            let fold = case_ignore.contains(&name);
+            normalized
+                .entry(name)
+                .or_default()
+                .extend(
+                    values
+                        .iter()
+                        .map(|v| if fold { v.to_lowercase() } else { v.clone() }),
+                );
+        }
+        normalized
+    }
+}
+
+impl From<ldap3::SearchEntry> for LdapEntry {
+    fn from(entry: ldap3::SearchEntry) -> Self {
+        Self {
+            dn: entry.dn,
+            attributes: entry.attrs.into_iter().collect(),
+            bin_attributes: entry.bin_attrs.into_iter().collect(),
+        }
+    }
+}
+
+/// Checks that `name` is a valid RFC 4512 attribute description.
+///
+/// The attribute type is either a descriptor (a letter followed by letters,
+/// digits and hyphens) or a numeric OID, optionally followed by `;option`
+/// suffixes made of letters, digits and hyphens, as in `cn;lang-en`.
+pub fn validate_attribute_name(name: &str) -> Result<()> {
+    let mut parts = name.split(';');
+    let attr_type = parts.next().unwrap_or_default();
+
+    let is_keychar = |c: char| c.is_ascii_alphanumeric() || c == '-';
+    let is_descr = attr_type.starts_with(|c: char| c.is_ascii_alphabetic())
+        && attr_type.chars().all(is_keychar);
+    let is_oid = attr_type.split('.').count() > 1
+        && attr_type
+            .split('.')
+            .all(|n| !n.is_empty() && n.chars().all(|c| c.is_ascii_digit()));
+    let options_ok = parts.all(|o| !o.is_empty() && o.chars().all(is_keychar));
+
+    if (is_descr || is_oid) && options_ok {
+        Ok(())
+    } else {
+        Err(HeraclesError::SchemaValidation(format!(
+            "invalid attribute name: {:?}",
+            name
+        )))
+    }
 }
 
 /// Represents an LDAP modification operation.
-#[derive(Debug, Clone)]
+///
+/// Serializes as an internally tagged object, e.g.
+/// `{"op":"replace","attr":"mail","values":["a@example.com"]}`, with `op`
+/// one of `add`, `delete` or `replace`; `increment` carries `by` instead of
+/// `values`. This format is stable so queued writes and audit records stay
+/// readable across releases.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "lowercase")]
 pub enum LdapModification {
     /// Add values to an attribute.
     Add { attr: String, values: Vec<String> },
@@ -73,6 +237,8 @@ pub enum LdapModification {
     Delete { attr: String, values: Vec<String> },
     /// Replace all values of an attribute.
     Replace { attr: String, values: Vec<String> },
+    /// Add `by` to an integer attribute on the server (RFC 4525).
+    Increment { attr: String, by: i64 },
 }
 
 impl LdapModification {
@@ -116,22 +282,193 @@ impl LdapModification {
         }
     }
 
+    /// Creates an Increment modification.
+    pub fn increment(attr: impl Into<String>, by: i64) -> Self {
+        Self::Increment {
+            attr: attr.into(),
+            by,
+        }
+    }
+
+    /// Returns the attribute this modification targets.
+    pub fn attr(&self) -> &str {
+        match self {
+            LdapModification::Add { attr, .. }
+            | LdapModification::Delete { attr, .. }
+            | LdapModification::Replace { attr, .. }
+            | LdapModification::Increment { attr, .. } => attr,
+        }
+    }
+
     /// Converts to ldap3 Mod type.
-    pub(crate) fn to_ldap3_mod(&self) -> ldap3::Mod<&str> {
+    pub(crate) fn to_ldap3_mod(&self) -> ldap3::Mod<String> {
+        let vals = |values: &[String]| values.iter().cloned().collect::<HashSet<_>>();
         match self {
-            LdapModification::Add { attr, values } => {
-                let vals: Vec<&str> = values.iter().map(|s| s.as_str()).collect();
-                ldap3::Mod::Add(attr.as_str(), std::collections::HashSet::from_iter(vals))
-            }
+            LdapModification::Add { attr, values } => ldap3::Mod::Add(attr.clone(), vals(values)),
             LdapModification::Delete { attr, values } => {
-                let vals: Vec<&str> = values.iter().map(|s| s.as_str()).collect();
-                ldap3::Mod::Delete(attr.as_str(), std::collections::HashSet::from_iter(vals))
+                ldap3::Mod::Delete(attr.clone(), vals(values))
             }
             LdapModification::Replace { attr, values } => {
-                let vals: Vec<&str> = values.iter().map(|s| s.as_str()).collect();
-                ldap3::Mod::Replace(attr.as_str(), std::collections::HashSet::from_iter(vals))
+                ldap3::Mod::Replace(attr.clone(), vals(values))
+            }
+            LdapModification::Increment { attr, by } => {
+                ldap3::Mod::Increment(attr.clone(), by.to_string())
+            }
+        }
+    }
+
+    /// Returns whether this is an [`Increment`](Self::Increment).
+    pub fn is_increment(&self) -> bool {
+        matches!(self, LdapModification::Increment { .. })
+    }
+}
+
+/// Pending change to one attribute in a [`ChangeSet`].
+#[derive(Debug, Clone)]
+enum AttrChange {
+    /// Replace all values (empty removes the attribute).
+    Replace(Vec<String>),
+    /// Values to add and remove.
+    Delta {
+        add: Vec<String>,
+        remove: Vec<String>,
+    },
+}
+
+/// Accumulates changes to one entry and flushes them as a single modify.
+///
+/// Changes are coalesced per attribute (names match case-insensitively):
+/// a `set` discards earlier changes to the attribute, adds and removes are
+/// folded into a pending `set`, and adding then removing a value (or the
+/// reverse) cancels out.
+///
+/// # Example
+///
+/// ```rust
+/// use heracles_core::ldap::ChangeSet;
+///
+/// let mut changes = ChangeSet::new("uid=jdoe,ou=users,dc=example,dc=com");
+/// changes.set("mail", vec!["old@example.com"]);
+/// changes.set("mail", vec!["jdoe@example.com"]);
+/// changes.add("memberOf", vec!["cn=devs"]).remove("memberOf", vec!["cn=devs"]);
+/// assert_eq!(changes.modifications().len(), 1);
+/// ```
+#[derive(Debug, Clone)]
+pub struct ChangeSet {
+    dn: String,
+    changes: Vec<(String, AttrChange)>,
+}
+
+impl ChangeSet {
+    /// Creates an empty change set for the entry at `dn`.
+    pub fn new(dn: impl Into<String>) -> Self {
+        Self {
+            dn: dn.into(),
+            changes: Vec::new(),
+        }
+    }
+
+    /// Returns the DN of the entry being changed.
+    pub fn dn(&self) -> &str {
+        &self.dn
+    }
+
+    /// Replaces all values of `attr`, discarding earlier changes to it.
+    pub fn set(&mut self, attr: &str, values: Vec<impl Into<String>>) -> &mut Self {
+        *self.change_mut(attr) = AttrChange::Replace(values.into_iter().map(Into::into).collect());
+        self
+    }
+
+    /// Removes `attr` entirely.
+    pub fn clear(&mut self, attr: &str) -> &mut Self {
+        *self.change_mut(attr) = AttrChange::Replace(Vec::new());
+        self
+    }
+
+    /// Adds values to `attr`.
+    pub fn add(&mut self, attr: &str, values: Vec<impl Into<String>>) -> &mut Self {
+        let change = self.change_mut(attr);
+        for value in values.into_iter().map(Into::into) {
+            match change {
+                AttrChange::Replace(current) => {
+                    if !current.contains(&value) {
+                        current.push(value);
+                    }
+                }
+                AttrChange::Delta { add, remove } => toggle(add, remove, value),
+            }
+        }
+        self
+    }
+
+    /// Removes values from `attr`.
+    pub fn remove(&mut self, attr: &str, values: Vec<impl Into<String>>) -> &mut Self {
+        let change = self.change_mut(attr);
+        for value in values.into_iter().map(Into::into) {
+            match change {
+                AttrChange::Replace(current) => current.retain(|v| *v != value),
+                AttrChange::Delta { add, remove } => toggle(remove, add, value),
+            }
+        }
+        self
+    }
+
+    /// Returns true if there is nothing to send.
+    pub fn is_empty(&self) -> bool {
+        self.modifications().is_empty()
+    }
+
+    /// Returns the minimal modifications, in the order attributes were
+    /// first touched.
+    pub fn modifications(&self) -> Vec<LdapModification> {
+        let mut mods = Vec::new();
+        for (attr, change) in &self.changes {
+            match change {
+                AttrChange::Replace(values) => {
+                    mods.push(LdapModification::replace(attr.as_str(), values.clone()))
+                }
+                AttrChange::Delta { add, remove } => {
+                    // An empty Delete would remove every value.
+                    if !remove.is_empty() {
+                        mods.push(LdapModification::delete(attr.as_str(), remove.clone()));
+                    }
+                    if !add.is_empty() {
+                        mods.push(LdapModification::add(attr.as_str(), add.clone()));
+                    }
+                }
             }
         }
+        mods
+    }
+
+    fn change_mut(&mut self, attr: &str) -> &mut AttrChange {
+        let index = match self
+            .changes
+            .iter()
+            .position(|(name, _)| name.eq_ignore_ascii_case(attr))
+        {
+            Some(index) => index,
+            None => {
+                self.changes.push((
+                    attr.to_string(),
+                    AttrChange::Delta {
+                        add: Vec::new(),
+                        remove: Vec::new(),
+                    },
+                ));
+                self.changes.len() - 1
+            }
+        };
+        &mut self.changes[index].1
+    }
+}
+
+/// Records `value` in `to`, unless it cancels a pending entry in `from`.
+fn toggle(to: &mut Vec<String>, from: &mut Vec<String>, value: String) {
+    if let Some(pos) = from.iter().position(|v| *v == value) {
+        from.remove(pos);
+    } else if !to.contains(&value) {
+        to.push(value);
     }
 }
 
@@ -147,6 +484,17 @@ pub enum SearchScope {
     Subtree,
 }
 
+impl SearchScope {
+    /// Returns the RFC 4516 LDAP URL scope token (`base`, `one` or `sub`).
+    pub fn url_token(&self) -> &'static str {
+        match self {
+            SearchScope::Base => "base",
+            SearchScope::OneLevel => "one",
+            SearchScope::Subtree => "sub",
+        }
+    }
+}
+
 impl From<SearchScope> for ldap3::Scope {
     fn from(scope: SearchScope) -> Self {
         match scope {
@@ -165,6 +513,7 @@ pub struct SearchBuilder {
     filter: String,
     attributes: Vec<String>,
     size_limit: Option<usize>,
+    sort_keys: Vec<(String, bool)>,
 }
 
 impl SearchBuilder {
@@ -176,6 +525,7 @@ impl SearchBuilder {
             filter: "(objectClass=*)".to_string(),
             attributes: vec![],
             size_limit: None,
+            sort_keys: vec![],
         }
     }
 
@@ -209,6 +559,17 @@ impl SearchBuilder {
         self
     }
 
+    /// Adds a server-side sort key; see [`LdapConnection::search_sorted`].
+    ///
+    /// Keys apply in the order added, so `.sort_by("sn", false)
+    /// .sort_by("givenName", false)` orders by surname, then first name.
+    ///
+    /// [`LdapConnection::search_sorted`]: crate::ldap::LdapConnection::search_sorted
+    pub fn sort_by(mut self, attr: impl Into<String>, descending: bool) -> Self {
+        self.sort_keys.push((attr.into(), descending));
+        self
+    }
+
     /// Returns the base DN.
     pub fn get_base(&self) -> &str {
         &self.base
@@ -228,6 +589,16 @@ impl SearchBuilder {
     pub fn get_attributes(&self) -> Vec<&str> {
         self.attributes.iter().map(|s| s.as_str()).collect()
     }
+
+    /// Returns the size limit, if one was set.
+    pub fn get_size_limit(&self) -> Option<usize> {
+        self.size_limit
+    }
+
+    /// Returns the sort keys as `(attribute, descending)` pairs.
+    pub fn get_sort_keys(&self) -> &[(String, bool)] {
+        &self.sort_keys
+    }
 }
 
 #[cfg(test)]
@@ -253,12 +624,215 @@ mod tests {
         assert!(!entry.has_object_class("groupOfNames"));
     }
 
+    #[test]
+    fn test_is_container_by_class() {
+        let ou = LdapEntry::new("ou=users,dc=example,dc=com")
+            .with_attribute("objectClass", vec!["top", "OrganizationalUnit"]);
+        let user = LdapEntry::new("uid=test,ou=users,dc=example,dc=com")
+            .with_attribute("objectClass", vec!["inetOrgPerson"]);
+
+        assert!(ou.is_container_by_class());
+        assert!(!user.is_container_by_class());
+        assert!(!LdapEntry::new("dc=example,dc=com").is_container_by_class());
+        assert!(user.is_container_by_classes(&["inetOrgPerson"]));
+        assert!(!ou.is_container_by_classes(&[]));
+    }
+
+    #[test]
+    fn test_validate_attribute_name() {
+        for valid in [
+            "cn",
+            "cn;lang-en",
+            "userPassword",
+            "x-custom-attr",
+            "2.5.4.3",
+            "cn;a;b",
+        ] {
+            assert!(validate_attribute_name(valid).is_ok(), "{}", valid);
+        }
+        for invalid in [
+            "cn; DROP", "my attr", "", "1cn", "cn;", "2.5..3", "cn=x", "-cn",
+        ] {
+            assert!(
+                matches!(
+                    validate_attribute_name(invalid),
+                    Err(HeraclesError::SchemaValidation(_))
+                ),
+                "{}",
+                invalid
+            );
+        }
+    }
+
+    #[test]
+    fn test_modification_wire_format() {
+        let json =
+            serde_json::to_string(&LdapModification::replace("mail", vec!["a@x.com"])).unwrap();
+        assert_eq!(
+            json,
+            r#"{"op":"replace","attr":"mail","values":["a@x.com"]}"#
+        );
+
+        let json = serde_json::to_string(&LdapModification::delete_all("description")).unwrap();
+        assert_eq!(json, r#"{"op":"delete","attr":"description","values":[]}"#);
+    }
+
+    #[test]
+    fn test_modification_serde_round_trip() {
+        let mods = vec![
+            LdapModification::add("memberUid", vec!["alice", "bob"]),
+            LdapModification::delete("memberUid", vec!["carol"]),
+            LdapModification::delete_all("description"),
+            LdapModification::replace("mail", vec!["a@x.com"]),
+            LdapModification::replace("telephoneNumber", Vec::<String>::new()),
+        ];
+
+        for m in mods {
+            let json = serde_json::to_string(&m).unwrap();
+            let back: LdapModification = serde_json::from_str(&json).unwrap();
+            assert_eq!(serde_json::to_string(&back).unwrap(), json);
+            assert_eq!(back.attr(), m.attr());
+        }
+
+        assert!(serde_json::from_str::<LdapModification>(
+            r#"{"op":"increment","attr":"x","values":[]}"#
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_typed_accessors() {
+        let entry = LdapEntry::new("uid=test,ou=users,dc=example,dc=com")
+            .with_single("uidNumber", "1001")
+            .with_single("nsAccountLock", "TRUE")
+            .with_single("createTimestamp", "20240101000000Z")
+            .with_single("description", "not a number");
+
+        assert_eq!(entry.get_i64("uidNumber"), Some(1001));
+        assert_eq!(entry.get_bool("nsAccountLock"), Some(true));
+        assert_eq!(
+            entry
+                .get_time("createTimestamp")
+                .map(|t| t.unix_timestamp()),
+            Some(1_704_067_200)
+        );
+
+        assert_eq!(entry.get_i64("description"), None);
+        assert_eq!(entry.get_bool("description"), None);
+        assert_eq!(entry.get_time("description"), None);
+        assert_eq!(entry.get_i64("gidNumber"), None);
+    }
+
     #[test]
     fn test_ldap_entry_rdn() {
         let entry = LdapEntry::new("uid=test,ou=users,dc=example,dc=com");
         assert_eq!(entry.rdn(), Some("uid=test"));
     }
 
+    #[test]
+    fn test_semantically_equals_ignores_order() {
+        let case_ignore: HashSet<String> = ["cn".to_string()].into();
+        let a = LdapEntry::new("uid=test,dc=example,dc=com")
+            .with_attribute("objectClass", vec!["top", "inetOrgPerson"])
+            .with_single("cn", "Test User")
+            .with_attribute("mail", vec!["a@example.com", "b@example.com"]);
+        let b = LdapEntry::new("uid=test,dc=example,dc=com")
+            .with_attribute("MAIL", vec!["b@example.com", "a@example.com"])
+            .with_single("CN", "test user")
+            .with_attribute("objectclass", vec!["inetOrgPerson", "top"])
+            .with_attribute("description", Vec::<String>::new());
+
+        assert!(a.semantically_equals(&b, &case_ignore));
+        assert!(b.semantically_equals(&a, &case_ignore));
+    }
+
+    #[test]
+    fn test_semantically_equals_case_exact_values() {
+        let case_ignore: HashSet<String> = ["cn".to_string()].into();
+        let a = LdapEntry::new("uid=test,dc=example,dc=com").with_single("uid", "jdoe");
+        let b = LdapEntry::new("uid=test,dc=example,dc=com").with_single("uid", "JDoe");
+
+        assert!(!a.semantically_equals(&b, &case_ignore));
+        assert!(a.semantically_equals(&b, &["UID".to_string()].into()));
+    }
+
+    fn mod_summary(mods: &[LdapModification]) -> Vec<(String, &str, Vec<String>)> {
+        mods.iter()
+            .map(|m| match m {
+                LdapModification::Add { attr, values } => {
+                    ("add".into(), attr.as_str(), values.clone())
+                }
+                LdapModification::Delete { attr, values } => {
+                    ("delete".into(), attr.as_str(), values.clone())
+                }
+                LdapModification::Replace { attr, values } => {
+                    ("replace".into(), attr.as_str(), values.clone())
+                }
+                LdapModification::Increment { attr, by } => {
+                    ("increment".into(), attr.as_str(), vec![by.to_string()])
+                }
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_change_set_coalesces_sets() {
+        let mut changes = ChangeSet::new("uid=test,dc=example,dc=com");
+        changes
+            .set("mail", vec!["a@example.com"])
+            .add("memberUid", vec!["u1"])
+            .set("MAIL", vec!["b@example.com"]);
+
+        assert_eq!(changes.dn(), "uid=test,dc=example,dc=com");
+        assert_eq!(
+            mod_summary(&changes.modifications()),
+            vec![
+                (
+                    "replace".to_string(),
+                    "mail",
+                    vec!["b@example.com".to_string()]
+                ),
+                ("add".to_string(), "memberUid", vec!["u1".to_string()]),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_change_set_add_then_remove_cancels() {
+        let mut changes = ChangeSet::new("cn=devs,dc=example,dc=com");
+        changes
+            .add("memberUid", vec!["u1", "u2"])
+            .remove("memberUid", vec!["u1"])
+            .remove("memberUid", vec!["u3"])
+            .add("memberUid", vec!["u3"]);
+
+        assert_eq!(
+            mod_summary(&changes.modifications()),
+            vec![("add".to_string(), "memberUid", vec!["u2".to_string()])]
+        );
+
+        changes.remove("memberUid", vec!["u2"]);
+        assert!(changes.is_empty());
+    }
+
+    #[test]
+    fn test_change_set_folds_into_set() {
+        let mut changes = ChangeSet::new("cn=devs,dc=example,dc=com");
+        changes
+            .set("memberUid", vec!["u1"])
+            .add("memberUid", vec!["u2"])
+            .remove("memberUid", vec!["u1"])
+            .clear("description");
+
+        assert_eq!(
+            mod_summary(&changes.modifications()),
+            vec![
+                ("replace".to_string(), "memberUid", vec!["u2".to_string()]),
+                ("replace".to_string(), "description", vec![]),
+            ]
+        );
+    }
+
     #[test]
     fn test_ldap_modification_add() {
         let mod_op = LdapModification::add("memberUid", vec!["user1", "user2"]);
@@ -271,6 +845,25 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_ldap_modification_increment() {
+        let mod_op = LdapModification::increment("uidNumber", 1);
+        assert!(mod_op.is_increment());
+        assert_eq!(mod_op.attr(), "uidNumber");
+        match mod_op.to_ldap3_mod() {
+            ldap3::Mod::Increment(attr, by) => {
+                assert_eq!(attr, "uidNumber");
+                assert_eq!(by, "1");
+            }
+            _ => panic!("Expected Increment modification"),
+        }
+
+        let json = serde_json::to_string(&LdapModification::increment("gidNumber", -2)).unwrap();
+        assert_eq!(json, r#"{"op":"increment","attr":"gidNumber","by":-2}"#);
+        let back: LdapModification = serde_json::from_str(&json).unwrap();
+        assert!(matches!(back, LdapModification::Increment { by: -2, .. }));
+    }
+
     #[test]
     fn test_ldap_modification_replace_single() {
         let mod_op = LdapModification::replace_single("description", "New description");
@@ -297,6 +890,49 @@ mod tests {
         assert_eq!(search.get_attributes(), vec!["cn", "mail", "uid"]);
     }
 
+    #[test]
+    fn test_binary_attributes_from_search_entry() {
+        let cert = vec![0x30, 0x82, 0x01, 0x0a, 0xff];
+        let search_entry = ldap3::SearchEntry {
+            dn: "uid=test,ou=users,dc=example,dc=com".to_string(),
+            attrs: [("uid".to_string(), vec!["test".to_string()])]
+                .into_iter()
+                .collect(),
+            bin_attrs: [("userCertificate;binary".to_string(), vec![cert.clone()])]
+                .into_iter()
+                .collect(),
+        };
+
+        let entry = LdapEntry::from(search_entry);
+        assert_eq!(entry.get_first("uid"), Some("test"));
+        assert!(entry.get_all("userCertificate;binary").is_none());
+        assert_eq!(entry.get_binary("userCertificate;binary"), Some(&cert[..]));
+        assert_eq!(entry.get_binary("userCertificate"), Some(&cert[..]));
+        assert_eq!(entry.get_all_binary("userCertificate").unwrap().len(), 1);
+        assert!(entry.get_binary("jpegPhoto").is_none());
+    }
+
+    #[test]
+    fn test_with_binary() {
+        let entry = LdapEntry::new("uid=test,dc=example,dc=com")
+            .with_binary("jpegPhoto", vec![vec![0xff, 0xd8], vec![0x89, 0x50]]);
+        assert_eq!(entry.get_binary("jpegPhoto"), Some(&[0xff, 0xd8][..]));
+        assert_eq!(entry.get_all_binary("jpegPhoto").unwrap().len(), 2);
+        assert!(entry.attributes.is_empty());
+    }
+
+    #[test]
+    fn test_search_builder_sort_by() {
+        let search = SearchBuilder::new("ou=users")
+            .sort_by("sn", false)
+            .sort_by("givenName", true);
+        assert_eq!(
+            search.get_sort_keys(),
+            [("sn".to_string(), false), ("givenName".to_string(), true)]
+        );
+        assert!(SearchBuilder::new("ou=users").get_sort_keys().is_empty());
+    }
+
     #[test]
     fn test_search_scope_conversion() {
         assert!(matches!(
@@ -2,11 +2,19 @@
 
 use crate::errors::{HeraclesError, Result};
 use crate::ldap::config::LdapConfig;
-use crate::ldap::connection::LdapConnection;
+use crate::ldap::connection::{ConnInfo, ConnState, LdapConnection};
+use crate::ldap::operations::{LdapEntry, LdapModification, SearchBuilder};
 use async_trait::async_trait;
-use deadpool::managed::{Manager, Metrics, Object, Pool, RecycleError, RecycleResult};
-use std::sync::Arc;
-use tracing::{debug, error, instrument, warn};
+use deadpool::managed::{Manager, Metrics, Object, Pool, RecycleError, RecycleResult, Timeouts};
+use ldap3::Scope;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, Weak};
+use std::time::{Duration, Instant};
+use tokio::task::JoinHandle;
+use tokio::time::MissedTickBehavior;
+use tracing::{debug, error, instrument, trace, warn};
 
 /// Connection pool for LDAP connections.
 pub type LdapPool = Pool<LdapConnectionManager>;
@@ -17,6 +25,12 @@ pub type PooledConnection = Object<LdapConnectionManager>;
 /// Manager for LDAP connections in the pool.
 pub struct LdapConnectionManager {
     config: Arc<LdapConfig>,
+    breaker: Option<Arc<CircuitBreaker>>,
+    registry: ConnectionRegistry,
+    warmed: AtomicUsize,
+    warm_up_done: AtomicBool,
+    counters: PoolCounters,
+    read_only: bool,
 }
 
 impl LdapConnectionManager {
@@ -24,6 +38,51 @@ impl LdapConnectionManager {
     pub fn new(config: LdapConfig) -> Self {
         Self {
             config: Arc::new(config),
+            breaker: None,
+            registry: ConnectionRegistry::default(),
+            warmed: AtomicUsize::new(0),
+            warm_up_done: AtomicBool::new(false),
+            counters: PoolCounters::default(),
+            read_only: false,
+        }
+    }
+
+    /// Marks every connection this manager creates as read-only, for a
+    /// read replica (see [`LdapConnection::is_read_only`]).
+    pub fn read_only(mut self) -> Self {
+        self.read_only = true;
+        self
+    }
+
+    /// Snapshot of every live connection this manager created.
+    pub fn connection_info(&self) -> Vec<ConnInfo> {
+        self.registry.snapshot()
+    }
+
+    /// Guards connection creation with a circuit breaker.
+    pub fn with_circuit_breaker(mut self, breaker: Arc<CircuitBreaker>) -> Self {
+        self.breaker = Some(breaker);
+        self
+    }
+
+    async fn connect(&self) -> Result<LdapConnection> {
+        let mut conn = LdapConnection::new((*self.config).clone()).await?;
+        conn.set_read_only(self.read_only);
+        conn.bind().await?;
+        Ok(conn)
+    }
+
+    /// Connects through the circuit breaker, if any.
+    async fn connect_guarded(&self) -> Result<LdapConnection> {
+        match &self.breaker {
+            None => self.connect().await,
+            Some(breaker) => {
+                breaker.try_acquire()?;
+                let attempt = BreakerAttempt(Some(breaker));
+                let result = self.connect().await;
+                attempt.finish(result.is_ok());
+                result
+            }
         }
     }
 }
@@ -36,8 +95,14 @@ impl Manager for LdapConnectionManager {
     #[instrument(skip(self))]
     async fn create(&self) -> Result<LdapConnection> {
         debug!("Creating new LDAP connection");
-        let mut conn = LdapConnection::new((*self.config).clone()).await?;
-        conn.bind().await?;
+        let conn = self.connect_guarded().await.inspect_err(|_| {
+            self.counters
+                .create_failures
+                .fetch_add(1, Ordering::Relaxed);
+        })?;
+
+        self.counters.created.fetch_add(1, Ordering::Relaxed);
+        self.registry.register(conn.state());
         Ok(conn)
     }
 
@@ -53,12 +118,402 @@ impl Manager for LdapConnectionManager {
             return Err(RecycleError::StaticMessage("Connection not bound"));
         }
 
-        // Connection seems valid
+        // The bound flag is local; only a round trip notices a dropped socket
+        if self.config.health_check_on_recycle {
+            probe(conn).await?;
+        }
+
         debug!("Recycling LDAP connection");
+        self.counters.recycled.fetch_add(1, Ordering::Relaxed);
         Ok(())
     }
 }
 
+/// Liveness probe run by [`LdapConnectionManager::recycle`].
+///
+/// A failed ping makes deadpool discard the connection and hand out (or
+/// create) another. The probe is bounded by the pool's recycle timeout,
+/// and a probe that times out discards the connection too.
+async fn probe<C: Ping + Send>(conn: &mut C) -> RecycleResult<HeraclesError> {
+    conn.ping().await.map_err(|e| {
+        warn!("Connection failed health check, discarding: {}", e);
+        RecycleError::Backend(e)
+    })
+}
+
+/// Lifetime totals kept by [`LdapConnectionManager`].
+#[derive(Debug, Default)]
+struct PoolCounters {
+    created: AtomicU64,
+    recycled: AtomicU64,
+    create_failures: AtomicU64,
+}
+
+/// Weak handles to the state of connections created by a manager.
+///
+/// Entries die with their connection, so a snapshot only reports
+/// connections that still exist (idle or checked out).
+#[derive(Debug, Default)]
+struct ConnectionRegistry {
+    conns: Mutex<Vec<Weak<Mutex<ConnState>>>>,
+}
+
+impl ConnectionRegistry {
+    fn register(&self, state: &Arc<Mutex<ConnState>>) {
+        let mut conns = self.conns.lock().unwrap();
+        conns.retain(|c| c.strong_count() > 0);
+        conns.push(Arc::downgrade(state));
+    }
+
+    fn snapshot(&self) -> Vec<ConnInfo> {
+        let mut conns = self.conns.lock().unwrap();
+        conns.retain(|c| c.strong_count() > 0);
+        conns
+            .iter()
+            .filter_map(Weak::upgrade)
+            .map(|state| state.lock().unwrap().info())
+            .collect()
+    }
+}
+
+/// Circuit breaker thresholds.
+#[derive(Debug, Clone, Copy)]
+pub struct CircuitBreakerConfig {
+    /// Consecutive failures that open the breaker.
+    pub failure_threshold: u32,
+    /// Failures further apart than this don't count as consecutive.
+    pub window: Duration,
+    /// How long the breaker stays open before letting a probe through.
+    pub cooldown: Duration,
+}
+
+impl Default for CircuitBreakerConfig {
+    fn default() -> Self {
+        Self {
+            failure_threshold: 5,
+            window: Duration::from_secs(30),
+            cooldown: Duration::from_secs(10),
+        }
+    }
+}
+
+/// Observable state of a [`CircuitBreaker`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CircuitState {
+    /// Connection attempts go through.
+    Closed,
+    /// Connection attempts fail fast until the cooldown ends.
+    Open,
+    /// A single probe attempt is allowed to test recovery.
+    HalfOpen,
+}
+
+#[derive(Debug)]
+enum BreakerState {
+    Closed {
+        failures: u32,
+        first_failure: Option<Instant>,
+    },
+    Open {
+        until: Instant,
+    },
+    HalfOpen {
+        probing: bool,
+    },
+}
+
+/// Fails connection attempts fast during a sustained directory outage.
+///
+/// After `failure_threshold` consecutive failures within `window`, the
+/// breaker opens and rejects attempts with `HeraclesError::LdapConnection`
+/// for `cooldown`. It then half-opens: one probe goes through, and its
+/// outcome either closes the breaker or re-opens it for another cooldown.
+#[derive(Debug)]
+pub struct CircuitBreaker {
+    config: CircuitBreakerConfig,
+    state: Mutex<BreakerState>,
+}
+
+impl CircuitBreaker {
+    /// Creates a closed breaker.
+    pub fn new(config: CircuitBreakerConfig) -> Self {
+        Self {
+            config,
+            state: Mutex::new(BreakerState::Closed {
+                failures: 0,
+                first_failure: None,
+            }),
+        }
+    }
+
+    /// Returns the current state.
+    pub fn state(&self) -> CircuitState {
+        self.state_at(Instant::now())
+    }
+
+    /// Checks whether an attempt may proceed.
+    ///
+    /// Callers that get `Ok` must report the outcome with
+    /// [`record_success`](Self::record_success) or
+    /// [`record_failure`](Self::record_failure).
+    pub fn try_acquire(&self) -> Result<()> {
+        self.try_acquire_at(Instant::now())
+    }
+
+    /// Records a successful attempt, closing the breaker.
+    pub fn record_success(&self) {
+        *self.lock() = BreakerState::Closed {
+            failures: 0,
+            first_failure: None,
+        };
+    }
+
+    /// Records a failed attempt.
+    pub fn record_failure(&self) {
+        self.record_failure_at(Instant::now())
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, BreakerState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn state_at(&self, now: Instant) -> CircuitState {
+        match *self.lock() {
+            BreakerState::Closed { .. } => CircuitState::Closed,
+            BreakerState::Open { until } if now < until => CircuitState::Open,
+            BreakerState::Open { .. } | BreakerState::HalfOpen { .. } => CircuitState::HalfOpen,
+        }
+    }
+
+    fn try_acquire_at(&self, now: Instant) -> Result<()> {
+        let mut state = self.lock();
+        match *state {
+            BreakerState::Closed { .. } => Ok(()),
+            BreakerState::Open { until } if now < until => Err(HeraclesError::LdapConnection(
+                "Circuit breaker open, directory unavailable".into(),
+            )),
+            BreakerState::Open { .. } | BreakerState::HalfOpen { probing: false } => {
+                debug!("Circuit breaker half-open, probing");
+                *state = BreakerState::HalfOpen { probing: true };
+                Ok(())
+            }
+            BreakerState::HalfOpen { probing: true } => Err(HeraclesError::LdapConnection(
+                "Circuit breaker half-open, probe in progress".into(),
+            )),
+        }
+    }
+
+    fn record_failure_at(&self, now: Instant) {
+        let threshold = self.config.failure_threshold;
+        let mut state = self.lock();
+        let (failures, first) = match *state {
+            BreakerState::Closed {
+                failures,
+                first_failure: Some(first),
+            } if now.duration_since(first) <= self.config.window => (failures + 1, first),
+            BreakerState::Closed { .. } => (1, now),
+            // A failed probe re-opens the breaker.
+            BreakerState::Open { .. } | BreakerState::HalfOpen { .. } => (threshold, now),
+        };
+
+        *state = if failures >= threshold {
+            warn!("Circuit breaker open for {:?}", self.config.cooldown);
+            BreakerState::Open {
+                until: now + self.config.cooldown,
+            }
+        } else {
+            BreakerState::Closed {
+                failures,
+                first_failure: Some(first),
+            }
+        };
+    }
+}
+
+/// Reports an attempt's outcome to the breaker, counting it as a failure
+/// if dropped unfinished (e.g. cancelled by the create timeout).
+struct BreakerAttempt<'a>(Option<&'a CircuitBreaker>);
+
+impl BreakerAttempt<'_> {
+    fn finish(mut self, success: bool) {
+        if let Some(breaker) = self.0.take() {
+            if success {
+                breaker.record_success();
+            } else {
+                breaker.record_failure();
+            }
+        }
+    }
+}
+
+impl Drop for BreakerAttempt<'_> {
+    fn drop(&mut self) {
+        if let Some(breaker) = self.0.take() {
+            breaker.record_failure();
+        }
+    }
+}
+
+/// Connections that support a cheap liveness request.
+#[async_trait]
+pub trait Ping {
+    /// Issues a lightweight request to the server.
+    async fn ping(&mut self) -> Result<()>;
+}
+
+#[async_trait]
+impl Ping for LdapConnection {
+    async fn ping(&mut self) -> Result<()> {
+        LdapConnection::ping(self).await
+    }
+}
+
+/// Keepalive settings for idle pooled connections.
+#[derive(Debug, Clone, Copy)]
+pub struct KeepaliveConfig {
+    /// Time between keepalive rounds.
+    pub interval: Duration,
+    /// Maximum number of idle connections pinged per round.
+    pub max_per_round: usize,
+}
+
+impl Default for KeepaliveConfig {
+    fn default() -> Self {
+        Self {
+            interval: Duration::from_secs(60),
+            max_per_round: 4,
+        }
+    }
+}
+
+/// Spawns a task that periodically pings idle connections in `pool`.
+///
+/// Only connections sitting idle in the pool are touched; checked-out
+/// connections are skipped. Connections whose ping fails are removed from
+/// the pool. A round stops early if checking out hands it a freshly created
+/// connection (the idle ones were taken meanwhile, or failed the manager's
+/// recycle check), so it opens at most one connection. The task holds a
+/// handle to the pool and runs until the pool is closed or the task is
+/// aborted.
+pub fn spawn_keepalive<M>(pool: Pool<M>, config: KeepaliveConfig) -> JoinHandle<()>
+where
+    M: Manager + 'static,
+    M::Type: Ping + Send,
+{
+    run_keepalive(pool, config, false)
+}
+
+/// [`spawn_keepalive`], skipping the explicit ping when `recycle_probes`
+/// says the manager's recycle already round-trips to the server (see
+/// [`LdapConfig::health_check_on_recycle`]).
+fn run_keepalive<M>(pool: Pool<M>, config: KeepaliveConfig, recycle_probes: bool) -> JoinHandle<()>
+where
+    M: Manager + 'static,
+    M::Type: Ping + Send,
+{
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(config.interval);
+        ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+        // The first tick completes immediately; skip it.
+        ticker.tick().await;
+
+        loop {
+            ticker.tick().await;
+            if pool.is_closed() {
+                break;
+            }
+            let pinged = keepalive_round(&pool, config.max_per_round, recycle_probes).await;
+            trace!("Keepalive pinged {} idle connections", pinged);
+        }
+    })
+}
+
+/// Ping timeout used when the pool has no recycle timeout configured.
+const KEEPALIVE_PING_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Pings up to `max` idle connections, returning how many answered.
+///
+/// Connections are checked out, pinged and released one at a time, so a
+/// round never holds more than one connection away from callers. The pool
+/// hands idle connections out in FIFO order, so a released connection goes
+/// to the back of the queue and each one is visited at most once. Each ping
+/// is bounded by the pool's recycle timeout; a connection that fails or
+/// times out is dropped from the pool.
+///
+/// With `recycle_probes`, checking a connection out already pinged it, so
+/// no second ping is sent. The round stops at the first freshly created
+/// connection, which deadpool opens when no idle one is left.
+async fn keepalive_round<M>(pool: &Pool<M>, max: usize, recycle_probes: bool) -> usize
+where
+    M: Manager,
+    M::Type: Ping + Send,
+{
+    let idle = pool.status().available.min(max);
+    let no_wait = Timeouts {
+        wait: Some(Duration::ZERO),
+        ..pool.timeouts()
+    };
+    let limit = pool.timeouts().recycle.unwrap_or(KEEPALIVE_PING_TIMEOUT);
+
+    let mut pinged = 0;
+    for _ in 0..idle {
+        let mut conn = match pool.timeout_get(&no_wait).await {
+            Ok(conn) => conn,
+            Err(_) => break,
+        };
+        if Object::metrics(&conn).recycled.is_none() {
+            trace!("Keepalive got a new connection, ending the round");
+            break;
+        }
+        if recycle_probes {
+            pinged += 1;
+            continue;
+        }
+        match tokio::time::timeout(limit, conn.ping()).await {
+            Ok(Ok(())) => pinged += 1,
+            Ok(Err(e)) => {
+                warn!("Keepalive ping failed, dropping connection: {}", e);
+                drop(Object::take(conn));
+            }
+            Err(_) => {
+                warn!(
+                    "Keepalive ping timed out after {:?}, dropping connection",
+                    limit
+                );
+                drop(Object::take(conn));
+            }
+        }
+    }
+    pinged
+}
+
+/// Eagerly opens connections so the first requests don't pay for connect
+/// and bind.
+///
+/// Checks out `n` connections at once (reusing idle ones, creating the
+/// rest) and returns them all to the pool, leaving at least `n` idle
+/// connections when every attempt succeeds. Failures are logged and
+/// skipped, so a partially reachable directory still gets what it can.
+/// Returns how many connections were warmed; `n` is capped at the pool's
+/// maximum size.
+pub async fn warm_up<M: Manager>(pool: &Pool<M>, n: usize) -> usize
+where
+    M::Error: std::fmt::Display,
+{
+    let n = n.min(pool.status().max_size);
+    let results = futures::future::join_all((0..n).map(|_| pool.get())).await;
+
+    let mut warmed = 0;
+    for result in results {
+        match result {
+            Ok(_) => warmed += 1,
+            Err(e) => warn!("Pool warm-up failed to open a connection: {}", e),
+        }
+    }
+    debug!("Pool warm-up opened {} of {} connections", warmed, n);
+    warmed
+}
+
 /// Builder for creating an LDAP connection pool.
 #[derive(Debug)]
 pub struct LdapPoolBuilder {
@@ -67,6 +522,9 @@ pub struct LdapPoolBuilder {
     wait_timeout: Option<std::time::Duration>,
     create_timeout: Option<std::time::Duration>,
     recycle_timeout: Option<std::time::Duration>,
+    keepalive: Option<KeepaliveConfig>,
+    circuit_breaker: Option<CircuitBreakerConfig>,
+    min_idle: usize,
 }
 
 impl LdapPoolBuilder {
@@ -79,6 +537,9 @@ impl LdapPoolBuilder {
             wait_timeout: Some(std::time::Duration::from_secs(30)),
             create_timeout: Some(std::time::Duration::from_secs(10)),
             recycle_timeout: Some(std::time::Duration::from_secs(5)),
+            keepalive: None,
+            circuit_breaker: None,
+            min_idle: 0,
         }
     }
 
@@ -106,13 +567,76 @@ impl LdapPoolBuilder {
         self
     }
 
+    /// Enables a background keepalive task for idle connections.
+    ///
+    /// Requires building the pool from within a Tokio runtime.
+    pub fn keepalive(mut self, config: KeepaliveConfig) -> Self {
+        self.keepalive = Some(config);
+        self
+    }
+
+    /// Enables a circuit breaker around connection creation.
+    ///
+    /// Each pool built (including each replica pool) gets its own breaker.
+    pub fn circuit_breaker(mut self, config: CircuitBreakerConfig) -> Self {
+        self.circuit_breaker = Some(config);
+        self
+    }
+
+    /// Pre-creates `n` connections in the background once the pool is built.
+    ///
+    /// See [`warm_up`]; await it directly instead to block until the pool
+    /// is warm. Requires building the pool from within a Tokio runtime.
+    pub fn min_idle(mut self, n: usize) -> Self {
+        self.min_idle = n;
+        self
+    }
+
     /// Builds the connection pool.
     pub fn build(self) -> Result<LdapPool> {
         self.config.validate()?;
+        self.build_for(self.config.clone(), false)
+    }
 
-        let manager = LdapConnectionManager::new(self.config);
+    /// Builds a primary pool plus one pool per configured read replica.
+    ///
+    /// Writes always go to the primary (`config.uri`, failing over across
+    /// `config.uris`); searches are distributed round-robin across
+    /// `config.read_uris`, falling back to the primary when no replica is
+    /// configured. Replica connections refuse writes.
+    pub fn build_replicated(self) -> Result<ReplicatedPool> {
+        self.config.validate()?;
 
-        let mut pool_builder = Pool::builder(manager).max_size(self.max_size);
+        let primary = self.build_for(
+            LdapConfig {
+                read_uris: Vec::new(),
+                ..self.config.clone()
+            },
+            false,
+        )?;
+        let replicas = self
+            .config
+            .read_uris
+            .iter()
+            .map(|uri| self.build_for(self.config.with_uri(uri.clone()), true))
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(ReadWriteRouter::new(primary, replicas))
+    }
+
+    fn build_for(&self, config: LdapConfig, read_only: bool) -> Result<LdapPool> {
+        let recycle_probes = config.health_check_on_recycle;
+        let mut manager = LdapConnectionManager::new(config);
+        if read_only {
+            manager = manager.read_only();
+        }
+        if let Some(breaker) = self.circuit_breaker {
+            manager = manager.with_circuit_breaker(Arc::new(CircuitBreaker::new(breaker)));
+        }
+
+        let mut pool_builder = Pool::builder(manager)
+            .max_size(self.max_size)
+            .runtime(deadpool::Runtime::Tokio1);
 
         if let Some(timeout) = self.wait_timeout {
             pool_builder = pool_builder.wait_timeout(Some(timeout));
@@ -126,9 +650,34 @@ impl LdapPoolBuilder {
             pool_builder = pool_builder.recycle_timeout(Some(timeout));
         }
 
-        pool_builder
+        let pool = pool_builder
             .build()
-            .map_err(|e| HeraclesError::Config(format!("Failed to build pool: {}", e)))
+            .map_err(|e| HeraclesError::Config(format!("Failed to build pool: {}", e)))?;
+
+        if let Some(keepalive) = self.keepalive {
+            if tokio::runtime::Handle::try_current().is_err() {
+                return Err(HeraclesError::Config(
+                    "Keepalive requires a Tokio runtime".into(),
+                ));
+            }
+            run_keepalive(pool.clone(), keepalive, recycle_probes);
+        }
+
+        if self.min_idle > 0 {
+            if tokio::runtime::Handle::try_current().is_err() {
+                return Err(HeraclesError::Config(
+                    "Pool warm-up requires a Tokio runtime".into(),
+                ));
+            }
+            let (pool, n) = (pool.clone(), self.min_idle);
+            tokio::spawn(async move {
+                let warmed = warm_up(&pool, n).await;
+                pool.manager().warmed.store(warmed, Ordering::Relaxed);
+                pool.manager().warm_up_done.store(true, Ordering::Release);
+            });
+        }
+
+        Ok(pool)
     }
 }
 
@@ -151,6 +700,12 @@ pub trait LdapPoolExt {
 
     /// Gets pool status information.
     fn status(&self) -> PoolStatus;
+
+    /// Lists each live connection's age, bound DN, encryption and URI.
+    fn connection_info(&self) -> Vec<ConnInfo>;
+
+    /// Gets lifetime connection counters along with the current status.
+    fn metrics(&self) -> PoolMetrics;
 }
 
 #[async_trait]
@@ -170,33 +725,207 @@ impl LdapPoolExt for LdapPool {
             size: status.size,
             available: status.available,
             waiting: status.waiting,
+            warmed: self.manager().warmed.load(Ordering::Relaxed),
+        }
+    }
+
+    fn connection_info(&self) -> Vec<ConnInfo> {
+        self.manager().connection_info()
+    }
+
+    fn metrics(&self) -> PoolMetrics {
+        let counters = &self.manager().counters;
+        PoolMetrics {
+            connections_created: counters.created.load(Ordering::Relaxed),
+            connections_recycled: counters.recycled.load(Ordering::Relaxed),
+            create_failures: counters.create_failures.load(Ordering::Relaxed),
+            status: LdapPoolExt::status(self),
+        }
+    }
+}
+
+/// Routes reads to replicas (round-robin) and writes to the primary.
+#[derive(Debug)]
+pub struct ReadWriteRouter<T> {
+    primary: T,
+    replicas: Vec<T>,
+    next: AtomicUsize,
+}
+
+impl<T> ReadWriteRouter<T> {
+    /// Creates a router over a primary and its read replicas.
+    pub fn new(primary: T, replicas: Vec<T>) -> Self {
+        Self {
+            primary,
+            replicas,
+            next: AtomicUsize::new(0),
+        }
+    }
+
+    /// Returns the target for the next read (primary if no replicas).
+    pub fn for_read(&self) -> &T {
+        if self.replicas.is_empty() {
+            return &self.primary;
         }
+        let idx = self.next.fetch_add(1, Ordering::Relaxed) % self.replicas.len();
+        &self.replicas[idx]
+    }
+
+    /// Returns the target for writes (always the primary).
+    pub fn for_write(&self) -> &T {
+        &self.primary
+    }
+
+    /// Returns the primary target.
+    pub fn primary(&self) -> &T {
+        &self.primary
+    }
+
+    /// Returns the read replica targets.
+    pub fn replicas(&self) -> &[T] {
+        &self.replicas
+    }
+}
+
+/// Primary pool plus read replica pools.
+pub type ReplicatedPool = ReadWriteRouter<LdapPool>;
+
+impl ReplicatedPool {
+    /// Gets a connection suitable for searches.
+    ///
+    /// Connections to a replica refuse add, modify, modify DN and delete;
+    /// use [`get_write_connection`](Self::get_write_connection) or the
+    /// write helpers below for those.
+    pub async fn get_read_connection(&self) -> Result<PooledConnection> {
+        self.for_read().get_connection().await
+    }
+
+    /// Gets a connection to the primary for add/modify/delete.
+    pub async fn get_write_connection(&self) -> Result<PooledConnection> {
+        self.for_write().get_connection().await
+    }
+
+    /// Searches on the next replica; see [`LdapConnection::search`].
+    pub async fn search(
+        &self,
+        base: &str,
+        scope: Scope,
+        filter: &str,
+        attrs: Vec<&str>,
+    ) -> Result<Vec<LdapEntry>> {
+        let mut conn = self.get_read_connection().await?;
+        conn.search(base, scope, filter, attrs).await
+    }
+
+    /// Searches on the next replica; see [`LdapConnection::search_with`].
+    pub async fn search_with(&self, builder: &SearchBuilder) -> Result<Vec<LdapEntry>> {
+        let mut conn = self.get_read_connection().await?;
+        conn.search_with(builder).await
+    }
+
+    /// Adds an entry on the primary; see [`LdapConnection::add`].
+    pub async fn add(&self, dn: &str, attributes: HashMap<String, Vec<String>>) -> Result<()> {
+        let mut conn = self.get_write_connection().await?;
+        conn.add(dn, attributes).await
+    }
+
+    /// Modifies an entry on the primary; see [`LdapConnection::modify`].
+    pub async fn modify(&self, dn: &str, modifications: Vec<LdapModification>) -> Result<()> {
+        let mut conn = self.get_write_connection().await?;
+        conn.modify(dn, modifications).await
+    }
+
+    /// Renames or moves an entry on the primary; see
+    /// [`LdapConnection::modify_dn`].
+    pub async fn modify_dn(
+        &self,
+        dn: &str,
+        new_rdn: &str,
+        delete_old_rdn: bool,
+        new_superior: Option<&str>,
+    ) -> Result<()> {
+        let mut conn = self.get_write_connection().await?;
+        conn.modify_dn(dn, new_rdn, delete_old_rdn, new_superior)
+            .await
+    }
+
+    /// Deletes an entry on the primary; see [`LdapConnection::delete`].
+    pub async fn delete(&self, dn: &str) -> Result<()> {
+        let mut conn = self.get_write_connection().await?;
+        conn.delete(dn).await
     }
 }
 
 /// Pool status information.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct PoolStatus {
     /// Maximum pool size.
     pub max_size: usize,
     /// Current number of connections.
     pub size: usize,
-    /// Number of available connections.
+    /// Number of idle connections ready to be handed out.
     pub available: usize,
     /// Number of tasks waiting for a connection.
     pub waiting: usize,
+    /// Connections opened by the startup warm-up (see
+    /// [`LdapPoolBuilder::min_idle`]); 0 until the warm-up finishes.
+    pub warmed: usize,
 }
 
 impl std::fmt::Display for PoolStatus {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(
             f,
-            "Pool[max={}, size={}, available={}, waiting={}]",
-            self.max_size, self.size, self.available, self.waiting
+            "Pool[max={}, size={}, available={}, waiting={}, warmed={}]",
+            self.max_size, self.size, self.available, self.waiting, self.warmed
         )
     }
 }
 
+/// Lifetime pool counters plus the live [`PoolStatus`].
+///
+/// Serializes to JSON, and displays in the Prometheus text exposition
+/// format so it can be served from a metrics endpoint as-is.
+#[derive(Debug, Clone, Serialize)]
+pub struct PoolMetrics {
+    /// Connections opened and bound since the pool was built.
+    pub connections_created: u64,
+    /// Times an idle connection passed its checks and was handed out again.
+    pub connections_recycled: u64,
+    /// Connection attempts that failed, including circuit breaker rejections.
+    pub create_failures: u64,
+    /// Current pool occupancy.
+    pub status: PoolStatus,
+}
+
+impl std::fmt::Display for PoolMetrics {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let metrics = [
+            (
+                "connections_created_total",
+                "counter",
+                self.connections_created,
+            ),
+            (
+                "connections_recycled_total",
+                "counter",
+                self.connections_recycled,
+            ),
+            ("create_failures_total", "counter", self.create_failures),
+            ("max_size", "gauge", self.status.max_size as u64),
+            ("size", "gauge", self.status.size as u64),
+            ("available", "gauge", self.status.available as u64),
+            ("waiting", "gauge", self.status.waiting as u64),
+            ("warmed", "gauge", self.status.warmed as u64),
+        ];
+        for (name, kind, value) in metrics {
+            writeln!(f, "# TYPE heracles_ldap_pool_{} {}", name, kind)?;
+            writeln!(f, "heracles_ldap_pool_{} {}", name, value)?;
+        }
+        Ok(())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -228,6 +957,645 @@ mod tests {
         assert!(result.is_err());
     }
 
+    struct MockConn {
+        id: usize,
+        pings: Arc<std::sync::Mutex<Vec<usize>>>,
+        dead: bool,
+        hang: bool,
+    }
+
+    #[async_trait]
+    impl Ping for MockConn {
+        async fn ping(&mut self) -> Result<()> {
+            self.pings.lock().unwrap().push(self.id);
+            if self.hang {
+                std::future::pending::<()>().await;
+            }
+            if self.dead {
+                return Err(HeraclesError::LdapConnection("connection lost".into()));
+            }
+            Ok(())
+        }
+    }
+
+    #[derive(Default)]
+    struct MockManager {
+        next_id: AtomicUsize,
+        pings: Arc<std::sync::Mutex<Vec<usize>>>,
+        health_check: bool,
+        failing_creates: AtomicUsize,
+    }
+
+    #[async_trait]
+    impl Manager for MockManager {
+        type Type = MockConn;
+        type Error = HeraclesError;
+
+        async fn create(&self) -> Result<MockConn> {
+            let failing = self.failing_creates.load(Ordering::SeqCst);
+            if failing > 0 {
+                self.failing_creates.store(failing - 1, Ordering::SeqCst);
+                return Err(HeraclesError::LdapConnection("unreachable".into()));
+            }
+            Ok(MockConn {
+                id: self.next_id.fetch_add(1, Ordering::SeqCst),
+                pings: self.pings.clone(),
+                dead: false,
+                hang: false,
+            })
+        }
+
+        async fn recycle(
+            &self,
+            conn: &mut MockConn,
+            _metrics: &Metrics,
+        ) -> RecycleResult<Self::Error> {
+            if self.health_check {
+                probe(conn).await?;
+            }
+            Ok(())
+        }
+    }
+
+    fn mock_pool(manager: MockManager) -> Pool<MockManager> {
+        Pool::builder(manager)
+            .max_size(4)
+            .runtime(deadpool::Runtime::Tokio1)
+            .build()
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_recycle_discards_connection_failing_probe() {
+        let pool = mock_pool(MockManager {
+            health_check: true,
+            ..Default::default()
+        });
+        let pings = pool.manager().pings.clone();
+
+        let mut conn = pool.get().await.unwrap();
+        assert_eq!(conn.id, 0);
+        conn.dead = true;
+        drop(conn);
+
+        let conn = pool.get().await.unwrap();
+        assert_eq!(conn.id, 1);
+        assert_eq!(*pings.lock().unwrap(), vec![0]);
+        drop(conn);
+
+        // A healthy connection passes the probe and is reused
+        let conn = pool.get().await.unwrap();
+        assert_eq!(conn.id, 1);
+        assert_eq!(*pings.lock().unwrap(), vec![0, 1]);
+    }
+
+    #[tokio::test]
+    async fn test_recycle_without_health_check_skips_probe() {
+        let pool = mock_pool(MockManager::default());
+        let pings = pool.manager().pings.clone();
+
+        let mut conn = pool.get().await.unwrap();
+        conn.dead = true;
+        drop(conn);
+
+        let conn = pool.get().await.unwrap();
+        assert_eq!(conn.id, 0);
+        assert!(pings.lock().unwrap().is_empty());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_keepalive_pings_idle_and_skips_busy() {
+        let pool = mock_pool(MockManager::default());
+        let pings = pool.manager().pings.clone();
+
+        let busy = pool.get().await.unwrap();
+        let idle = pool.get().await.unwrap();
+        assert_eq!((busy.id, idle.id), (0, 1));
+        drop(idle);
+
+        let handle = spawn_keepalive(
+            pool.clone(),
+            KeepaliveConfig {
+                interval: Duration::from_secs(60),
+                max_per_round: 4,
+            },
+        );
+
+        tokio::time::sleep(Duration::from_secs(30)).await;
+        assert!(pings.lock().unwrap().is_empty());
+
+        tokio::time::sleep(Duration::from_secs(31)).await;
+        assert_eq!(*pings.lock().unwrap(), vec![1]);
+        // No new connections were opened for the keepalive.
+        assert_eq!(pool.status().size, 2);
+
+        handle.abort();
+        drop(busy);
+    }
+
+    #[tokio::test]
+    async fn test_keepalive_round_respects_max() {
+        let pool = mock_pool(MockManager::default());
+        let pings = pool.manager().pings.clone();
+
+        let conns = vec![
+            pool.get().await.unwrap(),
+            pool.get().await.unwrap(),
+            pool.get().await.unwrap(),
+        ];
+        drop(conns);
+
+        assert_eq!(keepalive_round(&pool, 2, false).await, 2);
+        assert_eq!(*pings.lock().unwrap(), vec![0, 1]);
+    }
+
+    #[tokio::test]
+    async fn test_keepalive_round_visits_each_connection_once() {
+        let pool = mock_pool(MockManager::default());
+        let pings = pool.manager().pings.clone();
+
+        let conns = vec![pool.get().await.unwrap(), pool.get().await.unwrap()];
+        drop(conns);
+
+        assert_eq!(keepalive_round(&pool, 4, false).await, 2);
+        assert_eq!(*pings.lock().unwrap(), vec![0, 1]);
+        assert_eq!(pool.status().available, 2);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_keepalive_round_drops_hung_connection() {
+        let pool = mock_pool(MockManager::default());
+        let pings = pool.manager().pings.clone();
+
+        let mut hung = pool.get().await.unwrap();
+        let healthy = pool.get().await.unwrap();
+        hung.hang = true;
+        drop(hung);
+        drop(healthy);
+
+        assert_eq!(keepalive_round(&pool, 4, false).await, 1);
+        assert_eq!(*pings.lock().unwrap(), vec![0, 1]);
+        assert_eq!(pool.status().size, 1);
+        assert_eq!(pool.get().await.unwrap().id, 1);
+    }
+
+    #[tokio::test]
+    async fn test_keepalive_round_relies_on_recycle_probe() {
+        let pool = mock_pool(MockManager {
+            health_check: true,
+            ..Default::default()
+        });
+        let pings = pool.manager().pings.clone();
+
+        let conns = vec![pool.get().await.unwrap(), pool.get().await.unwrap()];
+        drop(conns);
+
+        // Each connection is probed once by recycle, not pinged again.
+        assert_eq!(keepalive_round(&pool, 4, true).await, 2);
+        assert_eq!(*pings.lock().unwrap(), vec![0, 1]);
+        assert_eq!(pool.status().available, 2);
+    }
+
+    #[tokio::test]
+    async fn test_keepalive_round_stops_at_new_connection() {
+        let pool = mock_pool(MockManager {
+            health_check: true,
+            ..Default::default()
+        });
+        let pings = pool.manager().pings.clone();
+
+        let mut conns = vec![pool.get().await.unwrap(), pool.get().await.unwrap()];
+        for conn in &mut conns {
+            conn.dead = true;
+        }
+        drop(conns);
+
+        // Both idle connections fail their recycle probe and deadpool opens
+        // a replacement; the round ends there instead of visiting it.
+        assert_eq!(keepalive_round(&pool, 4, true).await, 0);
+        assert_eq!(*pings.lock().unwrap(), vec![0, 1]);
+        assert_eq!(pool.status().size, 1);
+        assert_eq!(pool.get().await.unwrap().id, 2);
+    }
+
+    #[test]
+    fn test_keepalive_requires_runtime() {
+        let result = LdapPoolBuilder::new(LdapConfig::default())
+            .keepalive(KeepaliveConfig::default())
+            .build();
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_connection_registry_tracks_live_connections() {
+        let registry = ConnectionRegistry::default();
+        let plain = Arc::new(Mutex::new(ConnState::new("ldap://a.example.com", false)));
+        let secure = Arc::new(Mutex::new(ConnState::new("ldaps://b.example.com", true)));
+        registry.register(&plain);
+        registry.register(&secure);
+        secure
+            .lock()
+            .unwrap()
+            .set_bound_dn(Some("cn=admin,dc=example,dc=com"));
+
+        let info = registry.snapshot();
+        assert_eq!(info.len(), 2);
+        assert_eq!(info[0].uri, "ldap://a.example.com");
+        assert!(!info[0].encrypted);
+        assert_eq!(info[0].bound_dn, None);
+        assert!(info[1].encrypted);
+        assert_eq!(
+            info[1].bound_dn.as_deref(),
+            Some("cn=admin,dc=example,dc=com")
+        );
+
+        drop(plain);
+        let info = registry.snapshot();
+        assert_eq!(info.len(), 1);
+        assert_eq!(info[0].uri, "ldaps://b.example.com");
+    }
+
+    #[test]
+    fn test_connection_info_empty_for_new_pool() {
+        let pool = LdapPoolBuilder::new(LdapConfig::default()).build().unwrap();
+        assert!(pool.connection_info().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_pool_build_sets_runtime() {
+        assert!(LdapPoolBuilder::new(LdapConfig::default()).build().is_ok());
+    }
+
+    fn breaker() -> CircuitBreaker {
+        CircuitBreaker::new(CircuitBreakerConfig {
+            failure_threshold: 3,
+            window: Duration::from_secs(30),
+            cooldown: Duration::from_secs(10),
+        })
+    }
+
+    #[test]
+    fn test_circuit_breaker_opens_after_threshold() {
+        let breaker = breaker();
+        let start = Instant::now();
+
+        for i in 0..3 {
+            assert!(breaker.try_acquire_at(start).is_ok());
+            breaker.record_failure_at(start + Duration::from_secs(i));
+        }
+        assert_eq!(breaker.state_at(start), CircuitState::Open);
+        assert!(matches!(
+            breaker.try_acquire_at(start + Duration::from_secs(5)),
+            Err(HeraclesError::LdapConnection(_))
+        ));
+    }
+
+    #[test]
+    fn test_circuit_breaker_window_resets_count() {
+        let breaker = breaker();
+        let start = Instant::now();
+
+        breaker.record_failure_at(start);
+        breaker.record_failure_at(start + Duration::from_secs(1));
+        // Outside the window: counting starts over.
+        breaker.record_failure_at(start + Duration::from_secs(40));
+        breaker.record_failure_at(start + Duration::from_secs(41));
+        assert_eq!(breaker.state_at(start), CircuitState::Closed);
+
+        breaker.record_success();
+        breaker.record_failure_at(start + Duration::from_secs(42));
+        breaker.record_failure_at(start + Duration::from_secs(43));
+        assert_eq!(breaker.state_at(start), CircuitState::Closed);
+    }
+
+    #[test]
+    fn test_circuit_breaker_half_open_probe() {
+        let breaker = breaker();
+        let start = Instant::now();
+        for _ in 0..3 {
+            breaker.record_failure_at(start);
+        }
+
+        // After the cooldown a single probe is let through.
+        let later = start + Duration::from_secs(11);
+        assert_eq!(breaker.state_at(later), CircuitState::HalfOpen);
+        assert!(breaker.try_acquire_at(later).is_ok());
+        assert!(breaker.try_acquire_at(later).is_err());
+
+        // A failed probe re-opens the breaker.
+        breaker.record_failure_at(later);
+        assert_eq!(breaker.state_at(later), CircuitState::Open);
+
+        // A successful probe closes it.
+        let recovered = later + Duration::from_secs(11);
+        assert!(breaker.try_acquire_at(recovered).is_ok());
+        breaker.record_success();
+        assert_eq!(breaker.state_at(recovered), CircuitState::Closed);
+        assert!(breaker.try_acquire_at(recovered).is_ok());
+    }
+
+    #[test]
+    fn test_circuit_breaker_dropped_attempt_counts_as_failure() {
+        let breaker = breaker();
+        for _ in 0..3 {
+            breaker.try_acquire().unwrap();
+            drop(BreakerAttempt(Some(&breaker)));
+        }
+        assert_eq!(breaker.state(), CircuitState::Open);
+    }
+
+    #[tokio::test]
+    async fn test_pool_circuit_breaker_fails_fast() {
+        let config = LdapConfig {
+            uri: "ldap://127.0.0.1:1".to_string(),
+            ..Default::default()
+        };
+        let pool = LdapPoolBuilder::new(config)
+            .circuit_breaker(CircuitBreakerConfig {
+                failure_threshold: 2,
+                ..Default::default()
+            })
+            .build()
+            .unwrap();
+
+        for _ in 0..2 {
+            let err = pool.get_connection().await.err().unwrap().to_string();
+            assert!(!err.contains("Circuit breaker"), "{}", err);
+        }
+        let err = pool.get_connection().await.err().unwrap();
+        assert!(matches!(err, HeraclesError::LdapConnection(_)));
+        assert!(err.to_string().contains("Circuit breaker open"));
+    }
+
+    /// Connector that records the URI of every connection it opens.
+    struct RecordingConnector {
+        uri: &'static str,
+        used: Arc<Mutex<Vec<&'static str>>>,
+    }
+
+    impl RecordingConnector {
+        fn connect(&self) {
+            self.used.lock().unwrap().push(self.uri);
+        }
+    }
+
+    fn recording_router(
+        replicas: &[&'static str],
+    ) -> (
+        ReadWriteRouter<RecordingConnector>,
+        Arc<Mutex<Vec<&'static str>>>,
+    ) {
+        let used = Arc::new(Mutex::new(Vec::new()));
+        let connector = |uri| RecordingConnector {
+            uri,
+            used: used.clone(),
+        };
+        let router = ReadWriteRouter::new(
+            connector("ldap://primary"),
+            replicas.iter().map(|&uri| connector(uri)).collect(),
+        );
+        (router, used)
+    }
+
+    #[test]
+    fn test_router_reads_round_robin() {
+        let (router, used) = recording_router(&["ldap://r1", "ldap://r2"]);
+        router.for_read().connect();
+        router.for_read().connect();
+        router.for_read().connect();
+        router.for_write().connect();
+        assert_eq!(
+            *used.lock().unwrap(),
+            vec!["ldap://r1", "ldap://r2", "ldap://r1", "ldap://primary"]
+        );
+    }
+
+    #[test]
+    fn test_router_reads_fall_back_to_primary() {
+        let (router, used) = recording_router(&[]);
+        router.for_read().connect();
+        router.for_write().connect();
+        assert_eq!(*used.lock().unwrap(), vec!["ldap://primary"; 2]);
+    }
+
+    #[tokio::test]
+    async fn test_build_replicated() {
+        let (primary, _) = crate::ldap::test_server::spawn(0).await;
+        let (replica1, _) = crate::ldap::test_server::spawn(0).await;
+        let (replica2, _) = crate::ldap::test_server::spawn(0).await;
+        let config = LdapConfig {
+            read_uris: vec![replica1.clone(), replica2.clone()],
+            ..LdapConfig::default().with_uri(primary.clone())
+        };
+        let pool = LdapPoolBuilder::new(config).build_replicated().unwrap();
+        assert_eq!(pool.replicas().len(), 2);
+
+        let read = pool.get_read_connection().await.unwrap();
+        assert_eq!(read.info().uri, replica1);
+        assert!(read.is_read_only());
+        let read = pool.get_read_connection().await.unwrap();
+        assert_eq!(read.info().uri, replica2);
+        let write = pool.get_write_connection().await.unwrap();
+        assert_eq!(write.info().uri, primary);
+        assert!(!write.is_read_only());
+    }
+
+    #[tokio::test]
+    async fn test_replicated_pool_routes_operations() {
+        use crate::ldap::test_server::spawn_recording;
+
+        let (primary, primary_searches, primary_writes) = spawn_recording(0).await;
+        let (replica1, replica1_searches, replica1_writes) = spawn_recording(0).await;
+        let (replica2, replica2_searches, replica2_writes) = spawn_recording(0).await;
+        let config = LdapConfig {
+            read_uris: vec![replica1, replica2],
+            ..LdapConfig::default().with_uri(primary)
+        };
+        let pool = LdapPoolBuilder::new(config).build_replicated().unwrap();
+        let dn = "uid=jdoe,ou=users,dc=example,dc=com";
+
+        pool.search(dn, Scope::Base, "(objectClass=*)", vec![])
+            .await
+            .unwrap();
+        pool.search(dn, Scope::Base, "(objectClass=*)", vec![])
+            .await
+            .unwrap();
+        pool.add(
+            dn,
+            HashMap::from([("cn".to_string(), vec!["John".to_string()])]),
+        )
+        .await
+        .unwrap();
+        pool.modify(dn, vec![LdapModification::replace_single("cn", "Jane")])
+            .await
+            .unwrap();
+        pool.modify_dn(dn, "uid=jsmith", true, None).await.unwrap();
+        pool.delete(dn).await.unwrap();
+
+        // Recycling pings the root DSE; only count searches for the entry.
+        let entry_searches = |seen: &crate::ldap::test_server::SeenSearches| {
+            seen.lock().unwrap().iter().filter(|s| s.base == dn).count()
+        };
+        assert_eq!(entry_searches(&replica1_searches), 1);
+        assert_eq!(entry_searches(&replica2_searches), 1);
+        assert_eq!(entry_searches(&primary_searches), 0);
+        assert_eq!(
+            *primary_writes.lock().unwrap(),
+            vec![
+                format!("add {}", dn),
+                format!("modify {}", dn),
+                format!("modify_dn {}", dn),
+                format!("delete {}", dn),
+            ]
+        );
+        assert!(replica1_writes.lock().unwrap().is_empty());
+        assert!(replica2_writes.lock().unwrap().is_empty());
+
+        // A write on a replica connection is refused before it is sent.
+        let mut read = pool.get_read_connection().await.unwrap();
+        assert!(matches!(
+            read.delete(dn).await,
+            Err(HeraclesError::LdapDelete(_))
+        ));
+        assert!(matches!(
+            read.modify(dn, vec![LdapModification::replace_single("cn", "x")])
+                .await,
+            Err(HeraclesError::LdapModify(_))
+        ));
+        assert!(replica1_writes.lock().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_warm_up_fills_pool() {
+        let pool = mock_pool(MockManager::default());
+        assert_eq!(warm_up(&pool, 3).await, 3);
+
+        let status = pool.status();
+        assert!(status.available >= 3);
+        assert_eq!(status.size, 3);
+        // Capped at the pool's maximum size.
+        assert_eq!(warm_up(&pool, 10).await, 4);
+    }
+
+    #[tokio::test]
+    async fn test_warm_up_tolerates_failures() {
+        let pool = mock_pool(MockManager {
+            failing_creates: AtomicUsize::new(1),
+            ..Default::default()
+        });
+        assert_eq!(warm_up(&pool, 3).await, 2);
+        assert_eq!(pool.status().available, 2);
+    }
+
+    #[tokio::test]
+    async fn test_pool_builder_min_idle_warms_in_background() {
+        let (uri, _) = crate::ldap::test_server::spawn(0).await;
+        let config = LdapConfig::default().with_uris([uri]);
+        let pool = LdapPoolBuilder::new(config).min_idle(2).build().unwrap();
+
+        let status = tokio::time::timeout(Duration::from_secs(5), async {
+            loop {
+                let status = LdapPoolExt::status(&pool);
+                if status.warmed == 2 {
+                    return status;
+                }
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .unwrap();
+        assert_eq!((status.size, status.available), (2, 2));
+    }
+
+    #[tokio::test]
+    async fn test_pool_builder_min_idle_tolerates_unreachable_server() {
+        let config = LdapConfig {
+            uri: "ldap://127.0.0.1:1".into(),
+            ..Default::default()
+        };
+        let pool = LdapPoolBuilder::new(config).min_idle(2).build().unwrap();
+
+        tokio::time::timeout(Duration::from_secs(5), async {
+            while !pool.manager().warm_up_done.load(Ordering::Acquire) {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .unwrap();
+        assert_eq!(LdapPoolExt::status(&pool).warmed, 0);
+        assert_eq!(pool.metrics().create_failures, 2);
+
+        // The pool survives the failed warm-up and keeps serving requests.
+        assert!(!pool.is_closed());
+        assert!(matches!(
+            pool.get_connection().await,
+            Err(HeraclesError::LdapConnection(_))
+        ));
+        assert_eq!(pool.metrics().create_failures, 3);
+    }
+
+    #[test]
+    fn test_pool_builder_min_idle_requires_runtime() {
+        let result = LdapPoolBuilder::new(LdapConfig::default())
+            .min_idle(1)
+            .build();
+        assert!(matches!(result, Err(HeraclesError::Config(_))));
+    }
+
+    #[tokio::test]
+    async fn test_metrics_count_created_recycled_and_failed() {
+        let (uri, _) = crate::ldap::test_server::spawn(0).await;
+        let pool = create_pool(LdapConfig::default().with_uris([uri])).unwrap();
+
+        drop(pool.get_connection().await.unwrap());
+        drop(pool.get_connection().await.unwrap());
+        let metrics = pool.metrics();
+        assert_eq!(
+            (metrics.connections_created, metrics.connections_recycled),
+            (1, 1)
+        );
+        assert_eq!(metrics.create_failures, 0);
+        assert_eq!(metrics.status.available, 1);
+
+        let unreachable = create_pool(LdapConfig {
+            uri: "ldap://127.0.0.1:1".into(),
+            ..Default::default()
+        })
+        .unwrap();
+        assert!(unreachable.get_connection().await.is_err());
+        let metrics = unreachable.metrics();
+        assert_eq!(
+            (metrics.connections_created, metrics.create_failures),
+            (0, 1)
+        );
+    }
+
+    #[test]
+    fn test_pool_metrics_display_and_serialize() {
+        let metrics = PoolMetrics {
+            connections_created: 7,
+            connections_recycled: 40,
+            create_failures: 2,
+            status: PoolStatus {
+                max_size: 10,
+                size: 5,
+                available: 3,
+                waiting: 0,
+                warmed: 2,
+            },
+        };
+        let text = metrics.to_string();
+        assert!(text.contains("# TYPE heracles_ldap_pool_connections_created_total counter\n"));
+        assert!(text.contains("heracles_ldap_pool_connections_recycled_total 40\n"));
+        assert!(text.contains("heracles_ldap_pool_available 3\n"));
+        assert!(text.contains("# TYPE heracles_ldap_pool_warmed gauge\n"));
+        assert!(text.contains("heracles_ldap_pool_warmed 2\n"));
+
+        let json = serde_json::to_value(&metrics).unwrap();
+        assert_eq!(json["create_failures"], 2);
+        assert_eq!(json["status"]["size"], 5);
+    }
+
     #[test]
     fn test_pool_status_display() {
         let status = PoolStatus {
@@ -235,11 +1603,13 @@ mod tests {
             size: 5,
             available: 3,
             waiting: 2,
+            warmed: 4,
         };
         let display = format!("{}", status);
         assert!(display.contains("max=10"));
         assert!(display.contains("size=5"));
         assert!(display.contains("available=3"));
         assert!(display.contains("waiting=2"));
+        assert!(display.contains("warmed=4"));
     }
 }
@@ -0,0 +1,213 @@
+//! Password policy response control (draft-behera-ldap-password-policy).
+//!
+//! Servers running a password policy overlay attach this control to bind
+//! responses to warn about upcoming expiry, remaining grace logins, or the
+//! reason a bind was refused.
+
+use crate::errors::{HeraclesError, Result};
+use ldap3::asn1::{parse_tag, parse_uint, StructureTag, TagClass, PL};
+use ldap3::controls::{Control, RawControl};
+
+/// OID of the password policy request/response control.
+pub const PPOLICY_OID: &str = "1.3.6.1.4.1.42.2.27.8.5.1";
+
+/// Password policy error reported by the server.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PasswordPolicyError {
+    /// 0 - the password has expired
+    PasswordExpired,
+    /// 1 - the account is locked
+    AccountLocked,
+    /// 2 - the password must be changed after an administrative reset
+    ChangeAfterReset,
+    /// 3 - the user may not change the password
+    PasswordModNotAllowed,
+    /// 4 - the old password must be supplied
+    MustSupplyOldPassword,
+    /// 5 - the new password fails quality checks
+    InsufficientPasswordQuality,
+    /// 6 - the new password is too short
+    PasswordTooShort,
+    /// 7 - the password was changed too recently
+    PasswordTooYoung,
+    /// 8 - the new password is in the history
+    PasswordInHistory,
+    /// Any other value.
+    Other(u64),
+}
+
+impl PasswordPolicyError {
+    fn from_code(code: u64) -> Self {
+        match code {
+            0 => Self::PasswordExpired,
+            1 => Self::AccountLocked,
+            2 => Self::ChangeAfterReset,
+            3 => Self::PasswordModNotAllowed,
+            4 => Self::MustSupplyOldPassword,
+            5 => Self::InsufficientPasswordQuality,
+            6 => Self::PasswordTooShort,
+            7 => Self::PasswordTooYoung,
+            8 => Self::PasswordInHistory,
+            other => Self::Other(other),
+        }
+    }
+}
+
+/// Decoded password policy response control.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PasswordPolicyInfo {
+    /// Seconds until the password expires.
+    pub time_before_expiration: Option<u64>,
+    /// Grace logins left after the password has expired.
+    pub grace_auths_remaining: Option<u64>,
+    /// Reason the operation was refused, if any.
+    pub error: Option<PasswordPolicyError>,
+}
+
+impl PasswordPolicyInfo {
+    /// Decodes the BER value of a password policy response control.
+    ///
+    /// ```text
+    /// PasswordPolicyResponseValue ::= SEQUENCE {
+    ///     warning [0] CHOICE {
+    ///         timeBeforeExpiration [0] INTEGER (0 .. maxInt),
+    ///         graceAuthNsRemaining [1] INTEGER (0 .. maxInt) } OPTIONAL,
+    ///     error   [1] ENUMERATED { ... } OPTIONAL }
+    /// ```
+    pub fn parse(value: &[u8]) -> Result<Self> {
+        let (_, tag) = parse_tag(value).map_err(|_| malformed("invalid BER"))?;
+        let mut info = Self::default();
+
+        for element in tag
+            .expect_constructed()
+            .ok_or_else(|| malformed("not a sequence"))?
+        {
+            if element.class != TagClass::Context {
+                return Err(malformed("unexpected element"));
+            }
+            match element.id {
+                0 => {
+                    let warning = element
+                        .expect_constructed()
+                        .and_then(|mut inner| inner.pop())
+                        .ok_or_else(|| malformed("empty warning"))?;
+                    let id = warning.id;
+                    let value = integer(warning)?;
+                    match id {
+                        0 => info.time_before_expiration = Some(value),
+                        1 => info.grace_auths_remaining = Some(value),
+                        _ => return Err(malformed("unknown warning")),
+                    }
+                }
+                1 => info.error = Some(PasswordPolicyError::from_code(integer(element)?)),
+                _ => return Err(malformed("unexpected element")),
+            }
+        }
+
+        Ok(info)
+    }
+
+    /// Finds and decodes the password policy control among response controls.
+    pub fn from_controls(ctrls: &[Control]) -> Result<Option<Self>> {
+        ctrls
+            .iter()
+            .find(|Control(_, raw)| raw.ctype == PPOLICY_OID)
+            .and_then(|Control(_, raw)| raw.val.as_deref())
+            .map(Self::parse)
+            .transpose()
+    }
+
+    /// Whole days until the password expires, rounded down.
+    pub fn days_before_expiration(&self) -> Option<u64> {
+        self.time_before_expiration.map(|secs| secs / 86_400)
+    }
+}
+
+/// Request control asking the server to return password policy information.
+pub fn request_control() -> RawControl {
+    RawControl {
+        ctype: PPOLICY_OID.to_string(),
+        crit: false,
+        val: None,
+    }
+}
+
+fn integer(tag: StructureTag) -> Result<u64> {
+    match tag.payload {
+        PL::P(bytes) if !bytes.is_empty() && bytes.len() <= 8 => parse_uint(&bytes)
+            .map(|(_, v)| v)
+            .map_err(|_| malformed("invalid integer")),
+        _ => Err(malformed("invalid integer")),
+    }
+}
+
+fn malformed(reason: &str) -> HeraclesError {
+    HeraclesError::LdapBind(format!("Malformed password policy control: {}", reason))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // SEQUENCE { [0] { [1] 3 } }
+    const GRACE: &[u8] = &[0x30, 0x05, 0xa0, 0x03, 0x81, 0x01, 0x03];
+
+    #[test]
+    fn test_parse_grace_logins() {
+        let info = PasswordPolicyInfo::parse(GRACE).unwrap();
+        assert_eq!(info.grace_auths_remaining, Some(3));
+        assert_eq!(info.time_before_expiration, None);
+        assert_eq!(info.error, None);
+    }
+
+    #[test]
+    fn test_parse_expiry_and_error() {
+        // SEQUENCE { [0] { [0] 259200 }, [1] 2 }
+        let value = [
+            0x30, 0x0a, 0xa0, 0x05, 0x80, 0x03, 0x03, 0xf4, 0x80, 0x81, 0x01, 0x02,
+        ];
+        let info = PasswordPolicyInfo::parse(&value).unwrap();
+
+        assert_eq!(info.time_before_expiration, Some(259_200));
+        assert_eq!(info.days_before_expiration(), Some(3));
+        assert_eq!(info.error, Some(PasswordPolicyError::ChangeAfterReset));
+    }
+
+    #[test]
+    fn test_parse_empty_sequence() {
+        let info = PasswordPolicyInfo::parse(&[0x30, 0x00]).unwrap();
+        assert_eq!(info, PasswordPolicyInfo::default());
+    }
+
+    #[test]
+    fn test_parse_malformed() {
+        assert!(PasswordPolicyInfo::parse(&[0x30, 0x03, 0xa0]).is_err());
+        assert!(PasswordPolicyInfo::parse(&[0x30, 0x03, 0x02, 0x01, 0x01]).is_err());
+    }
+
+    #[test]
+    fn test_from_controls() {
+        let ctrls = vec![
+            Control(
+                None,
+                RawControl {
+                    ctype: "1.2.3".into(),
+                    crit: false,
+                    val: Some(vec![0x04, 0x00]),
+                },
+            ),
+            Control(
+                None,
+                RawControl {
+                    ctype: PPOLICY_OID.into(),
+                    crit: false,
+                    val: Some(GRACE.to_vec()),
+                },
+            ),
+        ];
+
+        let info = PasswordPolicyInfo::from_controls(&ctrls).unwrap().unwrap();
+        assert_eq!(info.grace_auths_remaining, Some(3));
+        assert_eq!(PasswordPolicyInfo::from_controls(&[]).unwrap(), None);
+    }
+}
@@ -0,0 +1,346 @@
+//! Referral chasing.
+//!
+//! Servers answer searches for data they don't hold with referral URLs
+//! (RFC 4511 §4.1.10, §4.5.3). When [`LdapConfig::chase_referrals`] is set,
+//! searches follow them on short-lived connections and merge the entries
+//! found there, up to [`LdapConfig::max_referral_depth`] hops.
+//!
+//! Referral URLs come from the server's response, so they are only
+//! followed to configured servers and [`LdapConfig::referral_hosts`] (see
+//! [`is_trusted`]).
+//!
+//! [`LdapConfig::chase_referrals`]: crate::ldap::LdapConfig::chase_referrals
+//! [`LdapConfig::max_referral_depth`]: crate::ldap::LdapConfig::max_referral_depth
+//! [`LdapConfig::referral_hosts`]: crate::ldap::LdapConfig::referral_hosts
+
+use std::collections::{HashSet, VecDeque};
+
+use crate::errors::{HeraclesError, Result};
+use crate::ldap::config::LdapConfig;
+use crate::ldap::operations::{LdapEntry, SearchScope};
+use futures::future::BoxFuture;
+use tracing::debug;
+
+/// A parsed RFC 4516 LDAP URL, as found in referrals.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LdapUrl {
+    /// Server to contact, as `ldap://host[:port]` or `ldaps://host[:port]`.
+    pub server: String,
+    /// Base DN, if the URL names one.
+    pub base: Option<String>,
+    /// Scope, if the URL names one.
+    pub scope: Option<SearchScope>,
+    /// Filter, if the URL names one.
+    pub filter: Option<String>,
+}
+
+impl LdapUrl {
+    /// Parses `scheme://host[:port]/dn?attributes?scope?filter?extensions`.
+    ///
+    /// Every part after the host is optional; the DN and filter are
+    /// percent-decoded. The attribute list is ignored, since a chased
+    /// search keeps the attributes of the original one. A URL without a
+    /// host, with an unknown scope, or with a critical extension fails
+    /// with `LdapSearch`.
+    pub fn parse(url: &str) -> Result<Self> {
+        let invalid = |reason: &str| {
+            HeraclesError::LdapSearch(format!("Invalid referral URL {}: {}", url, reason))
+        };
+
+        let (scheme, rest) = url.split_once("://").ok_or_else(|| invalid("no scheme"))?;
+        let scheme = scheme.to_ascii_lowercase();
+        if scheme != "ldap" && scheme != "ldaps" {
+            return Err(invalid("scheme must be ldap or ldaps"));
+        }
+
+        let (host, path) = rest.split_once('/').unwrap_or((rest, ""));
+        if host.is_empty() {
+            return Err(invalid("no host"));
+        }
+
+        let mut parts = path.split('?');
+        let base = parts.next().map(percent_decode).transpose()?;
+        let _attributes = parts.next();
+        let scope = match parts.next().unwrap_or("") {
+            "" => None,
+            "base" => Some(SearchScope::Base),
+            "one" => Some(SearchScope::OneLevel),
+            "sub" => Some(SearchScope::Subtree),
+            _ => return Err(invalid("unknown scope")),
+        };
+        let filter = parts.next().map(percent_decode).transpose()?;
+        if parts
+            .next()
+            .is_some_and(|exts| exts.split(',').any(|e| e.starts_with('!')))
+        {
+            return Err(invalid("unsupported critical extension"));
+        }
+
+        Ok(Self {
+            server: format!("{}://{}", scheme, host),
+            base: base.filter(|b| !b.is_empty()),
+            scope,
+            filter: filter.filter(|f| !f.is_empty()),
+        })
+    }
+}
+
+/// Returns whether referrals may be followed to `server`
+/// (`scheme://host[:port]`).
+///
+/// `server` must be one of the configured servers (`uri`, `uris`,
+/// `read_uris`), compared by host and port, or match an entry of
+/// `referral_hosts`; an entry without a port matches any port.
+pub fn is_trusted(server: &str, config: &LdapConfig) -> bool {
+    let Some((host, port)) = authority(server) else {
+        return false;
+    };
+    let configured = config
+        .server_uris()
+        .into_iter()
+        .chain(config.read_uris.iter().map(String::as_str))
+        .filter_map(authority)
+        .any(|(h, p)| h == host && p == port);
+    configured
+        || config.referral_hosts.iter().any(|entry| {
+            let (h, p) = split_host_port(entry);
+            h.eq_ignore_ascii_case(&host) && p.is_none_or(|p| p == port)
+        })
+}
+
+/// Lowercased host and port of an LDAP URI, defaulting the port by scheme.
+fn authority(uri: &str) -> Option<(String, u16)> {
+    let (scheme, rest) = uri.split_once("://")?;
+    let default_port = match scheme.to_ascii_lowercase().as_str() {
+        "ldap" => 389,
+        "ldaps" => 636,
+        _ => return None,
+    };
+    let hostport = rest.split(['/', '?']).next().unwrap_or("");
+    let (host, port) = split_host_port(hostport);
+    if host.is_empty() {
+        return None;
+    }
+    Some((host.to_ascii_lowercase(), port.unwrap_or(default_port)))
+}
+
+/// Splits `host[:port]`, keeping bracketed IPv6 addresses whole.
+fn split_host_port(value: &str) -> (&str, Option<u16>) {
+    let colon = match value.rfind(']') {
+        Some(end) => value[end..].find(':').map(|i| end + i),
+        None => value.rfind(':'),
+    };
+    match colon {
+        Some(i) => match value[i + 1..].parse() {
+            Ok(port) => (&value[..i], Some(port)),
+            Err(_) => (value, None),
+        },
+        None => (value, None),
+    }
+}
+
+/// Decodes `%XX` escapes.
+fn percent_decode(value: &str) -> Result<String> {
+    let bytes = value.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' {
+            let hex = value
+                .get(i + 1..i + 3)
+                .and_then(|h| u8::from_str_radix(h, 16).ok())
+                .ok_or_else(|| {
+                    HeraclesError::LdapSearch(format!("Invalid percent-encoding in {}", value))
+                })?;
+            out.push(hex);
+            i += 3;
+        } else {
+            out.push(bytes[i]);
+            i += 1;
+        }
+    }
+    String::from_utf8(out)
+        .map_err(|_| HeraclesError::LdapSearch(format!("Non-UTF-8 value in {}", value)))
+}
+
+/// Entries and further referrals found on a referred server.
+pub(crate) type Fetched = Result<(Vec<LdapEntry>, Vec<String>)>;
+
+/// Follows `referrals` breadth-first, merging the entries `fetch` returns.
+///
+/// `fetch` searches the server a URL points at and returns its entries
+/// plus any further referrals, which are followed one hop deeper. Each URL
+/// is followed once, so referral loops end; a referral more than
+/// `max_depth` hops from the original search fails with `LdapSearch`
+/// rather than being dropped.
+pub(crate) async fn follow<F>(
+    referrals: Vec<String>,
+    max_depth: u32,
+    mut fetch: F,
+) -> Result<Vec<LdapEntry>>
+where
+    F: FnMut(LdapUrl) -> BoxFuture<'static, Fetched>,
+{
+    let mut seen = HashSet::new();
+    let mut queue: VecDeque<(String, u32)> = referrals.into_iter().map(|r| (r, 1)).collect();
+    let mut entries = Vec::new();
+
+    while let Some((url, depth)) = queue.pop_front() {
+        if !seen.insert(url.clone()) {
+            debug!("Referral already followed: {}", url);
+            continue;
+        }
+        if depth > max_depth {
+            return Err(HeraclesError::LdapSearch(format!(
+                "Referral depth limit ({}) exceeded at {}",
+                max_depth, url
+            )));
+        }
+
+        debug!("Following referral (depth {}): {}", depth, url);
+        let (found, more) = fetch(LdapUrl::parse(&url)?).await?;
+        entries.extend(found);
+        queue.extend(more.into_iter().map(|r| (r, depth + 1)));
+    }
+    Ok(entries)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_parse_full_url() {
+        let url = LdapUrl::parse(
+            "ldaps://dc2.example.com:636/ou=people,dc=example,dc=com?cn,mail?one?(uid=j%20doe)",
+        )
+        .unwrap();
+        assert_eq!(
+            url,
+            LdapUrl {
+                server: "ldaps://dc2.example.com:636".into(),
+                base: Some("ou=people,dc=example,dc=com".into()),
+                scope: Some(SearchScope::OneLevel),
+                filter: Some("(uid=j doe)".into()),
+            }
+        );
+    }
+
+    #[test]
+    fn test_parse_minimal_urls() {
+        let url = LdapUrl::parse("ldap://dc2.example.com").unwrap();
+        assert_eq!(url.server, "ldap://dc2.example.com");
+        assert_eq!((url.base, url.scope, url.filter), (None, None, None));
+
+        let url = LdapUrl::parse("LDAP://dc2/o=Caf%C3%A9??sub").unwrap();
+        assert_eq!(url.server, "ldap://dc2");
+        assert_eq!(url.base.as_deref(), Some("o=Café"));
+        assert_eq!(url.scope, Some(SearchScope::Subtree));
+    }
+
+    #[test]
+    fn test_parse_rejects_invalid_urls() {
+        for url in [
+            "dc2.example.com/dc=x",
+            "http://dc2/dc=x",
+            "ldap:///dc=x",
+            "ldap://dc2/dc=x??children",
+            "ldap://dc2/dc=%zz",
+            "ldap://dc2/dc=x????!1.2.3.4",
+        ] {
+            assert!(
+                matches!(LdapUrl::parse(url), Err(HeraclesError::LdapSearch(_))),
+                "{}",
+                url
+            );
+        }
+        assert!(LdapUrl::parse("ldap://dc2/dc=x????1.2.3.4").is_ok());
+    }
+
+    #[test]
+    fn test_is_trusted() {
+        let config = LdapConfig {
+            read_uris: vec!["ldap://replica.example.com".into()],
+            referral_hosts: vec!["dc2.example.com".into(), "[::1]:3389".into()],
+            ..LdapConfig::default().with_uris(["ldaps://DC1.example.com", "ldap://dc3:1389"])
+        };
+
+        for server in [
+            "ldaps://dc1.example.com:636",
+            "ldap://dc3:1389",
+            "ldap://replica.example.com:389",
+            "ldap://dc2.example.com:3268",
+            "ldaps://DC2.example.com",
+            "ldap://[::1]:3389",
+        ] {
+            assert!(is_trusted(server, &config), "{}", server);
+        }
+        for server in [
+            "ldap://dc1.example.com",
+            "ldap://dc3",
+            "ldap://evil.example.net",
+            "ldap://dc2.example.com.evil.net",
+            "ldap://[::1]",
+            "http://dc2.example.com",
+        ] {
+            assert!(!is_trusted(server, &config), "{}", server);
+        }
+    }
+
+    /// Fetcher over a fixed map of server -> (entry DNs, referrals).
+    fn directory(
+        servers: &[(&str, &[&str], &[&str])],
+    ) -> impl FnMut(LdapUrl) -> BoxFuture<'static, Fetched> {
+        let servers: HashMap<String, (Vec<LdapEntry>, Vec<String>)> = servers
+            .iter()
+            .map(|(server, dns, refs)| {
+                (
+                    server.to_string(),
+                    (
+                        dns.iter().map(|dn| LdapEntry::new(*dn)).collect(),
+                        refs.iter().map(|r| r.to_string()).collect(),
+                    ),
+                )
+            })
+            .collect();
+        move |url| {
+            let result = servers[&url.server].clone();
+            Box::pin(async move { Ok(result) })
+        }
+    }
+
+    #[tokio::test]
+    async fn test_follow_merges_entries_and_stops_loops() {
+        let fetch = directory(&[
+            ("ldap://a", &["cn=1,dc=a"], &["ldap://b/dc=b"]),
+            ("ldap://b", &["cn=2,dc=b"], &["ldap://a/dc=a"]),
+        ]);
+        let entries = follow(vec!["ldap://a/dc=a".into()], 5, fetch)
+            .await
+            .unwrap();
+        let dns: Vec<&str> = entries.iter().map(|e| e.dn.as_str()).collect();
+        assert_eq!(dns, vec!["cn=1,dc=a", "cn=2,dc=b"]);
+    }
+
+    #[tokio::test]
+    async fn test_follow_enforces_depth_cap() {
+        let servers: &[(&str, &[&str], &[&str])] = &[
+            ("ldap://a", &["cn=1,dc=a"], &["ldap://b/dc=b"]),
+            ("ldap://b", &["cn=2,dc=b"], &["ldap://c/dc=c"]),
+            ("ldap://c", &["cn=3,dc=c"], &[]),
+        ];
+
+        let entries = follow(vec!["ldap://a/dc=a".into()], 3, directory(servers))
+            .await
+            .unwrap();
+        assert_eq!(entries.len(), 3);
+
+        let err = follow(vec!["ldap://a/dc=a".into()], 2, directory(servers))
+            .await
+            .unwrap_err();
+        assert!(
+            matches!(err, HeraclesError::LdapSearch(ref m) if m.contains("depth limit (2)") && m.contains("ldap://c/dc=c"))
+        );
+    }
+}
@@ -0,0 +1,236 @@
+//! Typed LDAP result codes (RFC 4511, Appendix A).
+
+use std::fmt;
+
+/// An LDAP result code.
+///
+/// Codes without a named variant are preserved in `Other`, so conversion
+/// from `u32` never fails and round-trips exactly.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LdapResultCode {
+    /// 0 - success
+    Success,
+    /// 1 - operationsError
+    OperationsError,
+    /// 2 - protocolError
+    ProtocolError,
+    /// 3 - timeLimitExceeded
+    TimeLimitExceeded,
+    /// 4 - sizeLimitExceeded
+    SizeLimitExceeded,
+    /// 5 - compareFalse
+    CompareFalse,
+    /// 6 - compareTrue
+    CompareTrue,
+    /// 7 - authMethodNotSupported
+    AuthMethodNotSupported,
+    /// 8 - strongerAuthRequired
+    StrongerAuthRequired,
+    /// 10 - referral
+    Referral,
+    /// 11 - adminLimitExceeded
+    AdminLimitExceeded,
+    /// 12 - unavailableCriticalExtension
+    UnavailableCriticalExtension,
+    /// 13 - confidentialityRequired
+    ConfidentialityRequired,
+    /// 14 - saslBindInProgress
+    SaslBindInProgress,
+    /// 16 - noSuchAttribute
+    NoSuchAttribute,
+    /// 17 - undefinedAttributeType
+    UndefinedAttributeType,
+    /// 18 - inappropriateMatching
+    InappropriateMatching,
+    /// 19 - constraintViolation
+    ConstraintViolation,
+    /// 20 - attributeOrValueExists
+    AttributeOrValueExists,
+    /// 21 - invalidAttributeSyntax
+    InvalidAttributeSyntax,
+    /// 32 - noSuchObject
+    NoSuchObject,
+    /// 33 - aliasProblem
+    AliasProblem,
+    /// 34 - invalidDNSyntax
+    InvalidDnSyntax,
+    /// 36 - aliasDereferencingProblem
+    AliasDereferencingProblem,
+    /// 48 - inappropriateAuthentication
+    InappropriateAuthentication,
+    /// 49 - invalidCredentials
+    InvalidCredentials,
+    /// 50 - insufficientAccessRights
+    InsufficientAccessRights,
+    /// 51 - busy
+    Busy,
+    /// 52 - unavailable
+    Unavailable,
+    /// 53 - unwillingToPerform
+    UnwillingToPerform,
+    /// 54 - loopDetect
+    LoopDetect,
+    /// 64 - namingViolation
+    NamingViolation,
+    /// 65 - objectClassViolation
+    ObjectClassViolation,
+    /// 66 - notAllowedOnNonLeaf
+    NotAllowedOnNonLeaf,
+    /// 67 - notAllowedOnRDN
+    NotAllowedOnRdn,
+    /// 68 - entryAlreadyExists
+    EntryAlreadyExists,
+    /// 69 - objectClassModsProhibited
+    ObjectClassModsProhibited,
+    /// 71 - affectsMultipleDSAs
+    AffectsMultipleDsas,
+    /// 80 - other
+    OtherError,
+    /// Any code without a named variant.
+    Other(u32),
+}
+
+/// Named variants and their numeric codes.
+const CODES: &[(LdapResultCode, u32)] = &[
+    (LdapResultCode::Success, 0),
+    (LdapResultCode::OperationsError, 1),
+    (LdapResultCode::ProtocolError, 2),
+    (LdapResultCode::TimeLimitExceeded, 3),
+    (LdapResultCode::SizeLimitExceeded, 4),
+    (LdapResultCode::CompareFalse, 5),
+    (LdapResultCode::CompareTrue, 6),
+    (LdapResultCode::AuthMethodNotSupported, 7),
+    (LdapResultCode::StrongerAuthRequired, 8),
+    (LdapResultCode::Referral, 10),
+    (LdapResultCode::AdminLimitExceeded, 11),
+    (LdapResultCode::UnavailableCriticalExtension, 12),
+    (LdapResultCode::ConfidentialityRequired, 13),
+    (LdapResultCode::SaslBindInProgress, 14),
+    (LdapResultCode::NoSuchAttribute, 16),
+    (LdapResultCode::UndefinedAttributeType, 17),
+    (LdapResultCode::InappropriateMatching, 18),
+    (LdapResultCode::ConstraintViolation, 19),
+    (LdapResultCode::AttributeOrValueExists, 20),
+    (LdapResultCode::InvalidAttributeSyntax, 21),
+    (LdapResultCode::NoSuchObject, 32),
+    (LdapResultCode::AliasProblem, 33),
+    (LdapResultCode::InvalidDnSyntax, 34),
+    (LdapResultCode::AliasDereferencingProblem, 36),
+    (LdapResultCode::InappropriateAuthentication, 48),
+    (LdapResultCode::InvalidCredentials, 49),
+    (LdapResultCode::InsufficientAccessRights, 50),
+    (LdapResultCode::Busy, 51),
+    (LdapResultCode::Unavailable, 52),
+    (LdapResultCode::UnwillingToPerform, 53),
+    (LdapResultCode::LoopDetect, 54),
+    (LdapResultCode::NamingViolation, 64),
+    (LdapResultCode::ObjectClassViolation, 65),
+    (LdapResultCode::NotAllowedOnNonLeaf, 66),
+    (LdapResultCode::NotAllowedOnRdn, 67),
+    (LdapResultCode::EntryAlreadyExists, 68),
+    (LdapResultCode::ObjectClassModsProhibited, 69),
+    (LdapResultCode::AffectsMultipleDsas, 71),
+    (LdapResultCode::OtherError, 80),
+];
+
+impl LdapResultCode {
+    /// Converts a numeric result code.
+    pub fn from_u32(code: u32) -> Self {
+        CODES
+            .iter()
+            .find(|(_, c)| *c == code)
+            .map(|(variant, _)| *variant)
+            .unwrap_or(LdapResultCode::Other(code))
+    }
+
+    /// Returns the numeric result code.
+    pub fn to_u32(self) -> u32 {
+        if let LdapResultCode::Other(code) = self {
+            return code;
+        }
+        CODES
+            .iter()
+            .find(|(variant, _)| *variant == self)
+            .map(|(_, c)| *c)
+            .unwrap_or_default()
+    }
+
+    /// Returns the result code carried by an ldap3 error, if any.
+    pub fn from_error(err: &ldap3::LdapError) -> Option<Self> {
+        match err {
+            ldap3::LdapError::LdapResult { result } => Some(Self::from_u32(result.rc)),
+            _ => None,
+        }
+    }
+
+    /// Returns true for `Success`.
+    pub fn is_success(self) -> bool {
+        self == LdapResultCode::Success
+    }
+}
+
+impl From<u32> for LdapResultCode {
+    fn from(code: u32) -> Self {
+        Self::from_u32(code)
+    }
+}
+
+impl From<LdapResultCode> for u32 {
+    fn from(code: LdapResultCode) -> Self {
+        code.to_u32()
+    }
+}
+
+impl fmt::Display for LdapResultCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LdapResultCode::Other(code) => write!(f, "{}", code),
+            named => write!(f, "{:?} ({})", named, named.to_u32()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_common_codes_round_trip() {
+        for (variant, code) in [
+            (LdapResultCode::Success, 0),
+            (LdapResultCode::NoSuchAttribute, 16),
+            (LdapResultCode::ConstraintViolation, 19),
+            (LdapResultCode::NoSuchObject, 32),
+            (LdapResultCode::InvalidCredentials, 49),
+            (LdapResultCode::InsufficientAccessRights, 50),
+            (LdapResultCode::EntryAlreadyExists, 68),
+        ] {
+            assert_eq!(LdapResultCode::from_u32(code), variant);
+            assert_eq!(variant.to_u32(), code);
+        }
+    }
+
+    #[test]
+    fn test_all_named_codes_round_trip() {
+        for (variant, code) in CODES {
+            assert_eq!(LdapResultCode::from_u32(*code), *variant);
+            assert_eq!(u32::from(*variant), *code);
+        }
+    }
+
+    #[test]
+    fn test_unknown_code_preserved() {
+        let code = LdapResultCode::from_u32(4096);
+        assert_eq!(code, LdapResultCode::Other(4096));
+        assert_eq!(code.to_u32(), 4096);
+    }
+
+    #[test]
+    fn test_display() {
+        assert_eq!(
+            LdapResultCode::NoSuchObject.to_string(),
+            "NoSuchObject (32)"
+        );
+        assert_eq!(LdapResultCode::Other(999).to_string(), "999");
+    }
+}
@@ -0,0 +1,198 @@
+//! Server Side Sorting control (RFC 2891).
+//!
+//! Asks the server to order search results by one or more attributes, so
+//! callers don't have to buffer and sort entries themselves.
+
+use crate::errors::{HeraclesError, Result};
+use crate::ldap::result_code::LdapResultCode;
+use ldap3::asn1::{parse_tag, parse_uint, TagClass, Types, PL};
+use ldap3::controls::{Control, RawControl};
+
+/// OID of the sort request control.
+pub const SORT_REQUEST_OID: &str = "1.2.840.113556.1.4.473";
+
+/// OID of the sort response control.
+pub const SORT_RESPONSE_OID: &str = "1.2.840.113556.1.4.474";
+
+/// Builds a critical sort request control from `(attribute, descending)` keys.
+///
+/// ```text
+/// SortKeyList ::= SEQUENCE OF SEQUENCE {
+///     attributeType   AttributeDescription,
+///     orderingRule    [0] MatchingRuleId OPTIONAL,
+///     reverseOrder    [1] BOOLEAN DEFAULT FALSE }
+/// ```
+///
+/// The control is critical, so a server without sorting support fails the
+/// search with `unavailableCriticalExtension` instead of returning
+/// unsorted results.
+pub fn request_control(keys: &[(String, bool)]) -> RawControl {
+    let mut list = Vec::new();
+    for (attr, descending) in keys {
+        let mut key = tlv(0x04, attr.as_bytes());
+        if *descending {
+            key.extend(tlv(0x81, &[0xff]));
+        }
+        list.extend(tlv(0x30, &key));
+    }
+
+    RawControl {
+        ctype: SORT_REQUEST_OID.to_string(),
+        crit: true,
+        val: Some(tlv(0x30, &list)),
+    }
+}
+
+/// Checks the sort response control among search response controls.
+///
+/// ```text
+/// SortResult ::= SEQUENCE {
+///     sortResult      ENUMERATED,
+///     attributeType   [0] AttributeDescription OPTIONAL }
+/// ```
+///
+/// A non-success `sortResult` (e.g. `inappropriateMatching` for an
+/// attribute without an ordering rule) becomes an `LdapSearch` error
+/// naming the attribute when the server reports it. A missing control is
+/// accepted.
+pub fn check_response(ctrls: &[Control]) -> Result<()> {
+    let Some(value) = ctrls
+        .iter()
+        .find(|Control(_, raw)| raw.ctype == SORT_RESPONSE_OID)
+        .and_then(|Control(_, raw)| raw.val.as_deref())
+    else {
+        return Ok(());
+    };
+
+    let (_, tag) = parse_tag(value).map_err(|_| malformed("invalid BER"))?;
+    let mut elements = tag
+        .expect_constructed()
+        .ok_or_else(|| malformed("not a sequence"))?
+        .into_iter();
+
+    let code = match elements.next() {
+        Some(e) if e.class == TagClass::Universal && e.id == Types::Enumerated as u64 => {
+            match e.payload {
+                PL::P(bytes) if !bytes.is_empty() && bytes.len() <= 4 => parse_uint(&bytes)
+                    .map(|(_, v)| v as u32)
+                    .map_err(|_| malformed("invalid sortResult"))?,
+                _ => return Err(malformed("invalid sortResult")),
+            }
+        }
+        _ => return Err(malformed("missing sortResult")),
+    };
+
+    let code = LdapResultCode::from_u32(code);
+    if code.is_success() {
+        return Ok(());
+    }
+
+    let attr = elements
+        .next()
+        .filter(|e| e.class == TagClass::Context && e.id == 0)
+        .and_then(|e| match e.payload {
+            PL::P(bytes) => String::from_utf8(bytes).ok(),
+            PL::C(_) => None,
+        });
+    Err(HeraclesError::LdapSearch(match attr {
+        Some(attr) => format!("Server-side sort failed on {}: {}", attr, code),
+        None => format!("Server-side sort failed: {}", code),
+    }))
+}
+
+/// Encodes a BER tag-length-value with a definite length.
+fn tlv(tag: u8, content: &[u8]) -> Vec<u8> {
+    let mut out = vec![tag];
+    let len = content.len();
+    if len < 0x80 {
+        out.push(len as u8);
+    } else {
+        let bytes: Vec<u8> = len
+            .to_be_bytes()
+            .into_iter()
+            .skip_while(|b| *b == 0)
+            .collect();
+        out.push(0x80 | bytes.len() as u8);
+        out.extend(bytes);
+    }
+    out.extend_from_slice(content);
+    out
+}
+
+fn malformed(reason: &str) -> HeraclesError {
+    HeraclesError::LdapSearch(format!("Malformed sort response control: {}", reason))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn response(value: &[u8]) -> Control {
+        Control(
+            None,
+            RawControl {
+                ctype: SORT_RESPONSE_OID.to_string(),
+                crit: false,
+                val: Some(value.to_vec()),
+            },
+        )
+    }
+
+    #[test]
+    fn test_request_control_encoding() {
+        let control = request_control(&[("sn".to_string(), false), ("cn".to_string(), true)]);
+        assert_eq!(control.ctype, SORT_REQUEST_OID);
+        assert!(control.crit);
+        assert_eq!(
+            control.val.unwrap(),
+            [
+                0x30, 0x0f, // SortKeyList
+                0x30, 0x04, 0x04, 0x02, b's', b'n', // sn ascending
+                0x30, 0x07, 0x04, 0x02, b'c', b'n', 0x81, 0x01, 0xff, // cn descending
+            ]
+        );
+    }
+
+    #[test]
+    fn test_request_control_round_trips_through_ber_parser() {
+        let long_attr = "a".repeat(200);
+        let control = request_control(&[(long_attr.clone(), true)]);
+        let (_, tag) = parse_tag(&control.val.unwrap()).unwrap();
+        let key = tag.expect_constructed().unwrap().pop().unwrap();
+        let mut fields = key.expect_constructed().unwrap().into_iter();
+        assert_eq!(
+            fields.next().unwrap().expect_primitive().unwrap(),
+            long_attr.as_bytes()
+        );
+        let reverse = fields.next().unwrap();
+        assert_eq!((reverse.class, reverse.id), (TagClass::Context, 1));
+    }
+
+    #[test]
+    fn test_check_response_success() {
+        assert!(check_response(&[]).is_ok());
+        assert!(check_response(&[response(&[0x30, 0x03, 0x0a, 0x01, 0x00])]).is_ok());
+    }
+
+    #[test]
+    fn test_check_response_failure_names_attribute() {
+        // inappropriateMatching (18) on "jpegPhoto"
+        let mut value = vec![0x30, 0x0e, 0x0a, 0x01, 0x12, 0x80, 0x09];
+        value.extend_from_slice(b"jpegPhoto");
+        let err = check_response(&[response(&value)]).unwrap_err();
+        assert!(
+            matches!(err, HeraclesError::LdapSearch(ref m) if m.contains("jpegPhoto") && m.contains("InappropriateMatching"))
+        );
+
+        let err = check_response(&[response(&[0x30, 0x03, 0x0a, 0x01, 0x35])]).unwrap_err();
+        assert!(
+            matches!(err, HeraclesError::LdapSearch(ref m) if m.contains("UnwillingToPerform"))
+        );
+    }
+
+    #[test]
+    fn test_check_response_malformed() {
+        assert!(check_response(&[response(&[0x30, 0x03, 0x02, 0x01, 0x00])]).is_err());
+        assert!(check_response(&[response(&[0x04, 0x00])]).is_err());
+    }
+}
@@ -0,0 +1,229 @@
+//! DNS SRV discovery of LDAP servers (RFC 2782).
+//!
+//! Looks up `_ldaps._tcp.<domain>` and `_ldap._tcp.<domain>` the way Active
+//! Directory clients do, so deployments can move servers without touching
+//! the configuration. Requires the `srv` feature.
+
+use crate::errors::{HeraclesError, Result};
+use crate::ldap::config::LdapConfig;
+use hickory_resolver::error::ResolveErrorKind;
+use hickory_resolver::TokioAsyncResolver;
+use rand::Rng;
+use tracing::debug;
+
+/// Port of LDAP over TLS; `_ldap._tcp` targets on it are reached via `ldaps://`.
+const LDAPS_PORT: u16 = 636;
+
+/// One SRV answer.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct SrvTarget {
+    priority: u16,
+    weight: u16,
+    port: u16,
+    target: String,
+}
+
+impl LdapConfig {
+    /// Discovers the servers of `domain` from its SRV records.
+    ///
+    /// `_ldaps._tcp` records are preferred when the domain publishes any;
+    /// otherwise `_ldap._tcp` records are used, with targets on port 636
+    /// reached over `ldaps://`. Targets are ordered by priority, then by a
+    /// weighted shuffle within each priority, and stored in `uris` for
+    /// failover. The base DN is derived from the domain (`example.com`
+    /// gives `dc=example,dc=com`); bind credentials are left empty for the
+    /// caller to fill in.
+    pub async fn from_srv(domain: &str) -> Result<LdapConfig> {
+        let domain = domain.trim_end_matches('.');
+        let resolver = TokioAsyncResolver::tokio_from_system_conf()
+            .map_err(|e| HeraclesError::Configuration(format!("DNS resolver: {}", e)))?;
+
+        let ldaps = lookup(&resolver, &format!("_ldaps._tcp.{}.", domain)).await?;
+        let ldap = if ldaps.is_empty() {
+            lookup(&resolver, &format!("_ldap._tcp.{}.", domain)).await?
+        } else {
+            Vec::new()
+        };
+
+        let uris = srv_uris(&ldaps, &ldap, &mut rand::thread_rng());
+        if uris.is_empty() {
+            return Err(HeraclesError::Configuration(format!(
+                "No LDAP SRV records found for {}",
+                domain
+            )));
+        }
+        debug!("Discovered LDAP servers for {}: {:?}", domain, uris);
+
+        Ok(LdapConfig {
+            base_dn: domain_base_dn(domain),
+            bind_dn: String::new(),
+            ..LdapConfig::default()
+        }
+        .with_uris(uris))
+    }
+}
+
+/// Resolves the SRV records of `name`, treating "no records" as empty.
+async fn lookup(resolver: &TokioAsyncResolver, name: &str) -> Result<Vec<SrvTarget>> {
+    match resolver.srv_lookup(name).await {
+        Ok(answer) => Ok(answer
+            .iter()
+            .map(|srv| SrvTarget {
+                priority: srv.priority(),
+                weight: srv.weight(),
+                port: srv.port(),
+                target: srv.target().to_utf8(),
+            })
+            .collect()),
+        Err(e) if matches!(e.kind(), ResolveErrorKind::NoRecordsFound { .. }) => Ok(Vec::new()),
+        Err(e) => Err(HeraclesError::LdapConnection(format!(
+            "SRV lookup for {} failed: {}",
+            name, e
+        ))),
+    }
+}
+
+/// Builds the ordered URI list from `_ldaps._tcp` and `_ldap._tcp` answers.
+fn srv_uris<R: Rng>(ldaps: &[SrvTarget], ldap: &[SrvTarget], rng: &mut R) -> Vec<String> {
+    let secure = order_targets(ldaps.to_vec(), rng)
+        .into_iter()
+        .map(|t| format!("ldaps://{}:{}", t.target, t.port));
+    let plain = order_targets(ldap.to_vec(), rng).into_iter().map(|t| {
+        let scheme = if t.port == LDAPS_PORT {
+            "ldaps"
+        } else {
+            "ldap"
+        };
+        format!("{}://{}:{}", scheme, t.target, t.port)
+    });
+    secure.chain(plain).collect()
+}
+
+/// Orders targets by priority, then by weighted selection within each
+/// priority as described in RFC 2782. A target of "." (service not
+/// available) is dropped.
+fn order_targets<R: Rng>(targets: Vec<SrvTarget>, rng: &mut R) -> Vec<SrvTarget> {
+    let mut targets: Vec<SrvTarget> = targets
+        .into_iter()
+        .filter(|t| t.target != ".")
+        .map(|t| SrvTarget {
+            target: t.target.trim_end_matches('.').to_string(),
+            ..t
+        })
+        .collect();
+    // Zero-weight targets first, so they only win when the draw is 0.
+    targets.sort_by_key(|t| (t.priority, t.weight != 0));
+
+    let mut ordered = Vec::with_capacity(targets.len());
+    while !targets.is_empty() {
+        let priority = targets[0].priority;
+        let end = targets
+            .iter()
+            .position(|t| t.priority != priority)
+            .unwrap_or(targets.len());
+        let mut group: Vec<SrvTarget> = targets.drain(..end).collect();
+
+        while !group.is_empty() {
+            let total: u32 = group.iter().map(|t| u32::from(t.weight)).sum();
+            let draw = rng.gen_range(0..=total);
+            let mut running = 0;
+            let index = group
+                .iter()
+                .position(|t| {
+                    running += u32::from(t.weight);
+                    running >= draw
+                })
+                .unwrap_or(0);
+            ordered.push(group.remove(index));
+        }
+    }
+    ordered
+}
+
+/// Maps a DNS domain to its `dc=` base DN.
+fn domain_base_dn(domain: &str) -> String {
+    domain
+        .split('.')
+        .filter(|label| !label.is_empty())
+        .map(|label| format!("dc={}", label))
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    fn target(priority: u16, weight: u16, port: u16, host: &str) -> SrvTarget {
+        SrvTarget {
+            priority,
+            weight,
+            port,
+            target: host.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_srv_uris_orders_by_priority() {
+        let answer = vec![
+            target(20, 0, 389, "backup.example.com."),
+            target(10, 100, 389, "dc1.example.com."),
+            target(30, 0, 636, "legacy.example.com."),
+            target(0, 0, 389, "."),
+        ];
+        let uris = srv_uris(&[], &answer, &mut StdRng::seed_from_u64(7));
+        assert_eq!(
+            uris,
+            vec![
+                "ldap://dc1.example.com:389",
+                "ldap://backup.example.com:389",
+                "ldaps://legacy.example.com:636",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_srv_uris_prefers_ldaps_records() {
+        let uris = srv_uris(
+            &[target(10, 0, 636, "dc1.example.com.")],
+            &[target(0, 0, 389, "dc2.example.com.")],
+            &mut StdRng::seed_from_u64(7),
+        );
+        assert_eq!(
+            uris,
+            vec!["ldaps://dc1.example.com:636", "ldap://dc2.example.com:389"]
+        );
+    }
+
+    #[test]
+    fn test_order_targets_weighted_within_priority() {
+        let mut rng = StdRng::seed_from_u64(42);
+        let answer = vec![
+            target(10, 1, 389, "light"),
+            target(10, 99, 389, "heavy"),
+            target(20, 50, 389, "fallback"),
+        ];
+
+        let mut heavy_first = 0;
+        for _ in 0..200 {
+            let ordered = order_targets(answer.clone(), &mut rng);
+            assert_eq!(ordered.len(), 3);
+            assert_eq!(ordered[2].target, "fallback");
+            if ordered[0].target == "heavy" {
+                heavy_first += 1;
+            }
+        }
+        assert!(heavy_first > 180, "heavy first {} times", heavy_first);
+    }
+
+    #[test]
+    fn test_domain_base_dn() {
+        assert_eq!(domain_base_dn("example.com"), "dc=example,dc=com");
+        assert_eq!(
+            domain_base_dn("corp.example.com."),
+            "dc=corp,dc=example,dc=com"
+        );
+    }
+}
@@ -0,0 +1,158 @@
+//! In-process LDAP server for wire-level tests.
+//!
+//! Speaks just enough of the protocol to accept simple binds, answer
+//! searches with an empty result and accept add, modify, modify DN and
+//! delete requests, so tests can observe what a client sends and how it
+//! handles result codes.
+
+use ldap3::asn1::{parse_tag, parse_uint, StructureTag, PL};
+use std::sync::{Arc, Mutex};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+
+/// The parts of a search request the tests look at.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct SeenSearch {
+    pub base: String,
+    pub scope: u64,
+    pub size_limit: u64,
+    pub time_limit: u64,
+    /// Present (`(a=*)`) and equality (`(a=v)`) filters; others are `?`.
+    pub filter: String,
+    pub attrs: Vec<String>,
+    /// OIDs of the request controls.
+    pub controls: Vec<String>,
+}
+
+/// Search requests received, in order.
+pub(crate) type SeenSearches = Arc<Mutex<Vec<SeenSearch>>>;
+
+/// Write requests received, in order, as `"<operation> <dn>"`.
+pub(crate) type SeenWrites = Arc<Mutex<Vec<String>>>;
+
+/// Starts a server that accepts any bind and ends every search with
+/// `search_rc`, returning its `ldap://` URI and the searches it has seen.
+pub(crate) async fn spawn(search_rc: u8) -> (String, SeenSearches) {
+    let (uri, searches, _) = spawn_recording(search_rc).await;
+    (uri, searches)
+}
+
+/// Like [`spawn`], also returning the write requests the server accepted.
+pub(crate) async fn spawn_recording(search_rc: u8) -> (String, SeenSearches, SeenWrites) {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let uri = format!("ldap://{}", listener.local_addr().unwrap());
+    let searches = SeenSearches::default();
+    let writes = SeenWrites::default();
+    let (seen, written) = (searches.clone(), writes.clone());
+
+    tokio::spawn(async move {
+        while let Ok((sock, _)) = listener.accept().await {
+            tokio::spawn(serve(sock, search_rc, seen.clone(), written.clone()));
+        }
+    });
+
+    (uri, searches, writes)
+}
+
+async fn serve(mut sock: TcpStream, search_rc: u8, seen: SeenSearches, written: SeenWrites) {
+    while let Some(msg) = read_message(&mut sock).await {
+        let (_, tag) = parse_tag(&msg).unwrap();
+        let mut parts = tag.expect_constructed().unwrap().into_iter();
+        let id = parts.next().unwrap().expect_primitive().unwrap();
+        let op = parts.next().unwrap();
+        let controls = parts.next().map(control_oids).unwrap_or_default();
+        let (response_tag, rc) = match op.id {
+            0 => (0x61, 0),
+            3 => {
+                let fields = op.expect_constructed().unwrap();
+                let int = |i: usize| {
+                    let bytes = fields[i].clone().expect_primitive().unwrap();
+                    parse_uint(&bytes).unwrap().1
+                };
+                seen.lock().unwrap().push(SeenSearch {
+                    base: text(fields[0].clone()),
+                    scope: int(1),
+                    size_limit: int(3),
+                    time_limit: int(4),
+                    filter: filter(fields[6].clone()),
+                    attrs: fields[7]
+                        .clone()
+                        .expect_constructed()
+                        .unwrap()
+                        .into_iter()
+                        .map(text)
+                        .collect(),
+                    controls,
+                });
+                (0x65, search_rc)
+            }
+            // add, modify and modify DN carry the DN first; delete is the DN
+            6 | 8 | 10 | 12 => {
+                let (name, response_tag) = match op.id {
+                    6 => ("modify", 0x67),
+                    8 => ("add", 0x69),
+                    10 => ("delete", 0x6b),
+                    _ => ("modify_dn", 0x6d),
+                };
+                let dn = match op.id {
+                    10 => text(op),
+                    _ => text(op.expect_constructed().unwrap().remove(0)),
+                };
+                written.lock().unwrap().push(format!("{} {}", name, dn));
+                (response_tag, 0)
+            }
+            _ => break,
+        };
+
+        // LDAPResult with empty matchedDN and diagnosticMessage
+        let mut reply = vec![0x02, id.len() as u8];
+        reply.extend_from_slice(&id);
+        reply.extend_from_slice(&[response_tag, 0x07, 0x0a, 0x01, rc, 0x04, 0x00, 0x04, 0x00]);
+        reply.splice(0..0, [0x30, reply.len() as u8]);
+        if sock.write_all(&reply).await.is_err() {
+            break;
+        }
+    }
+}
+
+fn text(tag: StructureTag) -> String {
+    String::from_utf8(tag.expect_primitive().unwrap()).unwrap()
+}
+
+fn filter(tag: StructureTag) -> String {
+    match (tag.id, tag.payload) {
+        (7, PL::P(attr)) => format!("({}=*)", String::from_utf8(attr).unwrap()),
+        (3, PL::C(mut ava)) if ava.len() == 2 => {
+            let value = text(ava.pop().unwrap());
+            format!("({}={})", text(ava.pop().unwrap()), value)
+        }
+        _ => "?".to_string(),
+    }
+}
+
+fn control_oids(controls: StructureTag) -> Vec<String> {
+    controls
+        .expect_constructed()
+        .unwrap()
+        .into_iter()
+        .map(|control| text(control.expect_constructed().unwrap().remove(0)))
+        .collect()
+}
+
+/// Reads one BER-framed LDAP message, or `None` once the client hangs up.
+async fn read_message(sock: &mut TcpStream) -> Option<Vec<u8>> {
+    let mut msg = vec![0u8; 2];
+    sock.read_exact(&mut msg).await.ok()?;
+    let len = if msg[1] & 0x80 == 0 {
+        msg[1] as usize
+    } else {
+        let mut len_bytes = vec![0u8; (msg[1] & 0x7f) as usize];
+        sock.read_exact(&mut len_bytes).await.ok()?;
+        msg.extend_from_slice(&len_bytes);
+        len_bytes.iter().fold(0, |acc, b| (acc << 8) | *b as usize)
+    };
+    let start = msg.len();
+    msg.resize(start + len, 0);
+    sock.read_exact(&mut msg[start..]).await.ok()?;
+    Some(msg)
+}
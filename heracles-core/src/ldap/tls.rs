@@ -0,0 +1,210 @@
+//! TLS client configuration for LDAPS and STARTTLS.
+//!
+//! Turns a [`TlsConfig`] into the rustls configuration handed to ldap3, so
+//! a bad CA bundle or client certificate is reported as a configuration
+//! error before any server is contacted.
+
+use std::fs::File;
+use std::io::BufReader;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::SystemTime;
+
+use crate::errors::{HeraclesError, Result};
+use crate::ldap::config::TlsConfig;
+use rustls::client::{ServerCertVerified, ServerCertVerifier, WebPkiVerifier};
+use rustls::{Certificate, ClientConfig, PrivateKey, RootCertStore, ServerName};
+use rustls_pemfile::Item;
+use tracing::warn;
+
+/// Builds the rustls client configuration for `tls`.
+///
+/// Returns `None` when no CA bundle or client certificate is set, leaving
+/// ldap3 to use the system store (and its own verification switch).
+pub(crate) fn client_config(tls: &TlsConfig) -> Result<Option<Arc<ClientConfig>>> {
+    if !tls.verify {
+        warn!("TLS certificate verification is disabled");
+    }
+    if tls.ca_file.is_none() && tls.client_cert.is_none() && tls.client_key.is_none() {
+        return Ok(None);
+    }
+
+    let verifier: Arc<dyn ServerCertVerifier> = if tls.verify {
+        Arc::new(WebPkiVerifier::new(
+            root_store(tls.ca_file.as_deref())?,
+            None,
+        ))
+    } else {
+        Arc::new(NoVerification)
+    };
+    let builder = ClientConfig::builder()
+        .with_safe_defaults()
+        .with_custom_certificate_verifier(verifier);
+
+    let config = match (&tls.client_cert, &tls.client_key) {
+        (Some(cert), Some(key)) => builder
+            .with_client_auth_cert(read_certs(cert)?, read_key(key)?)
+            .map_err(|e| {
+                HeraclesError::Configuration(format!(
+                    "Invalid TLS client certificate {}: {}",
+                    cert.display(),
+                    e
+                ))
+            })?,
+        (None, None) => builder.with_no_client_auth(),
+        _ => {
+            return Err(HeraclesError::Configuration(
+                "TLS client certificate and key must be set together".into(),
+            ))
+        }
+    };
+    Ok(Some(Arc::new(config)))
+}
+
+/// Loads the trusted CAs from `ca_file`, or the system store.
+fn root_store(ca_file: Option<&Path>) -> Result<RootCertStore> {
+    let mut roots = RootCertStore::empty();
+    match ca_file {
+        Some(path) => {
+            let (added, _) = roots.add_parsable_certificates(&pem_certs(path)?);
+            if added == 0 {
+                return Err(HeraclesError::Configuration(format!(
+                    "No usable CA certificate in TLS CA file {}",
+                    path.display()
+                )));
+            }
+        }
+        None => {
+            let certs = rustls_native_certs::load_native_certs().map_err(|e| {
+                HeraclesError::Configuration(format!("Cannot load system CA store: {}", e))
+            })?;
+            for cert in certs {
+                // Skip certificates rustls cannot use, as ldap3 does.
+                let _ = roots.add(&Certificate(cert.0));
+            }
+        }
+    }
+    Ok(roots)
+}
+
+fn read_certs(path: &Path) -> Result<Vec<Certificate>> {
+    Ok(pem_certs(path)?.into_iter().map(Certificate).collect())
+}
+
+/// Reads the DER certificates of a PEM file, failing if there are none.
+fn pem_certs(path: &Path) -> Result<Vec<Vec<u8>>> {
+    let certs = rustls_pemfile::certs(&mut open(path)?).map_err(|e| unreadable(path, e))?;
+    if certs.is_empty() {
+        return Err(HeraclesError::Configuration(format!(
+            "No PEM certificate found in {}",
+            path.display()
+        )));
+    }
+    Ok(certs)
+}
+
+/// Reads the first PKCS#8, PKCS#1 or SEC1 private key of a PEM file.
+fn read_key(path: &Path) -> Result<PrivateKey> {
+    let items = rustls_pemfile::read_all(&mut open(path)?).map_err(|e| unreadable(path, e))?;
+    items
+        .into_iter()
+        .find_map(|item| match item {
+            Item::PKCS8Key(key) | Item::RSAKey(key) | Item::ECKey(key) => Some(PrivateKey(key)),
+            _ => None,
+        })
+        .ok_or_else(|| {
+            HeraclesError::Configuration(format!("No PEM private key found in {}", path.display()))
+        })
+}
+
+fn open(path: &Path) -> Result<BufReader<File>> {
+    File::open(path)
+        .map(BufReader::new)
+        .map_err(|e| unreadable(path, e))
+}
+
+fn unreadable(path: &Path, err: std::io::Error) -> HeraclesError {
+    HeraclesError::Configuration(format!("Cannot read TLS file {}: {}", path.display(), err))
+}
+
+/// Accepts any server certificate, for `verify: false`.
+struct NoVerification;
+
+impl ServerCertVerifier for NoVerification {
+    fn verify_server_cert(
+        &self,
+        _end_entity: &Certificate,
+        _intermediates: &[Certificate],
+        _server_name: &ServerName,
+        _scts: &mut dyn Iterator<Item = &[u8]>,
+        _ocsp_response: &[u8],
+        _now: SystemTime,
+    ) -> std::result::Result<ServerCertVerified, rustls::Error> {
+        Ok(ServerCertVerified::assertion())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    /// Writes `content` to a file unique to this test run.
+    fn temp_file(name: &str, content: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!("heracles-{}-{}", std::process::id(), name));
+        std::fs::write(&path, content).unwrap();
+        path
+    }
+
+    fn config_error(result: Result<Option<Arc<ClientConfig>>>) -> String {
+        match result {
+            Err(HeraclesError::Configuration(msg)) => msg,
+            other => panic!(
+                "expected a Configuration error, got {:?}",
+                other.map(|c| c.is_some())
+            ),
+        }
+    }
+
+    #[test]
+    fn test_default_uses_ldap3_config() {
+        assert!(client_config(&TlsConfig::default()).unwrap().is_none());
+        let lab = TlsConfig {
+            verify: false,
+            ..Default::default()
+        };
+        assert!(client_config(&lab).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_missing_ca_file() {
+        let tls = TlsConfig {
+            ca_file: Some("/nonexistent/heracles/ca.pem".into()),
+            ..Default::default()
+        };
+        let msg = config_error(client_config(&tls));
+        assert!(msg.contains("/nonexistent/heracles/ca.pem"), "{}", msg);
+    }
+
+    #[test]
+    fn test_ca_file_without_certificates() {
+        let path = temp_file("empty-ca.pem", "not a certificate\n");
+        let tls = TlsConfig {
+            ca_file: Some(path.clone()),
+            ..Default::default()
+        };
+        let msg = config_error(client_config(&tls));
+        assert!(msg.contains("No PEM certificate"), "{}", msg);
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_client_cert_requires_key() {
+        let tls = TlsConfig {
+            client_cert: Some("/nonexistent/heracles/client.pem".into()),
+            ..Default::default()
+        };
+        let msg = config_error(client_config(&tls));
+        assert!(msg.contains("must be set together"), "{}", msg);
+    }
+}
@@ -5,30 +5,43 @@
 
 use pyo3::exceptions::{PyConnectionError, PyRuntimeError, PyValueError};
 use pyo3::prelude::*;
-use pyo3::types::PyDict;
+use pyo3::types::{PyBytes, PyDict};
 use std::collections::HashMap;
 use std::sync::Arc;
 use tokio::sync::Mutex;
 
 use crate::acl::{compile as rust_compile_acl, AclRow, AttrRuleRow, PermissionBitmap, UserAcl};
+use crate::crypto::audit::classify_userpassword as rust_classify_userpassword;
 use crate::crypto::password::{
-    hash_password as rust_hash_password, verify_password as rust_verify_password, HashMethod,
-    PasswordHash,
+    benchmark_method as rust_benchmark_method,
+    hash_password_with_params as rust_hash_password_with_params, needs_rehash as rust_needs_rehash,
+    nt_hash as rust_nt_hash, verify as rust_verify,
+    verify_password_detailed as rust_verify_password_detailed, Argon2Params, HashMethod,
+    HashParams, PasswordHash,
 };
+use crate::errors::HeraclesError;
 use crate::ldap::config::LdapConfig;
 use crate::ldap::connection::LdapConnection;
 use crate::ldap::dn::{
     escape_dn_value as rust_escape_dn_value, escape_filter_value as rust_escape_filter_value,
     DistinguishedName,
 };
-use crate::ldap::operations::{LdapEntry as RustLdapEntry, LdapModification};
+use crate::ldap::operations::{
+    LdapEntry as RustLdapEntry, LdapModification, SearchBuilder, SearchScope,
+};
 
 /// Registers the Python module.
 pub fn register_module(_py: Python<'_>, m: &PyModule) -> PyResult<()> {
     // Password functions
     m.add_function(wrap_pyfunction!(hash_password, m)?)?;
     m.add_function(wrap_pyfunction!(verify_password, m)?)?;
+    m.add_function(wrap_pyfunction!(verify_password_detailed, m)?)?;
     m.add_function(wrap_pyfunction!(detect_hash_method, m)?)?;
+    m.add_function(wrap_pyfunction!(needs_rehash, m)?)?;
+    m.add_function(wrap_pyfunction!(classify_userpassword, m)?)?;
+    m.add_function(wrap_pyfunction!(nt_hash, m)?)?;
+    m.add_function(wrap_pyfunction!(hash_methods, m)?)?;
+    m.add_function(wrap_pyfunction!(benchmark_method, m)?)?;
 
     // DN utilities
     m.add_function(wrap_pyfunction!(escape_dn_value, m)?)?;
@@ -53,6 +66,39 @@ pub fn register_module(_py: Python<'_>, m: &PyModule) -> PyResult<()> {
     Ok(())
 }
 
+/// Connects, binds and stores the connection in `slot`.
+async fn open_connection(config: LdapConfig, slot: &Mutex<Option<LdapConnection>>) -> PyResult<()> {
+    let mut conn = LdapConnection::new(config)
+        .await
+        .map_err(|e| PyConnectionError::new_err(e.to_string()))?;
+
+    conn.bind()
+        .await
+        .map_err(|e| PyConnectionError::new_err(e.to_string()))?;
+
+    let mut guard = slot.lock().await;
+    *guard = Some(conn);
+
+    Ok(())
+}
+
+/// Takes the connection out of `slot`, if any, and closes it.
+///
+/// The slot is always emptied, even when closing fails.
+async fn release_connection<C, F, Fut>(slot: &Mutex<Option<C>>, close: F) -> PyResult<()>
+where
+    F: FnOnce(C) -> Fut,
+    Fut: std::future::Future<Output = crate::errors::Result<()>>,
+{
+    let conn = slot.lock().await.take();
+    if let Some(conn) = conn {
+        close(conn)
+            .await
+            .map_err(|e| PyRuntimeError::new_err(e.to_string()))?;
+    }
+    Ok(())
+}
+
 /// Supported password hash methods.
 #[pyclass(name = "HashMethod")]
 #[derive(Clone)]
@@ -71,6 +117,7 @@ pub struct PyLdapEntry {
     #[pyo3(get)]
     dn: String,
     attributes: HashMap<String, Vec<String>>,
+    bin_attributes: HashMap<String, Vec<Vec<u8>>>,
 }
 
 #[pymethods]
@@ -99,6 +146,33 @@ impl PyLdapEntry {
         self.attributes.get(attr).cloned().unwrap_or_default()
     }
 
+    /// Get all binary attributes as a dictionary of lists of bytes.
+    #[getter]
+    fn binary_attributes(&self, py: Python<'_>) -> PyResult<PyObject> {
+        let dict = PyDict::new(py);
+        for (key, values) in &self.bin_attributes {
+            let values: Vec<&PyBytes> = values.iter().map(|v| PyBytes::new(py, v)).collect();
+            dict.set_item(key, values)?;
+        }
+        Ok(dict.into())
+    }
+
+    /// Get the first value of a binary attribute as bytes.
+    ///
+    /// Also finds the attribute under its ";binary" option name.
+    fn get_binary<'py>(&self, py: Python<'py>, attr: &str) -> Option<&'py PyBytes> {
+        self.binary_values(attr)
+            .and_then(|v| v.first())
+            .map(|v| PyBytes::new(py, v))
+    }
+
+    /// Get all values of a binary attribute as bytes.
+    fn get_all_binary<'py>(&self, py: Python<'py>, attr: &str) -> Vec<&'py PyBytes> {
+        self.binary_values(attr)
+            .map(|values| values.iter().map(|v| PyBytes::new(py, v)).collect())
+            .unwrap_or_default()
+    }
+
     /// Check if entry has a specific objectClass.
     fn has_object_class(&self, object_class: &str) -> bool {
         self.attributes
@@ -112,11 +186,20 @@ impl PyLdapEntry {
     }
 }
 
+impl PyLdapEntry {
+    fn binary_values(&self, attr: &str) -> Option<&Vec<Vec<u8>>> {
+        self.bin_attributes
+            .get(attr)
+            .or_else(|| self.bin_attributes.get(&format!("{};binary", attr)))
+    }
+}
+
 impl From<RustLdapEntry> for PyLdapEntry {
     fn from(entry: RustLdapEntry) -> Self {
         Self {
             dn: entry.dn,
             attributes: entry.attributes,
+            bin_attributes: entry.bin_attributes,
         }
     }
 }
@@ -171,33 +254,61 @@ impl PyLdapConnection {
         let connection = self.connection.clone();
 
         pyo3_asyncio::tokio::future_into_py(py, async move {
-            let mut conn = LdapConnection::new(config)
-                .await
-                .map_err(|e| PyConnectionError::new_err(e.to_string()))?;
+            open_connection(config, &connection).await
+        })
+    }
 
-            conn.bind()
-                .await
-                .map_err(|e| PyConnectionError::new_err(e.to_string()))?;
+    /// Disconnect from the LDAP server.
+    fn disconnect<'py>(&self, py: Python<'py>) -> PyResult<&'py PyAny> {
+        let connection = self.connection.clone();
 
-            let mut guard = connection.lock().await;
-            *guard = Some(conn);
+        pyo3_asyncio::tokio::future_into_py(py, async move {
+            release_connection(&connection, |mut conn: LdapConnection| async move {
+                conn.unbind().await
+            })
+            .await
+        })
+    }
+
+    /// Connect and bind on entering an `async with` block.
+    ///
+    /// Example:
+    ///     >>> async with heracles_core.LdapConnection(...) as conn:
+    ///     ...     entries = await conn.search("ou=users", "(objectClass=person)")
+    fn __aenter__<'py>(slf: PyRef<'py, Self>, py: Python<'py>) -> PyResult<&'py PyAny> {
+        let config = slf.config.clone();
+        let connection = slf.connection.clone();
+        let this: Py<Self> = slf.into();
 
-            Ok(())
+        pyo3_asyncio::tokio::future_into_py(py, async move {
+            open_connection(config, &connection).await?;
+            Ok(this)
         })
     }
 
-    /// Disconnect from the LDAP server.
-    fn disconnect<'py>(&self, py: Python<'py>) -> PyResult<&'py PyAny> {
+    /// Unbind on leaving an `async with` block, including when it raised.
+    ///
+    /// Unbind failures are only reported when the block itself succeeded,
+    /// so they never mask the original exception.
+    fn __aexit__<'py>(
+        &self,
+        py: Python<'py>,
+        exc_type: &PyAny,
+        _exc_value: &PyAny,
+        _traceback: &PyAny,
+    ) -> PyResult<&'py PyAny> {
         let connection = self.connection.clone();
+        let body_failed = !exc_type.is_none();
 
         pyo3_asyncio::tokio::future_into_py(py, async move {
-            let mut guard = connection.lock().await;
-            if let Some(mut conn) = guard.take() {
-                conn.unbind()
-                    .await
-                    .map_err(|e| PyRuntimeError::new_err(e.to_string()))?;
+            let result = release_connection(&connection, |mut conn: LdapConnection| async move {
+                conn.unbind().await
+            })
+            .await;
+            match result {
+                Err(_) if body_failed => Ok(false),
+                other => other.map(|_| false),
             }
-            Ok(())
         })
     }
 
@@ -250,11 +361,11 @@ impl PyLdapConnection {
     /// Search for LDAP entries.
     ///
     /// Args:
-    ///     base: Search base DN (relative to configured base_dn)
+    ///     base: Search base DN (empty for the configured base_dn)
     ///     filter: LDAP search filter
     ///     scope: Search scope ("base", "onelevel", "subtree")
     ///     attributes: List of attributes to return (None = all)
-    ///     size_limit: Maximum entries to return (0 = unlimited)
+    ///     size_limit: Maximum entries to return (0 = configured limit)
     #[pyo3(signature = (base, filter, scope="subtree", attributes=None, size_limit=0))]
     fn search<'py>(
         &self,
@@ -265,14 +376,20 @@ impl PyLdapConnection {
         attributes: Option<Vec<String>>,
         size_limit: i32,
     ) -> PyResult<&'py PyAny> {
-        let _ = size_limit; // TODO: implement size_limit support
         let connection = self.connection.clone();
         let search_scope = match scope {
-            "base" => ldap3::Scope::Base,
-            "onelevel" | "one" => ldap3::Scope::OneLevel,
-            _ => ldap3::Scope::Subtree,
+            "base" => SearchScope::Base,
+            "onelevel" | "one" => SearchScope::OneLevel,
+            _ => SearchScope::Subtree,
         };
         let attrs = attributes.unwrap_or_else(|| vec!["*".to_string()]);
+        let mut builder = SearchBuilder::new(base)
+            .scope(search_scope)
+            .filter(filter)
+            .attributes(attrs);
+        if size_limit > 0 {
+            builder = builder.size_limit(size_limit as usize);
+        }
 
         pyo3_asyncio::tokio::future_into_py(py, async move {
             let mut guard = connection.lock().await;
@@ -280,10 +397,8 @@ impl PyLdapConnection {
                 .as_mut()
                 .ok_or_else(|| PyConnectionError::new_err("Not connected"))?;
 
-            let attrs_ref: Vec<&str> = attrs.iter().map(|s| s.as_str()).collect();
-
             let entries = conn
-                .search(&base, search_scope, &filter, attrs_ref)
+                .search_with(&builder)
                 .await
                 .map_err(|e| PyRuntimeError::new_err(e.to_string()))?;
 
@@ -353,7 +468,8 @@ impl PyLdapConnection {
     /// Args:
     ///     dn: Distinguished name of the entry
     ///     modifications: List of (operation, attribute, values) tuples
-    ///                   operation: "add", "delete", "replace"
+    ///                   operation: "add", "delete", "replace", "increment"
+    ///                   ("increment" takes a single integer value)
     #[pyo3(signature = (dn, modifications))]
     fn modify<'py>(
         &self,
@@ -369,14 +485,25 @@ impl PyLdapConnection {
                 .as_mut()
                 .ok_or_else(|| PyConnectionError::new_err("Not connected"))?;
 
-            let mods: Vec<LdapModification> = modifications
+            let mods = modifications
                 .into_iter()
                 .map(|(op, attr, values)| match op.as_str() {
-                    "add" => LdapModification::add(attr, values),
-                    "delete" => LdapModification::delete(attr, values),
-                    _ => LdapModification::replace(attr, values),
+                    "add" => Ok(LdapModification::add(attr, values)),
+                    "delete" => Ok(LdapModification::delete(attr, values)),
+                    "increment" => match values.as_slice() {
+                        [by] => by
+                            .parse()
+                            .map(|by| LdapModification::increment(attr, by))
+                            .map_err(|_| {
+                                PyValueError::new_err(format!("Invalid increment: {}", by))
+                            }),
+                        _ => Err(PyValueError::new_err(
+                            "increment takes exactly one integer value",
+                        )),
+                    },
+                    _ => Ok(LdapModification::replace(attr, values)),
                 })
-                .collect();
+                .collect::<PyResult<Vec<_>>>()?;
 
             conn.modify(&dn, mods)
                 .await
@@ -386,6 +513,38 @@ impl PyLdapConnection {
         })
     }
 
+    /// Rename an entry and/or move it under a new parent.
+    ///
+    /// Args:
+    ///     dn: Distinguished name of the entry
+    ///     new_rdn: New RDN, e.g. "uid=jsmith"
+    ///     delete_old_rdn: Remove the old RDN value from the entry
+    ///     new_superior: DN of the new parent (must already exist)
+    #[pyo3(signature = (dn, new_rdn, delete_old_rdn=true, new_superior=None))]
+    fn modify_dn<'py>(
+        &self,
+        py: Python<'py>,
+        dn: String,
+        new_rdn: String,
+        delete_old_rdn: bool,
+        new_superior: Option<String>,
+    ) -> PyResult<&'py PyAny> {
+        let connection = self.connection.clone();
+
+        pyo3_asyncio::tokio::future_into_py(py, async move {
+            let mut guard = connection.lock().await;
+            let conn = guard
+                .as_mut()
+                .ok_or_else(|| PyConnectionError::new_err("Not connected"))?;
+
+            conn.modify_dn(&dn, &new_rdn, delete_old_rdn, new_superior.as_deref())
+                .await
+                .map_err(|e| PyRuntimeError::new_err(e.to_string()))?;
+
+            Ok(true)
+        })
+    }
+
     /// Delete an LDAP entry.
     #[pyo3(signature = (dn,))]
     fn delete<'py>(&self, py: Python<'py>, dn: String) -> PyResult<&'py PyAny> {
@@ -484,6 +643,27 @@ impl PyHashMethod {
         }
     }
 
+    #[staticmethod]
+    fn pbkdf2_sha256() -> Self {
+        Self {
+            inner: HashMethod::Pbkdf2Sha256,
+        }
+    }
+
+    #[staticmethod]
+    fn pbkdf2_sha512() -> Self {
+        Self {
+            inner: HashMethod::Pbkdf2Sha512,
+        }
+    }
+
+    #[staticmethod]
+    fn crypt() -> Self {
+        Self {
+            inner: HashMethod::Crypt,
+        }
+    }
+
     #[staticmethod]
     fn from_string(s: &str) -> PyResult<Self> {
         HashMethod::from_str(s)
@@ -491,6 +671,20 @@ impl PyHashMethod {
             .ok_or_else(|| PyValueError::new_err(format!("Unknown hash method: {}", s)))
     }
 
+    /// Every supported method, strongest first.
+    #[staticmethod]
+    fn all() -> Vec<Self> {
+        HashMethod::all()
+            .iter()
+            .map(|&inner| Self { inner })
+            .collect()
+    }
+
+    /// Security metadata as a dict, see `hash_methods()`.
+    fn describe(&self, py: Python<'_>) -> PyResult<PyObject> {
+        hash_method_info(py, self.inner)
+    }
+
     fn scheme(&self) -> &str {
         self.inner.scheme()
     }
@@ -514,27 +708,109 @@ impl PyHashMethod {
 ///     password: The password to hash.
 ///     method: The hash method to use (default: "argon2").
 ///             Supported: "ssha", "argon2", "bcrypt", "sha512", "ssha512",
-///                       "sha256", "ssha256", "md5", "smd5"
+///                       "sha256", "ssha256", "md5", "smd5", "crypt",
+///                       "pbkdf2_sha256", "pbkdf2_sha512"
+///     memory_kib: Argon2 memory cost in KiB (default: 19456).
+///     iterations: Argon2 iteration count (default: 2).
+///     parallelism: Argon2 degree of parallelism (default: 1).
+///     bcrypt_cost: bcrypt cost factor, 4-31 (default: 12).
 ///
 /// Returns:
 ///     The LDAP-formatted password hash (e.g., "{ARGON2}$argon2id$...").
 ///
+/// Raises:
+///     ValueError: If the method is unknown or a cost parameter is out of range.
+///
 /// Example:
 ///     >>> import heracles_core
 ///     >>> hash = heracles_core.hash_password("secret123")
 ///     >>> print(hash)
 ///     {ARGON2}$argon2id$v=19$m=19456,t=2,p=1$...
+///     >>> heracles_core.hash_password("secret123", memory_kib=1024, iterations=1)
+///     '{ARGON2}$argon2id$v=19$m=1024,t=1,p=1$...'
 #[pyfunction]
-#[pyo3(signature = (password, method="argon2"))]
-fn hash_password(password: &str, method: &str) -> PyResult<String> {
+#[pyo3(signature = (password, method="argon2", *, memory_kib=None, iterations=None, parallelism=None, bcrypt_cost=None))]
+fn hash_password(
+    password: &str,
+    method: &str,
+    memory_kib: Option<u32>,
+    iterations: Option<u32>,
+    parallelism: Option<u32>,
+    bcrypt_cost: Option<u32>,
+) -> PyResult<String> {
     let hash_method = HashMethod::from_str(method)
         .ok_or_else(|| PyValueError::new_err(format!("Unknown hash method: {}", method)))?;
+    let params = hash_params(memory_kib, iterations, parallelism, bcrypt_cost)?;
 
-    rust_hash_password(password, hash_method)
+    rust_hash_password_with_params(password, hash_method, &params)
         .map(|h| h.hash)
         .map_err(|e| PyRuntimeError::new_err(e.to_string()))
 }
 
+/// Builds and validates hashing cost parameters from Python keyword args.
+fn hash_params(
+    memory_kib: Option<u32>,
+    iterations: Option<u32>,
+    parallelism: Option<u32>,
+    bcrypt_cost: Option<u32>,
+) -> PyResult<HashParams> {
+    let argon2 = if memory_kib.is_some() || iterations.is_some() || parallelism.is_some() {
+        let defaults = Argon2Params::default();
+        Some(Argon2Params::new(
+            memory_kib.unwrap_or(defaults.m_cost),
+            iterations.unwrap_or(defaults.t_cost),
+            parallelism.unwrap_or(defaults.p_cost),
+        ))
+    } else {
+        None
+    };
+    let params = HashParams {
+        argon2,
+        bcrypt_cost,
+    };
+    params
+        .validate()
+        .map_err(|e| PyValueError::new_err(e.to_string()))?;
+    Ok(params)
+}
+
+/// Times a single hash with the given method and cost parameters.
+///
+/// Args:
+///     method: The hash method to time (default: "argon2").
+///     memory_kib: Argon2 memory cost in KiB (default: 19456).
+///     iterations: Argon2 iteration count (default: 2).
+///     parallelism: Argon2 degree of parallelism (default: 1).
+///     bcrypt_cost: bcrypt cost factor, 4-31 (default: 12).
+///
+/// Returns:
+///     The time taken in seconds, as a float.
+///
+/// Raises:
+///     ValueError: If the method is unknown or a cost parameter is out of range.
+///
+/// Example:
+///     >>> import heracles_core
+///     >>> heracles_core.benchmark_method("argon2", memory_kib=65536) > 0
+///     True
+#[pyfunction]
+#[pyo3(signature = (method="argon2", *, memory_kib=None, iterations=None, parallelism=None, bcrypt_cost=None))]
+fn benchmark_method(
+    method: &str,
+    memory_kib: Option<u32>,
+    iterations: Option<u32>,
+    parallelism: Option<u32>,
+    bcrypt_cost: Option<u32>,
+) -> PyResult<f64> {
+    let hash_method = HashMethod::from_str(method)
+        .ok_or_else(|| PyValueError::new_err(format!("Unknown hash method: {}", method)))?;
+    let params = hash_params(memory_kib, iterations, parallelism, bcrypt_cost)?;
+
+    rust_benchmark_method(hash_method, &params)
+        .map(|elapsed| elapsed.as_secs_f64())
+        .map_err(|e| PyRuntimeError::new_err(e.to_string()))
+}
+
 /// Verifies a password against an LDAP password hash.
 ///
 /// Args:
@@ -553,11 +829,38 @@ fn hash_password(password: &str, method: &str) -> PyResult<String> {
 ///     False
 #[pyfunction]
 fn verify_password(password: &str, hash: &str) -> PyResult<bool> {
-    let password_hash = PasswordHash::parse(hash)
+    rust_verify(password, hash).map_err(|e| match e {
+        HeraclesError::UnsupportedHashMethod(_) => {
+            PyValueError::new_err(format!("Invalid hash format: {}", e))
+        }
+        e => PyRuntimeError::new_err(e.to_string()),
+    })
+}
+
+/// Verifies a password and reports the detected hash method.
+///
+/// Args:
+///     password: The password to verify.
+///     hash: The LDAP password hash (e.g., "{SSHA}base64hash").
+///
+/// Returns:
+///     A dict with keys "matched" (bool), "method" (str) and "is_secure" (bool).
+///
+/// Example:
+///     >>> import heracles_core
+///     >>> hash = heracles_core.hash_password("secret123", "md5")
+///     >>> heracles_core.verify_password_detailed("secret123", hash)
+///     {'matched': True, 'method': 'md5', 'is_secure': False}
+#[pyfunction]
+fn verify_password_detailed(py: Python<'_>, password: &str, hash: &str) -> PyResult<PyObject> {
+    let result = rust_verify_password_detailed(password, hash)
         .map_err(|e| PyValueError::new_err(format!("Invalid hash format: {}", e)))?;
 
-    rust_verify_password(password, &password_hash)
-        .map_err(|e| PyRuntimeError::new_err(e.to_string()))
+    let dict = PyDict::new(py);
+    dict.set_item("matched", result.matched)?;
+    dict.set_item("method", hash_method_name(result.method))?;
+    dict.set_item("is_secure", result.is_secure)?;
+    Ok(dict.into())
 }
 
 /// Detects the hash method from an LDAP password hash.
@@ -574,18 +877,129 @@ fn verify_password(password: &str, hash: &str) -> PyResult<bool> {
 ///     'ssha'
 #[pyfunction]
 fn detect_hash_method(hash: &str) -> Option<String> {
-    HashMethod::detect(hash).map(|m| match m {
-        HashMethod::Ssha => "ssha".to_string(),
-        HashMethod::Argon2id => "argon2".to_string(),
-        HashMethod::Bcrypt => "bcrypt".to_string(),
-        HashMethod::Sha512 => "sha512".to_string(),
-        HashMethod::Ssha512 => "ssha512".to_string(),
-        HashMethod::Sha256 => "sha256".to_string(),
-        HashMethod::Ssha256 => "ssha256".to_string(),
-        HashMethod::Md5 => "md5".to_string(),
-        HashMethod::Smd5 => "smd5".to_string(),
-        HashMethod::Plain => "plain".to_string(),
-    })
+    HashMethod::detect(hash).map(|m| hash_method_name(m).to_string())
+}
+
+/// Checks whether a stored hash should be re-hashed with `method`.
+///
+/// Call after a successful verify to migrate legacy or low-cost hashes.
+///
+/// Args:
+///     hash: The stored LDAP password hash.
+///     method: The target hash method (default: "argon2").
+///
+/// Returns:
+///     True if the hash uses another method or weaker cost parameters.
+///
+/// Raises:
+///     ValueError: If the method is unknown or the hash format is invalid.
+///
+/// Example:
+///     >>> import heracles_core
+///     >>> heracles_core.needs_rehash("{SSHA}base64hash", "argon2")
+///     True
+#[pyfunction]
+#[pyo3(signature = (hash, method="argon2"))]
+fn needs_rehash(hash: &str, method: &str) -> PyResult<bool> {
+    let target = HashMethod::from_str(method)
+        .ok_or_else(|| PyValueError::new_err(format!("Unknown hash method: {}", method)))?;
+    let parsed = PasswordHash::parse(hash)
+        .map_err(|e| PyValueError::new_err(format!("Invalid hash format: {}", e)))?;
+    Ok(rust_needs_rehash(&parsed, target))
+}
+
+/// Classifies each value of a userPassword attribute.
+///
+/// Args:
+///     values: All userPassword values of an entry.
+///
+/// Returns:
+///     One dict per value with keys "index", "method" (None for an
+///     unrecognized scheme) and "is_secure".
+///
+/// Example:
+///     >>> import heracles_core
+///     >>> heracles_core.classify_userpassword(["{MD5}X03MO1qnZdYdgyfeuILPmQ==", "secret"])
+///     [{'index': 0, 'method': 'md5', 'is_secure': False}, {'index': 1, 'method': 'plain', 'is_secure': False}]
+#[pyfunction]
+fn classify_userpassword(py: Python<'_>, values: Vec<String>) -> PyResult<Vec<PyObject>> {
+    rust_classify_userpassword(&values)
+        .into_iter()
+        .map(|c| {
+            let dict = PyDict::new(py);
+            dict.set_item("index", c.index)?;
+            dict.set_item("method", c.method.map(hash_method_name))?;
+            dict.set_item("is_secure", c.is_secure)?;
+            Ok(dict.into())
+        })
+        .collect()
+}
+
+/// Computes the Samba NT hash (sambaNTPassword) of a password.
+///
+/// Args:
+///     password: The plain text password.
+///
+/// Returns:
+///     The MD4 hash of the UTF-16LE password as 32 uppercase hex
+///     characters, without a {SCHEME} prefix.
+///
+/// Example:
+///     >>> import heracles_core
+///     >>> heracles_core.nt_hash("password")
+///     '8846F7EAEE8FB117AD06BDD830B7586C'
+#[pyfunction]
+fn nt_hash(password: &str) -> String {
+    rust_nt_hash(password)
+}
+
+/// Lists every supported hash method with its security metadata.
+///
+/// Returns:
+///     One dict per method, strongest first, with keys "name", "scheme",
+///     "is_secure", "is_salted" and "is_legacy".
+///
+/// Example:
+///     >>> import heracles_core
+///     >>> heracles_core.hash_methods()[0]
+///     {'name': 'argon2', 'scheme': '{ARGON2}', 'is_secure': True, 'is_salted': True, 'is_legacy': False}
+#[pyfunction]
+fn hash_methods(py: Python<'_>) -> PyResult<Vec<PyObject>> {
+    HashMethod::all()
+        .iter()
+        .map(|&method| hash_method_info(py, method))
+        .collect()
+}
+
+/// Builds the metadata dict for a hash method.
+fn hash_method_info(py: Python<'_>, method: HashMethod) -> PyResult<PyObject> {
+    let info = method.describe();
+    let dict = PyDict::new(py);
+    dict.set_item("name", hash_method_name(method))?;
+    dict.set_item("scheme", info.scheme)?;
+    dict.set_item("is_secure", info.secure)?;
+    dict.set_item("is_salted", info.salted)?;
+    dict.set_item("is_legacy", info.legacy)?;
+    Ok(dict.into())
+}
+
+/// Python-facing name of a hash method.
+fn hash_method_name(method: HashMethod) -> &'static str {
+    match method {
+        HashMethod::Ssha => "ssha",
+        HashMethod::Argon2id => "argon2",
+        HashMethod::Bcrypt => "bcrypt",
+        HashMethod::Sha512 => "sha512",
+        HashMethod::Ssha512 => "ssha512",
+        HashMethod::Sha256 => "sha256",
+        HashMethod::Ssha256 => "ssha256",
+        HashMethod::Md5 => "md5",
+        HashMethod::Smd5 => "smd5",
+        HashMethod::Plain => "plain",
+        HashMethod::Pbkdf2Sha256 => "pbkdf2_sha256",
+        HashMethod::Pbkdf2Sha512 => "pbkdf2_sha512",
+        HashMethod::Crypt => "crypt",
+    }
 }
 
 /// Escapes a value for use in a Distinguished Name (DN).
@@ -1158,6 +1572,56 @@ mod tests {
         assert!(method.is_secure());
     }
 
+    #[test]
+    fn test_hash_password_low_params() {
+        let hash = hash_password("secret", "argon2", Some(1024), Some(1), Some(1), None).unwrap();
+        assert!(hash.contains("m=1024,t=1,p=1"));
+        assert!(verify_password("secret", &hash).unwrap());
+
+        let hash = hash_password("secret", "bcrypt", None, None, None, Some(4)).unwrap();
+        assert!(verify_password("secret", &hash).unwrap());
+    }
+
+    #[test]
+    fn test_hash_password_invalid_params() {
+        assert!(hash_password("secret", "argon2", None, Some(0), None, None).is_err());
+        assert!(hash_password("secret", "bcrypt", None, None, None, Some(3)).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_release_connection_closes_and_empties_slot() {
+        let slot = Mutex::new(Some(7u32));
+        let mut closed = None;
+
+        release_connection(&slot, |conn| {
+            closed = Some(conn);
+            async { Ok(()) }
+        })
+        .await
+        .unwrap();
+
+        assert_eq!(closed, Some(7));
+        assert!(slot.lock().await.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_release_connection_empties_slot_on_error() {
+        let slot = Mutex::new(Some(7u32));
+
+        let result = release_connection(&slot, |_| async {
+            Err(crate::errors::HeraclesError::LdapConnection("gone".into()))
+        })
+        .await;
+
+        assert!(result.is_err());
+        assert!(slot.lock().await.is_none());
+
+        // Nothing left to close on a second exit.
+        release_connection(&slot, |_: u32| async { panic!("already released") })
+            .await
+            .unwrap();
+    }
+
     #[test]
     fn test_py_permission_bitmap() {
         let bitmap = PyPermissionBitmap::from_bits(vec![0, 1, 2]);